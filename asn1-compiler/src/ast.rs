@@ -0,0 +1,155 @@
+use std::ops;
+
+/// An ASN.1 module.
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub name: String,
+    pub assignments: Vec<Assignment>,
+}
+
+/// A type or value assignment at the top level of a module.
+#[derive(Debug, Clone)]
+pub enum Assignment {
+    /// `Name {Params} ::= Type`
+    Type {
+        name: String,
+        params: Vec<Param>,
+        ty: Type,
+    },
+    /// `name Type ::= Value`
+    Value {
+        name: String,
+        ty: Type,
+        value: Value,
+    },
+}
+
+/// A formal parameter of a parameterized type assignment.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Param {
+    /// A type parameter, such as `{Item}`.
+    Type(String),
+    /// A value parameter, such as `{INTEGER:maxLen}` or `{maxLen}`.
+    Value(String),
+}
+
+/// An actual parameter given when referencing a parameterized type.
+#[derive(Debug, Clone)]
+pub enum Arg {
+    Type(Box<Type>),
+    Value(Value),
+}
+
+/// A type along with any tag and constraint applied to it.
+#[derive(Debug, Clone)]
+pub struct Type {
+    pub kind: TypeKind,
+    pub tag: Option<Tag>,
+    pub constraint: Constraint,
+}
+
+/// A tag such as `[APPLICATION 1]`, which is only visible to PER in the order of the components of a SET.
+#[derive(Debug, Clone)]
+pub struct Tag {
+    /// `UNIVERSAL`, `APPLICATION` or `PRIVATE`, or `None` for a context-specific tag.
+    pub class: Option<String>,
+    pub number: Value,
+}
+
+#[derive(Debug, Clone)]
+pub enum TypeKind {
+    Boolean,
+    Null,
+    Integer,
+    Real,
+    BitString,
+    OctetString,
+    /// A restricted character string type, such as `IA5String`, by its ASN.1 name.
+    CharString(String),
+    ObjectIdentifier,
+    RelativeOid,
+    /// A time type, such as `GeneralizedTime` or `DATE`, by its ASN.1 name.
+    Time(String),
+    Enumerated(Items<Item>),
+    Sequence(Items<Component>),
+    SequenceOf(Box<Type>),
+    Set(Items<Component>),
+    SetOf(Box<Type>),
+    Choice(Items<Component>),
+    /// A reference to another type, with the actual parameters of a parameterized type.
+    Reference(String, Vec<Arg>),
+}
+
+/// The root items of a type with an optional extension marker, and the extension additions that follow it.
+#[derive(Debug, Clone)]
+pub struct Items<T> {
+    pub root: Vec<T>,
+    pub extensible: bool,
+    pub additions: Vec<T>,
+    /// The ranges of `additions` that are extension addition groups (`[[ ... ]]`).
+    pub groups: Vec<ops::Range<usize>>,
+}
+
+/// An item of an ENUMERATED type.
+#[derive(Debug, Clone)]
+pub struct Item {
+    pub name: String,
+    pub value: Option<i64>,
+}
+
+/// A component of a SEQUENCE, or an alternative of a CHOICE.
+#[derive(Debug, Clone)]
+pub struct Component {
+    pub name: String,
+    pub ty: Type,
+    pub optional: bool,
+    pub default: Option<Value>,
+}
+
+/// The PER-visible parts of a constraint.
+#[derive(Debug, Clone, Default)]
+pub struct Constraint {
+    pub value: Option<Range>,
+    pub size: Option<Range>,
+    pub alphabet: Option<Alphabet>,
+    pub real: Option<RealComponents>,
+}
+
+impl Constraint {
+    /// Check if there are no PER-visible constraints at all.
+    pub fn is_empty(&self) -> bool {
+        self.value.is_none() && self.size.is_none() && self.alphabet.is_none() && self.real.is_none()
+    }
+}
+
+/// An inclusive range of values, where `None` stands for `MIN` or `MAX`, which is extensible if it's followed by
+/// an extension marker.
+#[derive(Debug, Clone)]
+pub struct Range {
+    pub min: Option<Value>,
+    pub max: Option<Value>,
+    pub extensible: bool,
+}
+
+/// A permitted alphabet, as in `FROM ("0123456789")` or `FROM ("A".."Z")`.
+#[derive(Debug, Clone)]
+pub enum Alphabet {
+    Chars(String),
+    Range(char, char),
+}
+
+/// The limits on the components of a REAL in a `WITH COMPONENTS` constraint.
+#[derive(Debug, Clone, Default)]
+pub struct RealComponents {
+    pub mantissa: Option<Range>,
+    pub base: Option<Value>,
+    pub exponent: Option<Range>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Integer(i64),
+    Boolean(bool),
+    /// A reference to a value assignment, a value parameter or an enumerated item.
+    Reference(String),
+}
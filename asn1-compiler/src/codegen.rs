@@ -0,0 +1,716 @@
+use ast::*;
+use std::collections::{HashMap, HashSet};
+use Error;
+
+const KEYWORDS: &[&str] = &["abstract", "as", "async", "await", "become", "box", "break", "const",
+                                            "continue", "crate", "do", "dyn", "else", "enum", "extern", "false",
+                                            "final", "fn", "for", "if", "impl", "in", "let", "loop", "macro",
+                                            "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+                                            "return", "self", "static", "struct", "super", "trait", "true", "try",
+                                            "type", "typeof", "unsafe", "unsized", "use", "virtual", "where",
+                                            "while", "yield"];
+
+/// Built-in types that have no `APerElement` implementation.
+const UNSUPPORTED_TYPES: &[&str] = &["VideotexString", "ObjectDescriptor", "TIME", "EXTERNAL", "EMBEDDED",
+                                                     "CHARACTER", "ANY"];
+
+/// Convert an ASN.1 type reference such as `Foo-Bar` to a Rust type name such as `FooBar`.
+pub fn type_name(s: &str) -> String {
+    let mut ret = String::with_capacity(s.len());
+    let mut upper = true;
+    for c in s.chars() {
+        if c == '-' || c == '_' {
+            upper = true;
+        } else if upper {
+            ret.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            ret.push(c);
+        }
+    }
+    ret
+}
+
+/// Convert an ASN.1 identifier such as `fooBar-baz` to a Rust field name such as `foo_bar_baz`.
+pub fn field_name(s: &str) -> String {
+    let mut ret = String::with_capacity(s.len() + 4);
+    let mut prev: Option<char> = None;
+    for c in s.chars() {
+        if c == '-' {
+            ret.push('_');
+        } else {
+            if c.is_uppercase() && prev.is_some_and(|p| p.is_lowercase() || p.is_ascii_digit()) {
+                ret.push('_');
+            }
+            ret.extend(c.to_lowercase());
+        }
+        prev = Some(c);
+    }
+    if KEYWORDS.contains(&ret.as_str()) {
+        ret.push('_');
+    }
+    ret
+}
+
+/// Convert an ASN.1 value reference such as `maxnoofCells` to a Rust constant name such as `MAXNOOF_CELLS`.
+pub fn const_name(s: &str) -> String {
+    field_name(s).trim_end_matches('_').to_uppercase()
+}
+
+/// Build a `#[aper(...)]` constraint argument such as `value(0..255)` or `size(1..4, ...)`.
+fn constraint_arg(kind: &str, min: Option<i64>, max: Option<i64>, extensible: bool) -> String {
+    let range = match (min, max) {
+        (Some(l), Some(h)) if l == h => format!("{}", l),
+        (Some(l), Some(h)) => format!("{}..{}", l, h),
+        (Some(l), None) => format!("{}..", l),
+        (None, Some(h)) => format!("..{}", h),
+        (None, None) => "..".to_string(),
+    };
+    if extensible {
+        format!("{}({}, ...)", kind, range)
+    } else {
+        format!("{}({})", kind, range)
+    }
+}
+
+/// Build a `#[aper(...)]` permitted alphabet argument such as `from("0123")` or `from('A'..'Z')`.
+fn alphabet_arg(alphabet: &Alphabet) -> String {
+    match *alphabet {
+        Alphabet::Chars(ref s) => format!("from({:?})", s),
+        Alphabet::Range(a, b) => format!("from({:?}..{:?})", a, b),
+    }
+}
+
+/// Get the Rust type of the restricted character string type `name`, and whether it's a known-multiplier type, i.e.
+/// one whose constraints are visible to PER.
+fn char_string_type(name: &str) -> (&'static str, bool) {
+    match name {
+        "IA5String" => ("::asn1::Ia5String", true),
+        "PrintableString" => ("::asn1::PrintableString", true),
+        "NumericString" => ("::asn1::NumericString", true),
+        "BMPString" => ("::asn1::BmpString", true),
+        "UniversalString" => ("::asn1::UniversalString", true),
+        "UTF8String" => ("::asn1::Utf8String", false),
+        "GraphicString" => ("::asn1::GraphicString", false),
+        "GeneralString" => ("::asn1::GeneralString", false),
+        "TeletexString" | "T61String" => ("::asn1::TeletexString", false),
+        // VisibleString, or ISO646String as it was once known
+        _ => ("::asn1::VisibleString", true),
+    }
+}
+
+/// Get the Rust type of the time type `name`.
+fn time_type(name: &str) -> &'static str {
+    match name {
+        "UTCTime" => "::asn1::UtcTime",
+        "GeneralizedTime" => "::asn1::GeneralizedTime",
+        "DATE" => "::asn1::Date",
+        "TIME-OF-DAY" => "::asn1::TimeOfDay",
+        "DATE-TIME" => "::asn1::DateTime",
+        _ => "::asn1::Duration",
+    }
+}
+
+/// Build the `#[aper(...)]` attribute for a list of arguments, if there are any.
+fn aper_attr(args: &[String]) -> String {
+    if args.is_empty() {
+        String::new()
+    } else {
+        format!("#[aper({})] ", args.join(", "))
+    }
+}
+
+/// Emits Rust types for the assignments of one or more modules.
+pub struct Generator<'a> {
+    types: HashMap<&'a str, (&'a [Param], &'a Type)>,
+    values: HashMap<&'a str, &'a Value>,
+    /// The items of each ENUMERATED type that has been emitted, keyed by Rust type name.
+    enums: HashMap<String, Vec<String>>,
+    defined: HashSet<String>,
+    out: String,
+}
+
+impl<'a> Generator<'a> {
+    /// Construct a new `Generator` for `modules`.
+    pub fn new(modules: &'a [Module]) -> Generator<'a> {
+        let mut types = HashMap::new();
+        let mut values = HashMap::new();
+        for m in modules {
+            for a in &m.assignments {
+                match *a {
+                    Assignment::Type { ref name, ref params, ref ty } => {
+                        types.insert(name.as_str(), (params.as_slice(), ty));
+                    }
+                    Assignment::Value { ref name, ref value, .. } => {
+                        values.insert(name.as_str(), value);
+                    }
+                }
+            }
+        }
+        Generator {
+            types,
+            values,
+            enums: HashMap::new(),
+            defined: HashSet::new(),
+            out: String::new(),
+        }
+    }
+
+    /// Generate the Rust source for every assignment of `modules`.
+    pub fn generate(mut self, modules: &'a [Module]) -> Result<String, Error> {
+        for m in modules {
+            self.out.push_str(&format!("// Types generated from the ASN.1 module {}\n", m.name));
+            for a in &m.assignments {
+                match *a {
+                    Assignment::Value { ref name, ref ty, ref value } => {
+                        match (&ty.kind, value) {
+                            (&TypeKind::Boolean, &Value::Boolean(b)) => {
+                                self.out.push_str(&format!("\npub const {}: bool = {};\n", const_name(name), b));
+                            }
+                            // Other values, such as enumerated items, have no constant of their own
+                            _ => {
+                                if let Ok(v) = self.int(value) {
+                                    self.out.push_str(&format!("\npub const {}: i64 = {};\n", const_name(name), v));
+                                }
+                            }
+                        }
+                    }
+                    Assignment::Type { ref name, ref params, ref ty } => {
+                        if params.is_empty() {
+                            self.define(&type_name(name), ty)?;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(self.out)
+    }
+
+    /// Resolve an integer value.
+    fn int(&self, v: &Value) -> Result<i64, Error> {
+        let mut v = v;
+        // Follow chains of value references, but not forever
+        for _ in 0..32 {
+            match *v {
+                Value::Integer(n) => return Ok(n),
+                Value::Boolean(_) => return Err(Error::Semantic("expected an integer, found a boolean".to_string())),
+                Value::Reference(ref r) => {
+                    match self.values.get(r.as_str()) {
+                        Some(next) => v = next,
+                        None => return Err(Error::Undefined(r.clone())),
+                    }
+                }
+            }
+        }
+        Err(Error::Semantic("value references are circular".to_string()))
+    }
+
+    /// Resolve the bounds of a range, and whether it's extensible.
+    fn bounds(&self, r: &Option<Range>) -> Result<(Option<i64>, Option<i64>, bool), Error> {
+        match *r {
+            Some(ref r) => {
+                let min = match r.min {
+                    Some(ref v) => Some(self.int(v)?),
+                    None => None,
+                };
+                let max = match r.max {
+                    Some(ref v) => Some(self.int(v)?),
+                    None => None,
+                };
+                Ok((min, max, r.extensible))
+            }
+            None => Ok((None, None, false)),
+        }
+    }
+
+    /// Build a `with_components(...)` argument from the limits on the components of a REAL.
+    fn real_components(&self, real: &RealComponents) -> Result<String, Error> {
+        let mut args = Vec::new();
+        if real.mantissa.is_some() {
+            let (min, max, _) = self.bounds(&real.mantissa)?;
+            args.push(constraint_arg("mantissa", min, max, false));
+        }
+        if let Some(ref base) = real.base {
+            args.push(format!("base({})", self.int(base)?));
+        }
+        if real.exponent.is_some() {
+            let (min, max, _) = self.bounds(&real.exponent)?;
+            args.push(constraint_arg("exponent", min, max, false));
+        }
+        Ok(format!("with_components({})", args.join(", ")))
+    }
+
+    /// Get the Rust type and `#[aper(...)]` arguments of a component of type `ty`, defining any types that it needs
+    /// along the way. Types that have to be named are named `hint`.
+    fn field(&mut self, ty: &Type, hint: &str) -> Result<(String, Vec<String>), Error> {
+        match ty.kind {
+            TypeKind::Boolean => Ok(("bool".to_string(), Vec::new())),
+            TypeKind::Null => Ok(("()".to_string(), Vec::new())),
+            TypeKind::Integer => {
+                let (min, max, extensible) = self.bounds(&ty.constraint.value)?;
+                let t = match (min, max) {
+                    // A value outside of the root can be any integer
+                    _ if extensible => "i64",
+                    (Some(l), Some(h)) if l >= 0 && h <= 0xff => "u8",
+                    (Some(l), Some(h)) if l >= 0 && h <= 0xffff => "u16",
+                    (Some(l), Some(h)) if l >= 0 && h <= 0xffff_ffff => "u32",
+                    (Some(l), Some(h)) if l >= -0x80 && h <= 0x7f => "i8",
+                    (Some(l), Some(h)) if l >= -0x8000 && h <= 0x7fff => "i16",
+                    (Some(l), Some(h)) if l >= -0x8000_0000 && h <= 0x7fff_ffff => "i32",
+                    (Some(l), Some(_)) if l >= 0 => "u64",
+                    (Some(_), Some(_)) => "i64",
+                    (Some(l), None) if l >= 0 => "u64",
+                    _ => "i64",
+                };
+                Ok((t.to_string(), vec![constraint_arg("value", min, max, extensible)]))
+            }
+            TypeKind::Real => {
+                let args = match ty.constraint.real {
+                    Some(ref real) => vec![self.real_components(real)?],
+                    None => Vec::new(),
+                };
+                Ok(("f64".to_string(), args))
+            }
+            TypeKind::BitString => {
+                let (min, max, extensible) = self.bounds(&ty.constraint.size)?;
+                Ok(("::asn1::BitString".to_string(), vec![constraint_arg("size", min, max, extensible)]))
+            }
+            TypeKind::OctetString => {
+                let (min, max, extensible) = self.bounds(&ty.constraint.size)?;
+                Ok(("::asn1::OctetString".to_string(), vec![constraint_arg("size", min, max, extensible)]))
+            }
+            TypeKind::CharString(ref name) => {
+                let (t, known_multiplier) = char_string_type(name);
+                if !known_multiplier {
+                    return Ok((t.to_string(), Vec::new()));
+                }
+                let mut args: Vec<String> = ty.constraint.alphabet.iter().map(alphabet_arg).collect();
+                if ty.constraint.size.is_some() {
+                    let (min, max, extensible) = self.bounds(&ty.constraint.size)?;
+                    args.push(constraint_arg("size", min, max, extensible));
+                }
+                Ok((t.to_string(), args))
+            }
+            TypeKind::ObjectIdentifier => Ok(("::asn1::ObjectIdentifier".to_string(), Vec::new())),
+            TypeKind::RelativeOid => Ok(("::asn1::RelativeOid".to_string(), Vec::new())),
+            TypeKind::Time(ref name) => Ok((time_type(name).to_string(), Vec::new())),
+            TypeKind::SequenceOf(ref el) | TypeKind::SetOf(ref el) => {
+                let (min, max, extensible) = self.bounds(&ty.constraint.size)?;
+                let mut args = vec![constraint_arg("size", min, max, extensible)];
+                let el_ty = match el.kind {
+                    // The size constraint of a string element is passed as the value constraint of the `Vec`, which
+                    // leaves no room for a permitted alphabet
+                    TypeKind::BitString | TypeKind::OctetString | TypeKind::CharString(_)
+                        if el.constraint.alphabet.is_none() => {
+                        let (t, el_args) = self.field(el, hint)?;
+                        args.extend(el_args.iter().map(|a| a.replacen("size", "value", 1)));
+                        t
+                    }
+                    TypeKind::Boolean | TypeKind::Null | TypeKind::ObjectIdentifier | TypeKind::RelativeOid |
+                    TypeKind::Time(_) | TypeKind::Reference(..) => self.field(el, hint)?.0,
+                    TypeKind::Real if el.constraint.real.is_none() => self.field(el, hint)?.0,
+                    // Anything else can't get its constraints through the `Vec`, so it gets a type of its own
+                    _ => {
+                        let name = format!("{}Item", hint);
+                        self.define(&name, el)?;
+                        name
+                    }
+                };
+                match ty.kind {
+                    TypeKind::SetOf(_) => Ok((format!("::asn1::SetOf<{}>", el_ty), args)),
+                    _ => Ok((format!("Vec<{}>", el_ty), args)),
+                }
+            }
+            TypeKind::Enumerated(_) | TypeKind::Sequence(_) | TypeKind::Set(_) | TypeKind::Choice(_) => {
+                self.define(hint, ty)?;
+                Ok((hint.to_string(), Vec::new()))
+            }
+            TypeKind::Reference(ref name, ref args) => {
+                if !ty.constraint.is_empty() {
+                    return Err(Error::Unsupported(format!("constraint on reference to {} in {}", name, hint)));
+                }
+                Ok((self.reference(name, args)?, Vec::new()))
+            }
+        }
+    }
+
+    /// Get the Rust type for a reference to the type `name`, instantiating it if it's parameterized.
+    fn reference(&mut self, name: &str, args: &[Arg]) -> Result<String, Error> {
+        let (params, ty) = match self.types.get(name) {
+            Some(&(params, ty)) => (params, ty),
+            None => {
+                if UNSUPPORTED_TYPES.contains(&name) {
+                    return Err(Error::Unsupported(format!("the {} type", name)));
+                }
+                // Assume the type is imported from another module
+                return Ok(type_name(name));
+            }
+        };
+
+        if params.len() != args.len() {
+            return Err(Error::Semantic(format!("{} takes {} parameters but was given {}",
+                                               name,
+                                               params.len(),
+                                               args.len())));
+        }
+        if params.is_empty() {
+            let rust_name = type_name(name);
+            self.define(&rust_name, ty)?;
+            return Ok(rust_name);
+        }
+
+        let mut rust_name = type_name(name);
+        let mut subst: HashMap<&str, &Arg> = HashMap::new();
+        for (param, arg) in params.iter().zip(args.iter()) {
+            match (param, arg) {
+                (Param::Type(p), Arg::Type(t)) => {
+                    subst.insert(p.as_str(), arg);
+                    rust_name.push_str(&label(t));
+                }
+                (Param::Value(p), Arg::Value(v)) => {
+                    subst.insert(p.as_str(), arg);
+                    match *v {
+                        Value::Integer(n) if n < 0 => rust_name.push_str(&format!("Minus{}", -n)),
+                        Value::Integer(n) => rust_name.push_str(&n.to_string()),
+                        Value::Boolean(b) => rust_name.push_str(if b { "True" } else { "False" }),
+                        Value::Reference(ref r) => rust_name.push_str(&type_name(r)),
+                    }
+                }
+                _ => return Err(Error::Semantic(format!("mismatched parameter for {}", name))),
+            }
+        }
+        let instance = substitute(ty, &subst);
+        self.define(&rust_name, &instance)?;
+        Ok(rust_name)
+    }
+
+    /// Emit the definition of a Rust type `name` for `ty`, unless it has been emitted already.
+    fn define(&mut self, name: &str, ty: &Type) -> Result<(), Error> {
+        if !self.defined.insert(name.to_string()) {
+            return Ok(());
+        }
+        let def = match ty.kind {
+            TypeKind::Sequence(ref items) => self.sequence(name, items, false)?,
+            TypeKind::Set(ref items) => self.sequence(name, items, true)?,
+            TypeKind::Choice(ref items) => self.choice(name, items)?,
+            TypeKind::Enumerated(ref items) => self.enumerated(name, items)?,
+            _ => {
+                let (t, args) = self.field(ty, name)?;
+                format!("#[derive(APerElement, Debug)]\npub struct {}({}pub {});\n", name, aper_attr(&args), t)
+            }
+        };
+        self.out.push('\n');
+        self.out.push_str(&def);
+        Ok(())
+    }
+
+    /// The struct for a SEQUENCE, or a SET if `set` is true.
+    fn sequence(&mut self, name: &str, items: &Items<Component>, set: bool) -> Result<String, Error> {
+        let mut fields = String::new();
+        for c in &items.root {
+            fields.push_str(&self.component(name, c, false, set)?);
+        }
+
+        // Each extension addition group becomes a struct of its own, which is a single addition
+        let mut i = 0;
+        let mut num_groups = 0;
+        while i < items.additions.len() {
+            match items.groups.iter().find(|g| g.start == i) {
+                Some(g) => {
+                    num_groups += 1;
+                    let group_name = format!("{}ExtGroup{}", name, num_groups);
+                    let group = Type {
+                        kind: TypeKind::Sequence(Items {
+                            root: items.additions[g.clone()].to_vec(),
+                            extensible: false,
+                            additions: Vec::new(),
+                            groups: Vec::new(),
+                        }),
+                        tag: None,
+                        constraint: Constraint::default(),
+                    };
+                    self.define(&group_name, &group)?;
+                    fields.push_str(&format!("    #[aper(extension)]\n    pub ext_group_{}: Option<{}>,\n",
+                                             num_groups,
+                                             group_name));
+                    i = g.end;
+                }
+                None => {
+                    fields.push_str(&self.component(name, &items.additions[i], true, false)?);
+                    i += 1;
+                }
+            }
+        }
+
+        let mut ret = "#[derive(APerElement, Debug)]\n".to_string();
+        match (set, items.extensible) {
+            (true, true) => ret.push_str("#[aper(set, extensible)]\n"),
+            (true, false) => ret.push_str("#[aper(set)]\n"),
+            (false, true) => ret.push_str("#[aper(extensible)]\n"),
+            (false, false) => {}
+        }
+        if fields.is_empty() {
+            ret.push_str(&format!("pub struct {};\n", name));
+        } else {
+            ret.push_str(&format!("pub struct {} {{\n{}}}\n", name, fields));
+        }
+        Ok(ret)
+    }
+
+    /// The field for the component `c` of the SEQUENCE or SET `name`. An extension addition is always an `Option`,
+    /// since it's absent from the encodings of older versions, so it never has a default. If `tagged`, the component's
+    /// tag is given with `tag(...)`, as for the root components of a SET.
+    fn component(&mut self, name: &str, c: &Component, extension: bool, tagged: bool) -> Result<String, Error> {
+        let (mut t, mut args) = self.field(&c.ty, &format!("{}{}", name, type_name(&c.name)))?;
+        if extension {
+            args.insert(0, "extension".to_string());
+        }
+        if let (true, Some(tag)) = (tagged, c.ty.tag.as_ref()) {
+            args.insert(0, self.tag_arg(tag)?);
+        }
+        if let Some(ref default) = c.default {
+            if !extension {
+                args.push(format!("default = {}", self.default(&t, &c.ty, default)?));
+            }
+        }
+        if c.optional || extension {
+            t = format!("Option<{}>", t);
+        }
+
+        let mut ret = String::new();
+        let attr = aper_attr(&args);
+        if !attr.is_empty() {
+            ret.push_str(&format!("    {}\n", attr.trim_end()));
+        }
+        ret.push_str(&format!("    pub {}: {},\n", field_name(&c.name), t));
+        Ok(ret)
+    }
+
+    /// Build a `#[aper(...)]` tag argument such as `tag(1)` or `tag(application 1)`.
+    fn tag_arg(&self, tag: &Tag) -> Result<String, Error> {
+        let number = self.int(&tag.number)?;
+        match tag.class {
+            Some(ref class) => Ok(format!("tag({} {})", class.to_lowercase(), number)),
+            None => Ok(format!("tag({})", number)),
+        }
+    }
+
+    /// The Rust expression for the DEFAULT `value` of a component of type `ty`.
+    fn default(&self, rust_ty: &str, ty: &Type, value: &Value) -> Result<String, Error> {
+        match (&ty.kind, value) {
+            (&TypeKind::Boolean, &Value::Boolean(b)) => Ok(b.to_string()),
+            (&TypeKind::Integer, v) => Ok(self.int(v)?.to_string()),
+            (_, Value::Reference(r)) if self.enums.get(rust_ty).is_some_and(|i| i.contains(r)) => {
+                Ok(format!("{}::{}", rust_ty, type_name(r)))
+            }
+            _ => Err(Error::Unsupported(format!("DEFAULT value {:?} for {}", value, rust_ty))),
+        }
+    }
+
+    fn choice(&mut self, name: &str, items: &Items<Component>) -> Result<String, Error> {
+        let mut variants = String::new();
+        for (i, c) in items.root.iter().chain(items.additions.iter()).enumerate() {
+            let variant = type_name(&c.name);
+            if i >= items.root.len() {
+                variants.push_str("    #[aper(extension)]\n");
+            }
+            // A NULL alternative is a unit variant
+            if let TypeKind::Null = c.ty.kind {
+                variants.push_str(&format!("    {},\n", variant));
+                continue;
+            }
+            let (t, args) = self.field(&c.ty, &format!("{}{}", name, variant))?;
+            variants.push_str(&format!("    {}({}{}),\n", variant, aper_attr(&args), t));
+        }
+
+        let mut ret = "#[derive(APerElement, Debug)]\n".to_string();
+        if items.extensible {
+            ret.push_str("#[aper(extensible)]\n");
+        }
+        ret.push_str(&format!("pub enum {} {{\n{}}}\n", name, variants));
+        Ok(ret)
+    }
+
+    fn enumerated(&mut self, name: &str, items: &Items<Item>) -> Result<String, Error> {
+        let root = enumeration(&items.root);
+        let additions = enumeration_additions(&root, &items.additions);
+        self.enums.insert(name.to_string(),
+                          root.iter().chain(additions.iter()).map(|(n, _)| n.clone()).collect());
+
+        let mut ret = String::new();
+        ret.push_str("#[derive(APerElement, Debug, Clone, Copy, PartialEq, Eq)]\n");
+        if items.extensible && additions.is_empty() {
+            ret.push_str("#[aper(enumerated, extensible)]\n");
+        } else {
+            ret.push_str("#[aper(enumerated)]\n");
+        }
+        ret.push_str(&format!("pub enum {} {{\n", name));
+        for &(ref n, v) in &root {
+            ret.push_str(&format!("    {} = {},\n", type_name(n), v));
+        }
+        for &(ref n, v) in &additions {
+            ret.push_str("    #[aper(extension)]\n");
+            ret.push_str(&format!("    {} = {},\n", type_name(n), v));
+        }
+        ret.push_str("}\n");
+        Ok(ret)
+    }
+}
+
+/// Assign values to the root items of an enumeration that don't have one, and sort the items by value, which is the
+/// order of their indexes.
+fn enumeration(items: &[Item]) -> Vec<(String, i64)> {
+    let used: HashSet<i64> = items.iter().filter_map(|i| i.value).collect();
+    let mut next = 0;
+    let mut ret: Vec<(String, i64)> = items.iter()
+        .map(|i| {
+            let v = match i.value {
+                Some(v) => v,
+                None => {
+                    while used.contains(&next) {
+                        next += 1;
+                    }
+                    next += 1;
+                    next - 1
+                }
+            };
+            (i.name.clone(), v)
+        })
+        .collect();
+    ret.sort_by_key(|&(_, v)| v);
+    ret
+}
+
+/// Assign values to the extension additions of an enumeration that don't have one. Each of these is one more than the
+/// largest value before it.
+fn enumeration_additions(root: &[(String, i64)], items: &[Item]) -> Vec<(String, i64)> {
+    let mut max = root.iter().map(|&(_, v)| v).max().unwrap_or(-1);
+    items.iter()
+        .map(|i| {
+            let v = i.value.unwrap_or(max + 1);
+            if v > max {
+                max = v;
+            }
+            (i.name.clone(), v)
+        })
+        .collect()
+}
+
+/// A label for a type given as an actual parameter, used to name the instance of a parameterized type.
+fn label(t: &Type) -> String {
+    match t.kind {
+        TypeKind::Boolean => "Boolean".to_string(),
+        TypeKind::Null => "Null".to_string(),
+        TypeKind::Integer => "Integer".to_string(),
+        TypeKind::Real => "Real".to_string(),
+        TypeKind::BitString => "BitString".to_string(),
+        TypeKind::OctetString => "OctetString".to_string(),
+        TypeKind::CharString(ref name) => type_name(name),
+        TypeKind::ObjectIdentifier => "ObjectIdentifier".to_string(),
+        TypeKind::RelativeOid => "RelativeOid".to_string(),
+        TypeKind::Time(ref name) => type_name(name),
+        TypeKind::Enumerated(_) => "Enumerated".to_string(),
+        TypeKind::Sequence(_) => "Sequence".to_string(),
+        TypeKind::SequenceOf(ref el) => format!("SequenceOf{}", label(el)),
+        TypeKind::Set(_) => "Set".to_string(),
+        TypeKind::SetOf(ref el) => format!("SetOf{}", label(el)),
+        TypeKind::Choice(_) => "Choice".to_string(),
+        TypeKind::Reference(ref name, ref args) => {
+            let mut ret = type_name(name);
+            for a in args {
+                match *a {
+                    Arg::Type(ref t) => ret.push_str(&label(t)),
+                    Arg::Value(Value::Integer(n)) => ret.push_str(&n.to_string()),
+                    Arg::Value(Value::Boolean(b)) => ret.push_str(&b.to_string()),
+                    Arg::Value(Value::Reference(ref r)) => ret.push_str(&type_name(r)),
+                }
+            }
+            ret
+        }
+    }
+}
+
+/// Replace references to the formal parameters in `subst` with their actual parameters.
+fn substitute(ty: &Type, subst: &HashMap<&str, &Arg>) -> Type {
+    let constraint = Constraint {
+        value: ty.constraint.value.as_ref().map(|r| substitute_range(r, subst)),
+        size: ty.constraint.size.as_ref().map(|r| substitute_range(r, subst)),
+        alphabet: ty.constraint.alphabet.clone(),
+        real: ty.constraint.real.as_ref().map(|r| {
+            RealComponents {
+                mantissa: r.mantissa.as_ref().map(|r| substitute_range(r, subst)),
+                base: r.base.as_ref().map(|v| substitute_value(v, subst)),
+                exponent: r.exponent.as_ref().map(|r| substitute_range(r, subst)),
+            }
+        }),
+    };
+    let kind = match ty.kind {
+        TypeKind::Reference(ref name, ref args) => {
+            if args.is_empty() {
+                if let Some(Arg::Type(t)) = subst.get(name.as_str()) {
+                    // Constraints and tags at the point of reference take the place of the actual parameter's
+                    let mut t = (**t).clone();
+                    if !constraint.is_empty() {
+                        t.constraint = constraint;
+                    }
+                    if ty.tag.is_some() {
+                        t.tag = ty.tag.clone();
+                    }
+                    return t;
+                }
+            }
+            let args = args.iter()
+                .map(|a| match *a {
+                    Arg::Type(ref t) => Arg::Type(Box::new(substitute(t, subst))),
+                    Arg::Value(ref v) => Arg::Value(substitute_value(v, subst)),
+                })
+                .collect();
+            TypeKind::Reference(name.clone(), args)
+        }
+        TypeKind::SequenceOf(ref el) => TypeKind::SequenceOf(Box::new(substitute(el, subst))),
+        TypeKind::Sequence(ref items) => TypeKind::Sequence(substitute_components(items, subst)),
+        TypeKind::SetOf(ref el) => TypeKind::SetOf(Box::new(substitute(el, subst))),
+        TypeKind::Set(ref items) => TypeKind::Set(substitute_components(items, subst)),
+        TypeKind::Choice(ref items) => TypeKind::Choice(substitute_components(items, subst)),
+        ref k => k.clone(),
+    };
+    Type {
+        kind,
+        tag: ty.tag.clone(),
+        constraint,
+    }
+}
+
+fn substitute_components(items: &Items<Component>, subst: &HashMap<&str, &Arg>) -> Items<Component> {
+    let f = |c: &Component| {
+        Component {
+            name: c.name.clone(),
+            ty: substitute(&c.ty, subst),
+            optional: c.optional,
+            default: c.default.as_ref().map(|v| substitute_value(v, subst)),
+        }
+    };
+    Items {
+        root: items.root.iter().map(&f).collect(),
+        extensible: items.extensible,
+        additions: items.additions.iter().map(&f).collect(),
+        groups: items.groups.clone(),
+    }
+}
+
+fn substitute_range(r: &Range, subst: &HashMap<&str, &Arg>) -> Range {
+    Range {
+        min: r.min.as_ref().map(|v| substitute_value(v, subst)),
+        max: r.max.as_ref().map(|v| substitute_value(v, subst)),
+        extensible: r.extensible,
+    }
+}
+
+fn substitute_value(v: &Value, subst: &HashMap<&str, &Arg>) -> Value {
+    if let Value::Reference(ref r) = *v {
+        if let Some(&Arg::Value(actual)) = subst.get(r.as_str()) {
+            return actual.clone();
+        }
+    }
+    v.clone()
+}
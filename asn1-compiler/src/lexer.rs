@@ -0,0 +1,181 @@
+use Error;
+
+/// A lexical item of an ASN.1 module.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    /// A reference or keyword, such as `Foo-Bar`, `maxFoo` or `SEQUENCE`.
+    Ident(String),
+    /// A field of an information object class, such as `&id`, without the ampersand.
+    Field(String),
+    Number(i64),
+    /// A quoted string, bit string (`'0101'B`) or hex string (`'0F'H`), kept verbatim.
+    Literal(String),
+    Assign,
+    Range,
+    Ellipsis,
+    LBrace,
+    RBrace,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    LVersionBrackets,
+    RVersionBrackets,
+    Comma,
+    Semicolon,
+    Colon,
+    Bar,
+    Dot,
+    At,
+    Exclamation,
+    Less,
+    Caret,
+}
+
+/// A `Token` along with the line it was found on.
+#[derive(Debug, Clone)]
+pub struct Spanned {
+    pub token: Token,
+    pub line: usize,
+}
+
+/// Split `src` into `Token`s, discarding whitespace and comments.
+pub fn tokenize(src: &str) -> Result<Vec<Spanned>, Error> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut tokens: Vec<Spanned> = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).cloned();
+
+        if c == '\n' {
+            line += 1;
+            i += 1;
+            continue;
+        }
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        // `--` comments run until the end of the line or the next `--`
+        if c == '-' && next == Some('-') {
+            i += 2;
+            while i < chars.len() && chars[i] != '\n' {
+                if chars[i] == '-' && chars.get(i + 1) == Some(&'-') {
+                    i += 2;
+                    break;
+                }
+                i += 1;
+            }
+            continue;
+        }
+        if c == '/' && next == Some('*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                if chars[i] == '\n' {
+                    line += 1;
+                }
+                i += 1;
+            }
+            i += 2;
+            continue;
+        }
+
+        let token;
+        if c.is_ascii_alphabetic() || (c == '&' && next.is_some_and(|n| n.is_ascii_alphabetic())) {
+            let field = c == '&';
+            if field {
+                i += 1;
+            }
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '-' || chars[i] == '_') {
+                // A hyphen can't end a reference, or be followed by another
+                if chars[i] == '-' && (chars.get(i + 1) == Some(&'-') || !is_ident_char(chars.get(i + 1))) {
+                    break;
+                }
+                i += 1;
+            }
+            let name = chars[start..i].iter().collect();
+            token = if field { Token::Field(name) } else { Token::Ident(name) };
+        } else if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) {
+            let start = i;
+            i += 1;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            let s: String = chars[start..i].iter().collect();
+            match s.parse() {
+                Ok(n) => token = Token::Number(n),
+                Err(_) => return Err(Error::Syntax(line, format!("number {} is out of range", s))),
+            }
+        } else if c == '"' || c == '\'' {
+            let start = i;
+            i += 1;
+            while i < chars.len() && chars[i] != c {
+                if chars[i] == '\n' {
+                    line += 1;
+                }
+                i += 1;
+            }
+            if i == chars.len() {
+                return Err(Error::Syntax(line, "unterminated string".to_string()));
+            }
+            i += 1;
+            // Bit and hex strings carry a trailing radix
+            if c == '\'' && i < chars.len() && chars[i].is_ascii_alphabetic() {
+                i += 1;
+            }
+            token = Token::Literal(chars[start..i].iter().collect());
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 3)].iter().collect();
+            let (t, len) = if rest.starts_with("::=") {
+                (Token::Assign, 3)
+            } else if rest.starts_with("...") {
+                (Token::Ellipsis, 3)
+            } else if rest.starts_with("..") {
+                (Token::Range, 2)
+            } else if rest.starts_with("[[") {
+                (Token::LVersionBrackets, 2)
+            } else if rest.starts_with("]]") {
+                (Token::RVersionBrackets, 2)
+            } else {
+                let t = match c {
+                    '{' => Token::LBrace,
+                    '}' => Token::RBrace,
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    '[' => Token::LBracket,
+                    ']' => Token::RBracket,
+                    ',' => Token::Comma,
+                    ';' => Token::Semicolon,
+                    ':' => Token::Colon,
+                    '|' => Token::Bar,
+                    '.' => Token::Dot,
+                    '@' => Token::At,
+                    '!' => Token::Exclamation,
+                    '<' => Token::Less,
+                    '^' => Token::Caret,
+                    _ => return Err(Error::Syntax(line, format!("unexpected character '{}'", c))),
+                };
+                (t, 1)
+            };
+            i += len;
+            token = t;
+        }
+        tokens.push(Spanned {
+            token,
+            line,
+        });
+    }
+    Ok(tokens)
+}
+
+fn is_ident_char(c: Option<&char>) -> bool {
+    match c {
+        Some(c) => c.is_ascii_alphanumeric() || *c == '_',
+        None => false,
+    }
+}
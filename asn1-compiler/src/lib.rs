@@ -0,0 +1,112 @@
+//! Generates Rust types for the `asn1` crate from ASN.1 module specifications.
+//!
+//! Each type assignment in a module becomes a Rust type deriving `APerElement`:
+//!
+//! * SEQUENCE becomes a struct, with `Option<T>` fields for OPTIONAL components.
+//! * CHOICE becomes an enum with one tuple variant per alternative.
+//! * ENUMERATED becomes a C-like enum.
+//! * Any other type becomes a newtype struct around the corresponding Rust type.
+//!
+//! Nested SEQUENCE, CHOICE and ENUMERATED types are given names made of the enclosing type's name and the component's
+//! name. Parameterized types are instantiated for each distinct set of actual parameters that they're referenced with.
+//! Integer value assignments become `i64` constants. Information object classes and objects are skipped, as are
+//! imports, which are expected to be generated separately and brought into scope by the user.
+//!
+//! The generated code refers to the `APerElement` derive, so it should be included where `asn1_derive` macros are in
+//! scope, typically from a build script:
+//!
+//! ```
+//! // build.rs
+//! extern crate asn1_compiler;
+//!
+//! fn main() {
+//!     asn1_compiler::build("src/foo.asn", "foo.rs").unwrap();
+//! }
+//!
+//! // src/lib.rs
+//! #[macro_use]
+//! extern crate asn1_derive;
+//! extern crate asn1;
+//!
+//! include!(concat!(env!("OUT_DIR"), "/foo.rs"));
+//! ```
+
+mod ast;
+mod codegen;
+mod lexer;
+mod parser;
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub enum Error {
+    /// Reading the specification or writing the generated code failed.
+    Io(io::Error),
+    /// The specification is malformed at the given line.
+    Syntax(usize, String),
+    /// The specification uses something that can't be generated yet.
+    Unsupported(String),
+    /// A value reference has no assignment.
+    Undefined(String),
+    /// The specification is well-formed but doesn't make sense, e.g. a parameterized type is given the wrong number
+    /// of parameters.
+    Semantic(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Io(ref e) => write!(f, "{}", e),
+            Error::Syntax(line, ref msg) => write!(f, "syntax error on line {}: {}", line, msg),
+            Error::Unsupported(ref what) => write!(f, "unsupported: {}", what),
+            Error::Undefined(ref name) => write!(f, "undefined reference to {}", name),
+            Error::Semantic(ref msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+/// Generate Rust source for the modules in `src`.
+///
+/// # Examples
+///
+/// ```
+/// let code = asn1_compiler::compile("Foo DEFINITIONS ::= BEGIN Bar ::= INTEGER (0..7) END").unwrap();
+/// assert!(code.contains("pub struct Bar(#[aper(value(0..7))] pub u8);"));
+/// ```
+pub fn compile(src: &str) -> Result<String, Error> {
+    let tokens = lexer::tokenize(src)?;
+    let mut parser = parser::Parser::new(tokens);
+    let mut modules = Vec::new();
+    while !parser.is_done() {
+        modules.push(parser.module()?);
+    }
+    codegen::Generator::new(&modules).generate(&modules)
+}
+
+/// Generate Rust source for the modules in the file `input` and write it to the file `output`.
+pub fn compile_file<P: AsRef<Path>, Q: AsRef<Path>>(input: P, output: Q) -> Result<(), Error> {
+    let src = fs::read_to_string(input)?;
+    let code = compile(&src)?;
+    fs::write(output, code)?;
+    Ok(())
+}
+
+/// Generate Rust source for the modules in the file `input` from a build script, writing it to `name` in `OUT_DIR`.
+pub fn build<P: AsRef<Path>>(input: P, name: &str) -> Result<(), Error> {
+    let out_dir = match env::var_os("OUT_DIR") {
+        Some(d) => PathBuf::from(d),
+        None => return Err(Error::Io(io::Error::new(io::ErrorKind::NotFound, "OUT_DIR is not set"))),
+    };
+    println!("cargo:rerun-if-changed={}", input.as_ref().display());
+    compile_file(input, out_dir.join(name))
+}
@@ -0,0 +1,631 @@
+use ast::*;
+use lexer::{Spanned, Token};
+use Error;
+
+/// A recursive descent parser over the `Token`s of a module.
+pub struct Parser {
+    tokens: Vec<Spanned>,
+    pos: usize,
+    /// Whether the module being parsed has `AUTOMATIC TAGS`.
+    automatic_tags: bool,
+}
+
+impl Parser {
+    /// Construct a new `Parser` over `tokens`.
+    pub fn new(tokens: Vec<Spanned>) -> Parser {
+        Parser {
+            tokens,
+            pos: 0,
+            automatic_tags: false,
+        }
+    }
+
+    /// Whether all of the tokens have been parsed.
+    pub fn is_done(&self) -> bool {
+        self.pos >= self.tokens.len()
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|t| &t.token)
+    }
+
+    fn peek_at(&self, n: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + n).map(|t| &t.token)
+    }
+
+    fn line(&self) -> usize {
+        match self.tokens.get(self.pos) {
+            Some(t) => t.line,
+            None => self.tokens.last().map_or(0, |t| t.line),
+        }
+    }
+
+    fn next(&mut self) -> Result<Token, Error> {
+        match self.tokens.get(self.pos) {
+            Some(t) => {
+                self.pos += 1;
+                Ok(t.token.clone())
+            }
+            None => Err(Error::Syntax(self.line(), "unexpected end of module".to_string())),
+        }
+    }
+
+    fn error<T>(&self, msg: &str) -> Result<T, Error> {
+        let found = match self.peek() {
+            Some(t) => format!("{:?}", t),
+            None => "end of module".to_string(),
+        };
+        Err(Error::Syntax(self.line(), format!("{}, found {}", msg, found)))
+    }
+
+    fn unsupported<T>(&self, what: &str) -> Result<T, Error> {
+        Err(Error::Unsupported(format!("{} (line {})", what, self.line())))
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), Error> {
+        if self.peek() == Some(&token) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            self.error(&format!("expected {:?}", token))
+        }
+    }
+
+    fn eat(&mut self, token: Token) -> bool {
+        if self.peek() == Some(&token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn is_keyword(&self, kw: &str) -> bool {
+        match self.peek() {
+            Some(Token::Ident(s)) => s == kw,
+            _ => false,
+        }
+    }
+
+    fn eat_keyword(&mut self, kw: &str) -> bool {
+        if self.is_keyword(kw) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_keyword(&mut self, kw: &str) -> Result<(), Error> {
+        if self.eat_keyword(kw) {
+            Ok(())
+        } else {
+            self.error(&format!("expected {}", kw))
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, Error> {
+        match self.peek().cloned() {
+            Some(Token::Ident(s)) => {
+                self.pos += 1;
+                Ok(s)
+            }
+            _ => self.error("expected an identifier"),
+        }
+    }
+
+    /// Skip a balanced `{ ... }` block, or a single token if the next token doesn't open a block.
+    fn skip_block(&mut self) -> Result<(), Error> {
+        if !self.eat(Token::LBrace) {
+            return self.next().map(|_| ());
+        }
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next()? {
+                Token::LBrace => depth += 1,
+                Token::RBrace => depth -= 1,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Parse a complete module.
+    pub fn module(&mut self) -> Result<Module, Error> {
+        let name = self.ident()?;
+        self.automatic_tags = false;
+        while !self.eat(Token::Assign) {
+            if self.is_keyword("AUTOMATIC") {
+                self.automatic_tags = true;
+            }
+            if self.peek() == Some(&Token::LBrace) {
+                self.skip_block()?;
+            } else {
+                self.next()?;
+            }
+        }
+        self.expect_keyword("BEGIN")?;
+
+        // Imported and exported symbols are resolved by the user
+        for kw in &["EXPORTS", "IMPORTS"] {
+            if self.eat_keyword(kw) {
+                while !self.eat(Token::Semicolon) {
+                    self.next()?;
+                }
+            }
+        }
+
+        let mut assignments = Vec::new();
+        while !self.eat_keyword("END") {
+            if let Some(a) = self.assignment()? {
+                assignments.push(a);
+            }
+        }
+
+        Ok(Module {
+            name,
+            assignments,
+        })
+    }
+
+    /// Parse an assignment, or skip it if it defines something other than a type or value (e.g. an information
+    /// object class).
+    fn assignment(&mut self) -> Result<Option<Assignment>, Error> {
+        let name = self.ident()?;
+        let upper = name.chars().next().is_some_and(|c| c.is_uppercase());
+
+        if upper && self.peek() == Some(&Token::LBrace) {
+            let params = self.params()?;
+            self.expect(Token::Assign)?;
+            let ty = self.ty()?;
+            return Ok(Some(Assignment::Type {
+                name,
+                params,
+                ty,
+            }));
+        }
+
+        if upper && self.eat(Token::Assign) {
+            if self.eat_keyword("CLASS") {
+                self.skip_block()?;
+                if self.eat_keyword("WITH") {
+                    self.expect_keyword("SYNTAX")?;
+                    self.skip_block()?;
+                }
+                return Ok(None);
+            }
+            let ty = self.ty()?;
+            return Ok(Some(Assignment::Type {
+                name,
+                params: Vec::new(),
+                ty,
+            }));
+        }
+
+        // Value assignments, and information object (set) assignments, which are skipped
+        let ty = self.ty()?;
+        self.expect(Token::Assign)?;
+        if upper || self.peek() == Some(&Token::LBrace) {
+            self.skip_block()?;
+            return Ok(None);
+        }
+        let value = self.value()?;
+        Ok(Some(Assignment::Value {
+            name,
+            ty,
+            value,
+        }))
+    }
+
+    /// Parse the formal parameters of a parameterized type assignment.
+    fn params(&mut self) -> Result<Vec<Param>, Error> {
+        self.expect(Token::LBrace)?;
+        let mut params = Vec::new();
+        loop {
+            let first = self.ident()?;
+            if self.eat(Token::Colon) {
+                // A governor, which makes this a value (or object set) parameter
+                params.push(Param::Value(self.ident()?));
+            } else if first.chars().next().is_some_and(|c| c.is_uppercase()) {
+                params.push(Param::Type(first));
+            } else {
+                params.push(Param::Value(first));
+            }
+            if !self.eat(Token::Comma) {
+                break;
+            }
+        }
+        self.expect(Token::RBrace)?;
+        Ok(params)
+    }
+
+    /// Parse a type, along with any constraints that follow it.
+    fn ty(&mut self) -> Result<Type, Error> {
+        let tag = if self.eat(Token::LBracket) {
+            Some(self.tag()?)
+        } else {
+            None
+        };
+
+        let mut constraint = Constraint::default();
+        let name = self.ident()?;
+        let kind = match name.as_str() {
+            "BOOLEAN" => TypeKind::Boolean,
+            "NULL" => TypeKind::Null,
+            "INTEGER" => {
+                // Named numbers don't affect the encoding
+                if self.peek() == Some(&Token::LBrace) {
+                    self.skip_block()?;
+                }
+                TypeKind::Integer
+            }
+            "REAL" => TypeKind::Real,
+            "BIT" => {
+                self.expect_keyword("STRING")?;
+                if self.peek() == Some(&Token::LBrace) {
+                    self.skip_block()?;
+                }
+                TypeKind::BitString
+            }
+            "OCTET" => {
+                self.expect_keyword("STRING")?;
+                TypeKind::OctetString
+            }
+            "IA5String" | "PrintableString" | "NumericString" | "VisibleString" | "ISO646String" | "BMPString" |
+            "UniversalString" | "UTF8String" | "GraphicString" | "GeneralString" | "TeletexString" | "T61String" => {
+                TypeKind::CharString(name)
+            }
+            "OBJECT" => {
+                self.expect_keyword("IDENTIFIER")?;
+                TypeKind::ObjectIdentifier
+            }
+            "RELATIVE-OID" => TypeKind::RelativeOid,
+            "UTCTime" | "GeneralizedTime" | "DATE" | "TIME-OF-DAY" | "DATE-TIME" | "DURATION" => TypeKind::Time(name),
+            "ENUMERATED" => TypeKind::Enumerated(self.items(|p| p.item())?),
+            "CHOICE" => TypeKind::Choice(self.items(|p| p.component())?),
+            "SEQUENCE" | "SET" => {
+                let set = name == "SET";
+                if self.peek() == Some(&Token::LBrace) {
+                    let items = self.items(|p| p.component())?;
+                    if !set {
+                        TypeKind::Sequence(items)
+                    } else {
+                        // Automatic tagging only applies if no component has a tag, and keeps them in declaration order
+                        let tagged = items.root.iter().filter(|c| c.ty.tag.is_some()).count();
+                        if tagged < items.root.len() && (tagged > 0 || !self.automatic_tags) {
+                            return self.unsupported("SET components without tags");
+                        }
+                        TypeKind::Set(items)
+                    }
+                } else {
+                    // SEQUENCE (SIZE(..)) OF, or SEQUENCE SIZE(..) OF
+                    if self.is_keyword("SIZE") {
+                        constraint.size = Some(self.size()?);
+                    } else if self.peek() == Some(&Token::LParen) {
+                        self.constraint(&mut constraint)?;
+                    }
+                    self.expect_keyword("OF")?;
+                    // The element may be named, e.g. `SEQUENCE OF item Item`
+                    if let (Some(Token::Ident(s)), Some(&Token::Ident(_))) = (self.peek(), self.peek_at(1)) {
+                        if s.chars().next().is_some_and(|c| c.is_lowercase()) {
+                            self.pos += 1;
+                        }
+                    }
+                    let el = Box::new(self.ty()?);
+                    if set {
+                        TypeKind::SetOf(el)
+                    } else {
+                        TypeKind::SequenceOf(el)
+                    }
+                }
+            }
+            _ => {
+                let mut args = Vec::new();
+                if self.eat(Token::LBrace) {
+                    loop {
+                        args.push(self.arg()?);
+                        if !self.eat(Token::Comma) {
+                            break;
+                        }
+                    }
+                    self.expect(Token::RBrace)?;
+                }
+                TypeKind::Reference(name, args)
+            }
+        };
+
+        while self.peek() == Some(&Token::LParen) {
+            self.constraint(&mut constraint)?;
+        }
+        Ok(Type {
+            kind,
+            tag,
+            constraint,
+        })
+    }
+
+    /// Parse the rest of a tag after its `[`, such as `APPLICATION 1]`, along with the IMPLICIT or EXPLICIT after it.
+    fn tag(&mut self) -> Result<Tag, Error> {
+        let class = if self.is_keyword("UNIVERSAL") || self.is_keyword("APPLICATION") || self.is_keyword("PRIVATE") {
+            Some(self.ident()?)
+        } else {
+            None
+        };
+        let number = self.value()?;
+        self.expect(Token::RBracket)?;
+        if !self.eat_keyword("IMPLICIT") {
+            self.eat_keyword("EXPLICIT");
+        }
+        Ok(Tag {
+            class,
+            number,
+        })
+    }
+
+    /// Parse an actual parameter, which is a value if it starts with a number or a lowercase reference.
+    fn arg(&mut self) -> Result<Arg, Error> {
+        match self.peek().cloned() {
+            Some(Token::Number(_)) => Ok(Arg::Value(self.value()?)),
+            Some(Token::Ident(ref s)) if s == "TRUE" || s == "FALSE" => Ok(Arg::Value(self.value()?)),
+            Some(Token::Ident(ref s)) if s.chars().next().is_some_and(|c| c.is_lowercase()) => {
+                Ok(Arg::Value(self.value()?))
+            }
+            _ => Ok(Arg::Type(Box::new(self.ty()?))),
+        }
+    }
+
+    /// Parse the `{ ... }` body of an ENUMERATED, SEQUENCE or CHOICE type with `item` parsing each item.
+    fn items<T, F>(&mut self, mut item: F) -> Result<Items<T>, Error>
+        where F: FnMut(&mut Parser) -> Result<T, Error>
+    {
+        self.expect(Token::LBrace)?;
+        let mut items = Items {
+            root: Vec::new(),
+            extensible: false,
+            additions: Vec::new(),
+            groups: Vec::new(),
+        };
+        if self.eat(Token::RBrace) {
+            return Ok(items);
+        }
+
+        let mut in_additions = false;
+        loop {
+            if self.eat(Token::Ellipsis) {
+                // Exception specifications don't affect the encoding
+                if self.eat(Token::Exclamation) {
+                    self.next()?;
+                }
+                items.extensible = true;
+                in_additions = !in_additions;
+            } else if in_additions && self.eat(Token::LVersionBrackets) {
+                // The version number of a group doesn't affect the encoding
+                if let (Some(&Token::Number(_)), Some(&Token::Colon)) = (self.peek(), self.peek_at(1)) {
+                    self.pos += 2;
+                }
+                let start = items.additions.len();
+                loop {
+                    items.additions.push(item(self)?);
+                    if !self.eat(Token::Comma) {
+                        break;
+                    }
+                }
+                self.expect(Token::RVersionBrackets)?;
+                items.groups.push(start..items.additions.len());
+            } else if in_additions {
+                items.additions.push(item(self)?);
+            } else {
+                items.root.push(item(self)?);
+            }
+
+            if !self.eat(Token::Comma) {
+                break;
+            }
+        }
+        self.expect(Token::RBrace)?;
+        Ok(items)
+    }
+
+    fn item(&mut self) -> Result<Item, Error> {
+        let name = self.ident()?;
+        let mut value = None;
+        if self.eat(Token::LParen) {
+            match self.next()? {
+                Token::Number(n) => value = Some(n),
+                _ => return self.error("expected a number"),
+            }
+            self.expect(Token::RParen)?;
+        }
+        Ok(Item {
+            name,
+            value,
+        })
+    }
+
+    fn component(&mut self) -> Result<Component, Error> {
+        if self.is_keyword("COMPONENTS") {
+            return self.unsupported("COMPONENTS OF");
+        }
+        let name = self.ident()?;
+        let ty = self.ty()?;
+        let mut optional = false;
+        let mut default = None;
+        if self.eat_keyword("OPTIONAL") {
+            optional = true;
+        } else if self.eat_keyword("DEFAULT") {
+            default = Some(self.value()?);
+        }
+        Ok(Component {
+            name,
+            ty,
+            optional,
+            default,
+        })
+    }
+
+    fn value(&mut self) -> Result<Value, Error> {
+        match self.next()? {
+            Token::Number(n) => Ok(Value::Integer(n)),
+            Token::Ident(ref s) if s == "TRUE" => Ok(Value::Boolean(true)),
+            Token::Ident(ref s) if s == "FALSE" => Ok(Value::Boolean(false)),
+            Token::Ident(s) => Ok(Value::Reference(s)),
+            _ => {
+                self.pos -= 1;
+                self.unsupported("values other than integers, booleans and references")
+            }
+        }
+    }
+
+    /// Parse a `( ... )` constraint into `constraint`.
+    fn constraint(&mut self, constraint: &mut Constraint) -> Result<(), Error> {
+        self.expect(Token::LParen)?;
+        if self.is_keyword("FROM") {
+            let alphabet = self.from()?;
+            // An extensible permitted alphabet isn't visible to PER
+            if !self.extension_marker()? {
+                constraint.alphabet = Some(alphabet);
+            }
+        } else if self.is_keyword("WITH") && self.peek_at(1) == Some(&Token::Ident("COMPONENTS".to_string())) {
+            constraint.real = Some(self.components()?);
+            self.extension_marker()?;
+        } else if self.is_keyword("WITH") || self.is_keyword("CONTAINING") {
+            return self.unsupported("constraints other than value, SIZE, FROM and WITH COMPONENTS");
+        } else {
+            let (slot, mut range) = if self.is_keyword("SIZE") {
+                (&mut constraint.size, self.size()?)
+            } else {
+                (&mut constraint.value, self.range()?)
+            };
+            if self.extension_marker()? {
+                range.extensible = true;
+            }
+            *slot = Some(range);
+        }
+
+        if self.peek() == Some(&Token::Bar) {
+            return self.unsupported("constraint unions");
+        }
+        self.expect(Token::RParen)
+    }
+
+    /// Parse the extension marker at the end of a constraint, if there is one, along with any additional elements
+    /// that follow it, which aren't visible to PER.
+    fn extension_marker(&mut self) -> Result<bool, Error> {
+        if !self.eat(Token::Comma) {
+            return Ok(false);
+        }
+        self.expect(Token::Ellipsis)?;
+        while self.peek() != Some(&Token::RParen) {
+            self.next()?;
+        }
+        Ok(true)
+    }
+
+    /// Parse `FROM ( ... )`, a permitted alphabet given as a string of its characters or a range of them.
+    fn from(&mut self) -> Result<Alphabet, Error> {
+        self.expect_keyword("FROM")?;
+        self.expect(Token::LParen)?;
+        let first = self.chars()?;
+        let alphabet = if self.eat(Token::Range) {
+            let last = self.chars()?;
+            let (mut a, mut b) = (first.chars(), last.chars());
+            match (a.next(), a.next(), b.next(), b.next()) {
+                (Some(a), None, Some(b), None) => Alphabet::Range(a, b),
+                _ => return Err(Error::Syntax(self.line(), "expected a range of single characters".to_string())),
+            }
+        } else {
+            Alphabet::Chars(first)
+        };
+        if self.peek() == Some(&Token::Bar) {
+            return self.unsupported("permitted alphabet unions");
+        }
+        self.expect(Token::RParen)?;
+        Ok(alphabet)
+    }
+
+    /// Parse `WITH COMPONENTS { ... }`, which is only supported on a REAL.
+    fn components(&mut self) -> Result<RealComponents, Error> {
+        self.expect_keyword("WITH")?;
+        self.expect_keyword("COMPONENTS")?;
+        self.expect(Token::LBrace)?;
+        // A partial specification starts with an ellipsis
+        if self.eat(Token::Ellipsis) {
+            self.expect(Token::Comma)?;
+        }
+
+        let mut real = RealComponents::default();
+        loop {
+            let name = self.ident()?;
+            if name != "mantissa" && name != "base" && name != "exponent" {
+                return self.unsupported("WITH COMPONENTS constraints other than those of a REAL");
+            }
+            self.expect(Token::LParen)?;
+            let range = self.range()?;
+            self.expect(Token::RParen)?;
+            // The components of a REAL are always present
+            self.eat_keyword("PRESENT");
+            match name.as_str() {
+                "mantissa" => real.mantissa = Some(range),
+                "base" => real.base = range.min,
+                _ => real.exponent = Some(range),
+            }
+            if !self.eat(Token::Comma) {
+                break;
+            }
+        }
+        self.expect(Token::RBrace)?;
+        Ok(real)
+    }
+
+    /// Parse a quoted character string such as `"abc"` into its characters.
+    fn chars(&mut self) -> Result<String, Error> {
+        match self.peek().cloned() {
+            Some(Token::Literal(ref s)) if s.starts_with('"') => {
+                self.pos += 1;
+                Ok(s[1..s.len() - 1].to_string())
+            }
+            _ => self.error("expected a character string"),
+        }
+    }
+
+    /// Parse `SIZE ( ... )`.
+    fn size(&mut self) -> Result<Range, Error> {
+        self.expect_keyword("SIZE")?;
+        self.expect(Token::LParen)?;
+        let mut range = self.range()?;
+        if self.eat(Token::Comma) {
+            self.expect(Token::Ellipsis)?;
+            range.extensible = true;
+        }
+        self.expect(Token::RParen)?;
+        Ok(range)
+    }
+
+    fn range(&mut self) -> Result<Range, Error> {
+        let min = self.bound("MIN")?;
+        if !self.eat(Token::Range) {
+            return Ok(Range {
+                min: min.clone(),
+                max: min,
+                extensible: false,
+            });
+        }
+        let max = self.bound("MAX")?;
+        Ok(Range {
+            min,
+            max,
+            extensible: false,
+        })
+    }
+
+    fn bound(&mut self, open: &str) -> Result<Option<Value>, Error> {
+        if self.eat_keyword(open) {
+            return Ok(None);
+        }
+        let v = self.value()?;
+        if self.peek() == Some(&Token::Less) {
+            return self.unsupported("exclusive range bounds");
+        }
+        Ok(Some(v))
+    }
+}
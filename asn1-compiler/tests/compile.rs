@@ -0,0 +1,203 @@
+extern crate asn1_compiler;
+
+use asn1_compiler::{compile, Error};
+
+fn module(body: &str) -> String {
+    format!("Test DEFINITIONS AUTOMATIC TAGS ::= BEGIN\n{}\nEND\n", body)
+}
+
+#[test]
+fn compile_example() {
+    let code = compile(include_str!("data/example.asn")).unwrap();
+    assert_eq!(code, include_str!("data/example.rs"));
+}
+
+#[test]
+fn compile_integer_types() {
+    let code = compile(&module("A ::= INTEGER (0..255)
+                                B ::= INTEGER (-1..255)
+                                C ::= INTEGER (0..65536)
+                                D ::= INTEGER (0..MAX)
+                                E ::= INTEGER
+                                F ::= INTEGER (0..4294967296)
+                                G ::= INTEGER (-1..4294967295)")).unwrap();
+    assert!(code.contains("pub struct A(#[aper(value(0..255))] pub u8);"));
+    assert!(code.contains("pub struct B(#[aper(value(-1..255))] pub i16);"));
+    assert!(code.contains("pub struct C(#[aper(value(0..65536))] pub u32);"));
+    assert!(code.contains("pub struct D(#[aper(value(0..))] pub u64);"));
+    assert!(code.contains("pub struct E(#[aper(value(..))] pub i64);"));
+    assert!(code.contains("pub struct F(#[aper(value(0..4294967296))] pub u64);"));
+    assert!(code.contains("pub struct G(#[aper(value(-1..4294967295))] pub i64);"));
+}
+
+#[test]
+fn compile_enumerated_values() {
+    let code = compile(&module("Foo ::= ENUMERATED { a, b (0), c, ..., d, e (7), f }")).unwrap();
+    assert!(code.contains("#[aper(enumerated)]\npub enum Foo {\n    B = 0,\n    A = 1,\n    C = 2,\n"));
+    assert!(code.contains("    #[aper(extension)]\n    D = 3,\n    #[aper(extension)]\n    E = 7,\n"));
+    assert!(code.contains("    #[aper(extension)]\n    F = 8,\n}"));
+}
+
+#[test]
+fn compile_parameterized() {
+    let code = compile(&module("max INTEGER ::= 4
+                                List {Item, INTEGER:n} ::= SEQUENCE (SIZE (1..n)) OF Item
+                                Foo ::= SEQUENCE { a List {BOOLEAN, max}, b List {Foo, 2} }")).unwrap();
+    assert!(code.contains("pub struct ListBooleanMax(#[aper(size(1..4))] pub Vec<bool>);"));
+    assert!(code.contains("pub struct ListFoo2(#[aper(size(1..2))] pub Vec<Foo>);"));
+    assert!(code.contains("    pub a: ListBooleanMax,\n    pub b: ListFoo2,\n"));
+}
+
+#[test]
+fn compile_names() {
+    let code = compile(&module("Foo-Bar ::= SEQUENCE { type BOOLEAN, protocolIE-ID INTEGER (0..7) }
+                                id-Foo INTEGER ::= 3")).unwrap();
+    assert!(code.contains("pub struct FooBar {"));
+    assert!(code.contains("    pub type_: bool,"));
+    assert!(code.contains("    pub protocol_ie_id: u8,"));
+    assert!(code.contains("pub const ID_FOO: i64 = 3;"));
+}
+
+#[test]
+fn compile_extensible_constraints() {
+    let code = compile(&module("A ::= INTEGER (0..7, ...)
+                                B ::= OCTET STRING (SIZE (1..4, ...))
+                                C ::= BIT STRING (SIZE (8), ...)
+                                D ::= SEQUENCE (SIZE (1..4, ...)) OF OCTET STRING (SIZE (2, ...))")).unwrap();
+    assert!(code.contains("pub struct A(#[aper(value(0..7, ...))] pub i64);"));
+    assert!(code.contains("pub struct B(#[aper(size(1..4, ...))] pub ::asn1::OctetString);"));
+    assert!(code.contains("pub struct C(#[aper(size(8, ...))] pub ::asn1::BitString);"));
+    assert!(code.contains("pub struct D(#[aper(size(1..4, ...), value(2, ...))] pub Vec<::asn1::OctetString>);"));
+}
+
+#[test]
+fn compile_char_strings() {
+    let code = compile(&module("A ::= IA5String
+                                B ::= PrintableString (SIZE (1..32))
+                                C ::= NumericString (FROM (\"0123456789\")) (SIZE (4, ...))
+                                D ::= VisibleString (FROM (\"A\"..\"Z\"), ...)
+                                E ::= SEQUENCE (SIZE (1..4)) OF IA5String (SIZE (1..8))
+                                F ::= SEQUENCE OF IA5String (FROM (\"ab\"))")).unwrap();
+    assert!(code.contains("pub struct A(pub ::asn1::Ia5String);"));
+    assert!(code.contains("pub struct B(#[aper(size(1..32))] pub ::asn1::PrintableString);"));
+    assert!(code.contains("pub struct C(#[aper(from(\"0123456789\"), size(4, ...))] pub ::asn1::NumericString);"));
+    // An extensible permitted alphabet isn't visible to PER
+    assert!(code.contains("pub struct D(pub ::asn1::VisibleString);"));
+    assert!(code.contains("pub struct E(#[aper(size(1..4), value(1..8))] pub Vec<::asn1::Ia5String>);"));
+    assert!(code.contains("pub struct FItem(#[aper(from(\"ab\"))] pub ::asn1::Ia5String);"));
+    assert!(code.contains("pub struct F(#[aper(size(..))] pub Vec<FItem>);"));
+
+    let code = compile(&module("G ::= BMPString (FROM (\"0\"..\"9\")) (SIZE (8))
+                                H ::= UniversalString
+                                I ::= UTF8String (SIZE (1..64))
+                                J ::= GraphicString
+                                K ::= GeneralString (FROM (\"ab\"))
+                                L ::= T61String")).unwrap();
+    assert!(code.contains("pub struct G(#[aper(from('0'..'9'), size(8))] pub ::asn1::BmpString);"));
+    assert!(code.contains("pub struct H(pub ::asn1::UniversalString);"));
+    // The constraints of a string type that isn't known-multiplier aren't visible to PER
+    assert!(code.contains("pub struct I(pub ::asn1::Utf8String);"));
+    assert!(code.contains("pub struct J(pub ::asn1::GraphicString);"));
+    assert!(code.contains("pub struct K(pub ::asn1::GeneralString);"));
+    assert!(code.contains("pub struct L(pub ::asn1::TeletexString);"));
+}
+
+#[test]
+fn compile_object_identifiers() {
+    let code = compile(&module("id-example OBJECT IDENTIFIER ::= { itu-t (0) identified-organization (4) 1 }
+                                Foo ::= SEQUENCE { oid OBJECT IDENTIFIER, arcs RELATIVE-OID OPTIONAL }
+                                Bar ::= SEQUENCE (SIZE (1..4)) OF OBJECT IDENTIFIER")).unwrap();
+    assert!(code.contains("    pub oid: ::asn1::ObjectIdentifier,\n    pub arcs: Option<::asn1::RelativeOid>,\n"));
+    assert!(code.contains("pub struct Bar(#[aper(size(1..4))] pub Vec<::asn1::ObjectIdentifier>);"));
+    // OBJECT IDENTIFIER values have no constant of their own
+    assert!(!code.contains("ID_EXAMPLE"));
+}
+
+#[test]
+fn compile_real() {
+    let code = compile(&module("A ::= REAL
+                                B ::= REAL (WITH COMPONENTS { mantissa (-16777215..16777215), base (2),
+                                                              exponent (-125..128) })
+                                C ::= REAL (WITH COMPONENTS { ..., base (10) })
+                                D ::= SEQUENCE OF REAL")).unwrap();
+    assert!(code.contains("pub struct A(pub f64);"));
+    assert!(code.contains("pub struct B(#[aper(with_components(mantissa(-16777215..16777215), base(2), \
+                           exponent(-125..128)))] pub f64);"));
+    assert!(code.contains("pub struct C(#[aper(with_components(base(10)))] pub f64);"));
+    assert!(code.contains("pub struct D(#[aper(size(..))] pub Vec<f64>);"));
+}
+
+#[test]
+fn compile_time_types() {
+    let code = compile(&module("Foo ::= SEQUENCE {
+                                    a UTCTime,
+                                    b GeneralizedTime,
+                                    c DATE,
+                                    d TIME-OF-DAY,
+                                    e DATE-TIME,
+                                    f DURATION
+                                }
+                                Bar ::= SEQUENCE (SIZE (1..4)) OF GeneralizedTime")).unwrap();
+    assert!(code.contains("    pub a: ::asn1::UtcTime,\n    pub b: ::asn1::GeneralizedTime,\n"));
+    assert!(code.contains("    pub c: ::asn1::Date,\n    pub d: ::asn1::TimeOfDay,\n"));
+    assert!(code.contains("    pub e: ::asn1::DateTime,\n    pub f: ::asn1::Duration,\n"));
+    assert!(code.contains("pub struct Bar(#[aper(size(1..4))] pub Vec<::asn1::GeneralizedTime>);"));
+}
+
+#[test]
+fn compile_sets() {
+    let code = compile(&module("Foo ::= SET {
+                                    a [1] BOOLEAN,
+                                    b [APPLICATION 2] INTEGER (0..7)
+                                }
+                                Bar ::= SET { a BOOLEAN, ... }
+                                Baz ::= SET (SIZE (1..4)) OF BOOLEAN")).unwrap();
+    assert!(code.contains("#[aper(set)]\npub struct Foo {\n    #[aper(tag(1))]\n    pub a: bool,\n"));
+    assert!(code.contains("    #[aper(tag(application 2), value(0..7))]\n    pub b: u8,\n"));
+    assert!(code.contains("#[aper(set, extensible)]\npub struct Bar {\n    pub a: bool,\n"));
+    assert!(code.contains("pub struct Baz(#[aper(size(1..4))] pub ::asn1::SetOf<bool>);"));
+}
+
+#[test]
+fn compile_unsupported() {
+    match compile(&module("Foo ::= VideotexString")) {
+        Err(Error::Unsupported(_)) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+    match compile(&module("Foo ::= TIME")) {
+        Err(Error::Unsupported(_)) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+    match compile(&module("Foo ::= INTEGER (0..7 | 9)")) {
+        Err(Error::Unsupported(_)) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+    match compile(&module("Foo ::= SEQUENCE { a BOOLEAN } (WITH COMPONENTS { a (TRUE) })")) {
+        Err(Error::Unsupported(_)) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+    match compile(&module("Foo ::= IA5String (FROM (\"a\"..\"z\" | \"0\"..\"9\"))")) {
+        Err(Error::Unsupported(_)) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+    match compile(&module("Foo ::= SET { a [0] BOOLEAN, b BOOLEAN }")) {
+        Err(Error::Unsupported(_)) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+    match compile("Test DEFINITIONS ::= BEGIN\nFoo ::= SET { a BOOLEAN }\nEND\n") {
+        Err(Error::Unsupported(_)) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+}
+
+#[test]
+fn compile_errors() {
+    match compile(&module("Foo ::= SEQUENCE {\n a INTEGER (0..max) }")) {
+        Err(Error::Undefined(ref name)) => assert_eq!(name, "max"),
+        r => panic!("unexpected result {:?}", r),
+    }
+    match compile(&module("Foo ::= SEQUENCE {\n a INTEGER (0..7) ")) {
+        Err(Error::Syntax(..)) => {}
+        r => panic!("unexpected result {:?}", r),
+    }
+}
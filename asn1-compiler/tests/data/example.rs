@@ -0,0 +1,103 @@
+// Types generated from the ASN.1 module Example-PDU-Descriptions
+
+pub const MAXNOOF_CELLS: i64 = 16;
+
+pub const MAX_NAME_LENGTH: i64 = 150;
+
+#[derive(APerElement, Debug)]
+pub struct CellID(#[aper(size(32))] pub ::asn1::BitString);
+
+#[derive(APerElement, Debug)]
+pub struct CellName(#[aper(size(1..150, ...))] pub ::asn1::OctetString);
+
+#[derive(APerElement, Debug)]
+pub struct DialString(#[aper(from("0123456789*#"), size(1..20))] pub ::asn1::Ia5String);
+
+#[derive(APerElement, Debug)]
+pub struct DisplayName(pub ::asn1::Utf8String);
+
+#[derive(APerElement, Debug)]
+pub struct VendorID(pub ::asn1::ObjectIdentifier);
+
+#[derive(APerElement, Debug)]
+pub struct ReportTime(pub ::asn1::GeneralizedTime);
+
+#[derive(APerElement, Debug)]
+pub struct SignalLevel(#[aper(with_components(mantissa(-16777215..16777215), base(2), exponent(-125..128)))] pub f64);
+
+#[derive(APerElement, Debug, Clone, Copy, PartialEq, Eq)]
+#[aper(enumerated, extensible)]
+pub enum Priority {
+    Low = 0,
+    Medium = 1,
+    High = 5,
+}
+
+#[derive(APerElement, Debug)]
+pub struct CellExtGroup1 {
+    #[aper(value(0..3279165))]
+    pub arfcn: u32,
+    #[aper(value(1..1024, ...))]
+    pub band: Option<i64>,
+}
+
+#[derive(APerElement, Debug)]
+#[aper(extensible)]
+pub struct Cell {
+    pub id: CellID,
+    pub name: Option<CellName>,
+    #[aper(default = Priority::Medium)]
+    pub priority: Priority,
+    pub barred: bool,
+    #[aper(value(0..65535))]
+    pub tac: u16,
+    #[aper(extension, value(0..1007))]
+    pub pci: Option<u16>,
+    #[aper(extension)]
+    pub ext_group_1: Option<CellExtGroup1>,
+}
+
+#[derive(APerElement, Debug)]
+pub struct CellList(#[aper(size(1..16))] pub Vec<Cell>);
+
+#[derive(APerElement, Debug)]
+pub struct CapabilitiesBandsItem(#[aper(value(1..1024))] pub u16);
+
+#[derive(APerElement, Debug)]
+#[aper(set)]
+pub struct Capabilities {
+    #[aper(value(1..16))]
+    pub max_cells: u8,
+    #[aper(size(1..8))]
+    pub bands: ::asn1::SetOf<CapabilitiesBandsItem>,
+}
+
+#[derive(APerElement, Debug)]
+pub struct Neighbours8Item(#[aper(value(0..1007))] pub u16);
+
+#[derive(APerElement, Debug)]
+pub struct Neighbours8(#[aper(size(0..8))] pub Vec<Neighbours8Item>);
+
+#[derive(APerElement, Debug)]
+pub struct SetupRequestModeTdd {
+    #[aper(value(0..6))]
+    pub config: u8,
+}
+
+#[derive(APerElement, Debug)]
+#[aper(extensible)]
+pub enum SetupRequestMode {
+    Fdd,
+    Tdd(SetupRequestModeTdd),
+    #[aper(extension)]
+    Nr(#[aper(size(..))] ::asn1::BitString),
+}
+
+#[derive(APerElement, Debug)]
+pub struct SetupRequest {
+    #[aper(value(0..255))]
+    pub transaction_id: u8,
+    pub cells: CellList,
+    pub neighbours: Neighbours8,
+    pub mode: SetupRequestMode,
+}
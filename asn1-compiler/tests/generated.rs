@@ -0,0 +1,127 @@
+extern crate asn1;
+#[macro_use]
+extern crate asn1_derive;
+use asn1::{BitString, GeneralizedTime, Ia5String, ObjectIdentifier, OctetString, SetOf, Utf8String};
+use asn1::aper::{APerElement, Decoder, Encoder, UNCONSTRAINED};
+
+include!("data/example.rs");
+
+#[test]
+fn generated_enumerated() {
+    let enc = Priority::High.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(enc.bytes(), &vec![0x40]);
+    let mut d = Decoder::new(enc.bytes());
+    assert_eq!(Priority::from_aper(&mut d, UNCONSTRAINED).unwrap(), Priority::High);
+
+    // An extension addition that isn't known
+    let data = [0x81];
+    let mut d = Decoder::new(&data);
+    assert!(Priority::from_aper(&mut d, UNCONSTRAINED).is_err());
+}
+
+#[test]
+fn generated_permitted_alphabet() {
+    // The length, padding, then each character as its 4-bit index in the alphabet, which is sorted by code: "#*01..9"
+    let x = DialString(Ia5String("911".to_string()));
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(enc.bytes(), &vec![0x10, 0xb3, 0x30]);
+    let mut d = Decoder::new(enc.bytes());
+    assert_eq!(DialString::from_aper(&mut d, UNCONSTRAINED).unwrap().0, x.0);
+
+    assert!(DialString(Ia5String("+44".to_string())).to_aper(UNCONSTRAINED).is_err());
+}
+
+#[test]
+fn generated_utf8_string() {
+    let x = DisplayName(Utf8String::from("Zürich"));
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(enc.bytes()[0], 7);
+    let mut d = Decoder::new(enc.bytes());
+    assert_eq!(DisplayName::from_aper(&mut d, UNCONSTRAINED).unwrap().0, x.0);
+}
+
+#[test]
+fn generated_object_identifier() {
+    // The length, then the X.690 contents octets
+    let x = VendorID("1.3.6.1.4.1.2021".parse::<ObjectIdentifier>().unwrap());
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(enc.bytes(), &vec![0x07, 0x2b, 0x06, 0x01, 0x04, 0x01, 0x8f, 0x65]);
+    let mut d = Decoder::new(enc.bytes());
+    assert_eq!(VendorID::from_aper(&mut d, UNCONSTRAINED).unwrap().0, x.0);
+}
+
+#[test]
+fn generated_time() {
+    // Encoded as a VisibleString, with each character in an octet
+    let x = ReportTime("20261018120000Z".parse::<GeneralizedTime>().unwrap());
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    let mut target = vec![15];
+    target.extend_from_slice(b"20261018120000Z");
+    assert_eq!(enc.bytes(), &target);
+    let mut d = Decoder::new(enc.bytes());
+    assert_eq!(ReportTime::from_aper(&mut d, UNCONSTRAINED).unwrap().0, x.0);
+}
+
+#[test]
+fn generated_real() {
+    let x = SignalLevel(-2.5);
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    let mut d = Decoder::new(enc.bytes());
+    assert_eq!(SignalLevel::from_aper(&mut d, UNCONSTRAINED).unwrap().0, -2.5);
+
+    // The exponent is beyond its limit
+    assert!(SignalLevel(1e300).to_aper(UNCONSTRAINED).is_err());
+}
+
+#[test]
+fn generated_set() {
+    let x = Capabilities {
+        max_cells: 2,
+        bands: SetOf(vec![CapabilitiesBandsItem(3), CapabilitiesBandsItem(1)]),
+    };
+    // A canonical encoding sorts the elements of the SET OF
+    let mut enc = Encoder::new();
+    enc.set_canonical(true);
+    x.write_aper(&mut enc, UNCONSTRAINED).unwrap();
+    let enc = enc.into_encoding();
+    let mut d = Decoder::new(enc.bytes());
+    let y = Capabilities::from_aper(&mut d, UNCONSTRAINED).unwrap();
+    assert_eq!(y.max_cells, 2);
+    let bands: Vec<u16> = y.bands.0.iter().map(|b| b.0).collect();
+    assert_eq!(bands, vec![1, 3]);
+}
+
+#[test]
+fn generated_round_trip() {
+    let req = SetupRequest {
+        transaction_id: 7,
+        cells: CellList(vec![Cell {
+                                 id: CellID(BitString::with_bytes_and_len(&[0x01, 0x23, 0x45, 0x67], 32)),
+                                 name: Some(CellName(OctetString(b"cell".to_vec()))),
+                                 priority: Priority::High,
+                                 barred: false,
+                                 tac: 1000,
+                                 pci: Some(500),
+                                 ext_group_1: Some(CellExtGroup1 {
+                                     arfcn: 620000,
+                                     // Outside of the extensible root
+                                     band: Some(2000),
+                                 }),
+                             },
+                             Cell {
+                                 id: CellID(BitString::with_bytes_and_len(&[0xff, 0xff, 0xff, 0xff], 32)),
+                                 name: None,
+                                 priority: Priority::Medium,
+                                 barred: true,
+                                 tac: 0,
+                                 pci: None,
+                                 ext_group_1: None,
+                             }]),
+        neighbours: Neighbours8(vec![Neighbours8Item(0), Neighbours8Item(1007)]),
+        mode: SetupRequestMode::Tdd(SetupRequestModeTdd { config: 6 }),
+    };
+    let enc = req.to_aper(UNCONSTRAINED).unwrap();
+    let mut d = Decoder::new(enc.bytes());
+    let decoded = SetupRequest::from_aper(&mut d, UNCONSTRAINED).unwrap();
+    assert_eq!(format!("{:?}", decoded), format!("{:?}", req));
+}
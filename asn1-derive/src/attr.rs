@@ -0,0 +1,220 @@
+use proc_macro2::TokenStream;
+use syn::{self, Attribute, Expr};
+use syn::parse::ParseStream;
+
+/// Options set on a field with `#[aper(...)]`.
+#[derive(Default)]
+pub struct FieldAttrs {
+    /// Tokens for the `Constraint` given with `value(...)`, `from(...)` or `with_components(...)`.
+    pub value: Option<TokenStream>,
+    /// Tokens for the `Constraint` given with `size(...)`.
+    pub size: Option<TokenStream>,
+    /// The expression given with `default = ...`.
+    pub default: Option<Expr>,
+    /// Whether the field is an extension addition, i.e. declared after the extension marker. Set with `extension`.
+    pub extension: bool,
+    /// The tag given with `tag(...)`, as its class in canonical order (universal, application, context-specific, then
+    /// private) and its number.
+    pub tag: Option<(u8, u64)>,
+}
+
+impl FieldAttrs {
+    /// Parse the `#[aper(...)]` attributes of a field.
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<FieldAttrs> {
+        let mut ret = FieldAttrs::default();
+        for attr in attrs {
+            if !attr.path().is_ident("aper") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("value") {
+                    let content;
+                    parenthesized!(content in meta.input);
+                    ret.value = Some(extensible_constraint(&content)?);
+                    Ok(())
+                } else if meta.path.is_ident("from") {
+                    // A permitted alphabet, either as a string of its characters or a range of them
+                    let content;
+                    parenthesized!(content in meta.input);
+                    if content.peek(syn::LitStr) {
+                        let chars: syn::LitStr = content.parse()?;
+                        ret.value = Some(quote!(::asn1::aper::Constraint::new_alphabet(#chars)));
+                    } else {
+                        ret.value = Some(constraint(&content.parse()?, false));
+                    }
+                    Ok(())
+                } else if meta.path.is_ident("with_components") {
+                    // The components of a REAL, as in `WITH COMPONENTS { mantissa (..), base (2), exponent (..) }`
+                    let mut mantissa = quote!((None, None));
+                    let mut base = quote!(None);
+                    let mut exponent = quote!((None, None));
+                    meta.parse_nested_meta(|component| {
+                        let content;
+                        parenthesized!(content in component.input);
+                        let e: Expr = content.parse()?;
+                        if component.path.is_ident("mantissa") {
+                            mantissa = range(&e);
+                        } else if component.path.is_ident("base") {
+                            base = quote!(Some((#e) as u32));
+                        } else if component.path.is_ident("exponent") {
+                            exponent = range(&e);
+                        } else {
+                            return Err(component.error("unsupported REAL component"));
+                        }
+                        Ok(())
+                    })?;
+                    ret.value = Some(quote!(::asn1::aper::Constraint::new_real(::asn1::aper::RealComponents {
+                        mantissa: #mantissa,
+                        base: #base,
+                        exponent: #exponent,
+                    })));
+                    Ok(())
+                } else if meta.path.is_ident("size") {
+                    let content;
+                    parenthesized!(content in meta.input);
+                    ret.size = Some(extensible_constraint(&content)?);
+                    Ok(())
+                } else if meta.path.is_ident("default") {
+                    ret.default = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("extension") {
+                    ret.extension = true;
+                    Ok(())
+                } else if meta.path.is_ident("tag") {
+                    // A tag as in `[APPLICATION 3]`, written `tag(application 3)`, or `tag(3)` if context-specific
+                    let content;
+                    parenthesized!(content in meta.input);
+                    let class = if content.peek(syn::Ident) {
+                        let class: syn::Ident = content.parse()?;
+                        if class == "universal" {
+                            0
+                        } else if class == "application" {
+                            1
+                        } else if class == "private" {
+                            3
+                        } else {
+                            return Err(syn::Error::new_spanned(class, "unsupported tag class"));
+                        }
+                    } else {
+                        2
+                    };
+                    let number: syn::LitInt = content.parse()?;
+                    ret.tag = Some((class, number.base10_parse()?));
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported aper attribute"))
+                }
+            })?;
+        }
+        Ok(ret)
+    }
+}
+
+/// Options set on a type with `#[aper(...)]`.
+#[derive(Default)]
+pub struct TypeAttrs {
+    /// Whether the type has an extension marker, set with `extensible`.
+    pub extensible: bool,
+    /// Whether an enum is an ENUMERATED rather than a CHOICE, set with `enumerated`.
+    pub enumerated: bool,
+    /// Whether a struct is a SET rather than a SEQUENCE, set with `set`.
+    pub set: bool,
+}
+
+impl TypeAttrs {
+    /// Parse the `#[aper(...)]` attributes of a type.
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<TypeAttrs> {
+        let mut ret = TypeAttrs::default();
+        for attr in attrs {
+            if !attr.path().is_ident("aper") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("extensible") {
+                    ret.extensible = true;
+                    Ok(())
+                } else if meta.path.is_ident("enumerated") {
+                    ret.enumerated = true;
+                    Ok(())
+                } else if meta.path.is_ident("set") {
+                    ret.set = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported aper attribute"))
+                }
+            })?;
+        }
+        Ok(ret)
+    }
+}
+
+/// Options set on an enum variant with `#[aper(...)]`.
+#[derive(Default)]
+pub struct VariantAttrs {
+    /// Whether the variant is an extension addition, i.e. declared after the extension marker. Set with `extension`.
+    pub extension: bool,
+}
+
+impl VariantAttrs {
+    /// Parse the `#[aper(...)]` attributes of an enum variant.
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<VariantAttrs> {
+        let mut ret = VariantAttrs::default();
+        for attr in attrs {
+            if !attr.path().is_ident("aper") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("extension") {
+                    ret.extension = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported aper attribute"))
+                }
+            })?;
+        }
+        Ok(ret)
+    }
+}
+
+/// Build a `Constraint` from a range such as `0..255`, `1..` or `..4`, or from a single value. Following ASN.1
+/// notation, both bounds of a range are inclusive, so `0..255` and `0..=255` are equivalent.
+fn constraint(e: &Expr, extensible: bool) -> TokenStream {
+    let (min, max) = bounds(e);
+    if extensible {
+        quote!(::asn1::aper::Constraint::new_extensible(#min, #max))
+    } else {
+        quote!(::asn1::aper::Constraint::new(#min, #max))
+    }
+}
+
+/// Parse a range or a single value followed by an optional extension marker, as in `1..4, ...`, and build a
+/// `Constraint` from it.
+fn extensible_constraint(content: ParseStream) -> syn::Result<TokenStream> {
+    let e = content.parse()?;
+    let extensible = content.peek(Token![,]);
+    if extensible {
+        content.parse::<Token![,]>()?;
+        content.parse::<Token![...]>()?;
+    }
+    Ok(constraint(&e, extensible))
+}
+
+/// Build a `(min, max)` tuple from a range or a single value, as for `constraint()`.
+fn range(e: &Expr) -> TokenStream {
+    let (min, max) = bounds(e);
+    quote!((#min, #max))
+}
+
+fn bounds(e: &Expr) -> (TokenStream, TokenStream) {
+    match *e {
+        Expr::Range(ref r) => (bound(&r.start), bound(&r.end)),
+        _ => (quote!(Some((#e) as i64)), quote!(Some((#e) as i64))),
+    }
+}
+
+fn bound(e: &Option<Box<Expr>>) -> TokenStream {
+    match *e {
+        Some(ref e) => quote!(Some((#e) as i64)),
+        None => quote!(None),
+    }
+}
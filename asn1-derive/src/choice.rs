@@ -0,0 +1,183 @@
+use attr::{TypeAttrs, VariantAttrs};
+use proc_macro2::TokenStream;
+use sequence::{self, Component};
+use syn::{self, DataEnum, DeriveInput, Fields};
+
+/// An alternative of a CHOICE.
+struct Alternative<'a> {
+    fields: &'a Fields,
+    path: TokenStream,
+    components: Vec<Component>,
+}
+
+impl<'a> Alternative<'a> {
+    /// A pattern that matches the alternative and binds references to each of its components.
+    fn pattern(&self) -> TokenStream {
+        let path = &self.path;
+        let bindings = self.components.iter().map(|c| &c.binding);
+        let members = self.components.iter().map(|c| &c.member);
+        match *self.fields {
+            Fields::Named(_) => quote!(#path { #(#members: ref #bindings),* }),
+            Fields::Unnamed(_) => quote!(#path(#(ref #bindings),*)),
+            Fields::Unit => quote!(#path),
+        }
+    }
+
+    /// Statements that decode the alternative's components from `decoder`, and evaluate to the alternative.
+    fn decode(&self) -> TokenStream {
+        let decode = sequence::decode_components(&self.components);
+        let construct = sequence::construct(self.path.clone(), self.fields, &self.components);
+        quote! {
+            #decode
+            Ok(#construct)
+        }
+    }
+
+    /// Statements that write the alternative's components to `enc`.
+    fn encode(&self) -> TokenStream {
+        sequence::encode_components(&self.components, |c| {
+            let binding = &c.binding;
+            quote!(#binding)
+        })
+    }
+}
+
+/// Derive `APerElement` for an enum, treating it as a CHOICE.
+pub fn derive(input: &DeriveInput, data: &DataEnum) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let type_attrs = TypeAttrs::parse(&input.attrs)?;
+
+    let mut root: Vec<Alternative> = Vec::new();
+    let mut additions: Vec<Alternative> = Vec::new();
+    for variant in &data.variants {
+        let attrs = VariantAttrs::parse(&variant.attrs)?;
+        let ident = &variant.ident;
+        let alt = Alternative {
+            fields: &variant.fields,
+            path: quote!(#name::#ident),
+            components: sequence::components(&variant.fields)?,
+        };
+        if let Some(c) = alt.components.iter().find(|c| c.attrs.extension) {
+            return Err(syn::Error::new_spanned(&c.member, "the fields of a CHOICE alternative can't be extensions"));
+        }
+        if attrs.extension {
+            additions.push(alt);
+        } else if !additions.is_empty() {
+            return Err(syn::Error::new_spanned(variant, "root alternatives must precede extension additions"));
+        } else {
+            root.push(alt);
+        }
+    }
+    if root.is_empty() {
+        return Err(syn::Error::new_spanned(name, "a CHOICE must have at least one root alternative"));
+    }
+    let extensible = type_attrs.extensible || !additions.is_empty();
+
+    // The index of a root alternative is a constrained integer, which takes no bits at all if there's just one
+    let max_index = root.len() as i64 - 1;
+    let decode_index = if max_index > 0 {
+        quote!(decoder.decode_int(Some(0), Some(#max_index))?)
+    } else {
+        quote!(0i64)
+    };
+
+    let root_decode = root.iter().enumerate().map(|(i, alt)| {
+        let i = i as i64;
+        let decode = alt.decode();
+        quote!(#i => { #decode })
+    });
+    let addition_decode = additions.iter().enumerate().map(|(i, alt)| {
+        let i = i as i64;
+        let decode = alt.decode();
+        quote! {
+            #i => {
+                let mut __inner = decoder.nested(&__content);
+                let decoder = &mut __inner;
+                #decode
+            }
+        }
+    });
+    let decode_extension = if extensible {
+        let num_root = root.len() as i64;
+        quote! {
+            if <bool as ::asn1::aper::APerElement>::from_aper(decoder, ::asn1::aper::UNCONSTRAINED)? {
+                let __index = decoder.decode_normally_small()? as i64;
+                let __content = decoder.decode_open_type()?;
+                return match __index {
+                    #(#addition_decode)*
+                    i => Err(::asn1::aper::DecodeError::InvalidChoice(#num_root + i)),
+                };
+            }
+        }
+    } else {
+        quote!()
+    };
+
+    let root_encode = root.iter().enumerate().map(|(i, alt)| {
+        let i = i as i64;
+        let pattern = alt.pattern();
+        let encode = alt.encode();
+        let marker = if extensible {
+            quote!(enc.write_bits(0, 1);)
+        } else {
+            quote!()
+        };
+        let index = if max_index > 0 {
+            quote!(enc.encode_int(#i, Some(0), Some(#max_index))?;)
+        } else {
+            quote!()
+        };
+        quote! {
+            #pattern => {
+                #marker
+                #index
+                #encode
+            }
+        }
+    });
+    let addition_encode = additions.iter().enumerate().map(|(i, alt)| {
+        let i = i as u64;
+        let pattern = alt.pattern();
+        let encode = alt.encode();
+        quote! {
+            #pattern => {
+                enc.write_bits(1, 1);
+                enc.encode_normally_small(#i)?;
+                enc.encode_open_type(|enc| {
+                    #encode
+                    Ok(())
+                })?;
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::asn1::aper::APerElement for #name #ty_generics #where_clause {
+            const CONSTRAINTS: ::asn1::aper::Constraints = ::asn1::aper::UNCONSTRAINED;
+
+            fn from_aper(decoder: &mut ::asn1::aper::Decoder, _: ::asn1::aper::Constraints)
+                         -> Result<Self, ::asn1::aper::DecodeError> {
+                #decode_extension
+                match #decode_index {
+                    #(#root_decode)*
+                    i => Err(::asn1::aper::DecodeError::InvalidChoice(i)),
+                }
+            }
+
+            fn to_aper(&self, constraints: ::asn1::aper::Constraints)
+                       -> Result<::asn1::aper::Encoding, ::asn1::aper::EncodeError> {
+                ::asn1::aper::Encoder::encode(|enc| ::asn1::aper::APerElement::write_aper(self, enc, constraints))
+            }
+
+            fn write_aper(&self, enc: &mut ::asn1::aper::Encoder, _: ::asn1::aper::Constraints)
+                          -> Result<(), ::asn1::aper::EncodeError> {
+                match *self {
+                    #(#root_encode)*
+                    #(#addition_encode)*
+                }
+                Ok(())
+            }
+        }
+    })
+}
@@ -0,0 +1,111 @@
+use attr::{TypeAttrs, VariantAttrs};
+use proc_macro2::TokenStream;
+use syn::{self, DataEnum, DeriveInput, Expr, Fields, Ident, Lit, UnOp};
+
+/// Get the value of a discriminant, which must be an integer literal, possibly negated.
+fn discriminant(e: &Expr) -> syn::Result<i128> {
+    match *e {
+        Expr::Lit(ref lit) => {
+            if let Lit::Int(ref i) = lit.lit {
+                return i.base10_parse();
+            }
+        }
+        Expr::Unary(ref u) => {
+            if let UnOp::Neg(_) = u.op {
+                return discriminant(&u.expr).map(|v| -v);
+            }
+        }
+        Expr::Paren(ref p) => return discriminant(&p.expr),
+        _ => {}
+    }
+    Err(syn::Error::new_spanned(e, "the value of an ENUMERATED item must be an integer literal"))
+}
+
+/// Derive `APerElement` and `Enumerated` for a C-like enum, treating it as an ENUMERATED.
+pub fn derive(input: &DeriveInput, data: &DataEnum) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let type_attrs = TypeAttrs::parse(&input.attrs)?;
+
+    let mut root: Vec<(i128, &Ident)> = Vec::new();
+    let mut additions: Vec<&Ident> = Vec::new();
+    let mut value = 0;
+    for variant in &data.variants {
+        match variant.fields {
+            Fields::Unit => {}
+            _ => return Err(syn::Error::new_spanned(variant, "the items of an ENUMERATED can't have fields")),
+        }
+        if let Some((_, ref e)) = variant.discriminant {
+            value = discriminant(e)?;
+        }
+        let attrs = VariantAttrs::parse(&variant.attrs)?;
+        if attrs.extension {
+            additions.push(&variant.ident);
+        } else if !additions.is_empty() {
+            return Err(syn::Error::new_spanned(variant, "root items must precede extension additions"));
+        } else {
+            root.push((value, &variant.ident));
+        }
+        value += 1;
+    }
+    if root.is_empty() {
+        return Err(syn::Error::new_spanned(name, "an ENUMERATED must have at least one root item"));
+    }
+    let extensible = type_attrs.extensible || !additions.is_empty();
+    let num_root = root.len();
+
+    // Root items are indexed in ascending order of their values, and additions in declaration order after them
+    root.sort_by_key(|&(v, _)| v);
+    let items: Vec<&Ident> = root.iter().map(|&(_, item)| item).chain(additions.iter().cloned()).collect();
+    let indexes: Vec<usize> = (0..items.len()).collect();
+    let from_index = items.iter().zip(indexes.iter()).map(|(item, i)| quote!(#i => Some(#name::#item),));
+    let index = items.iter().zip(indexes.iter()).map(|(item, i)| quote!(#name::#item => #i,));
+    let copy = items.iter().map(|item| quote!(#name::#item => #name::#item,));
+
+    Ok(quote! {
+        impl #impl_generics ::asn1::Enumerated for #name #ty_generics #where_clause {
+            const NUM_ROOT: usize = #num_root;
+            const EXTENSIBLE: bool = #extensible;
+
+            fn from_index(i: usize) -> Option<Self> {
+                match i {
+                    #(#from_index)*
+                    _ => None,
+                }
+            }
+
+            fn index(&self) -> usize {
+                match *self {
+                    #(#index)*
+                }
+            }
+        }
+
+        impl #impl_generics ::asn1::aper::APerElement for #name #ty_generics #where_clause {
+            const CONSTRAINTS: ::asn1::aper::Constraints = ::asn1::aper::UNCONSTRAINED;
+
+            fn from_aper(decoder: &mut ::asn1::aper::Decoder, constraints: ::asn1::aper::Constraints)
+                         -> Result<Self, ::asn1::aper::DecodeError> {
+                match <::asn1::EnumeratedValue<Self> as ::asn1::aper::APerElement>::from_aper(decoder, constraints)? {
+                    ::asn1::EnumeratedValue::Known(x) => Ok(x),
+                    ::asn1::EnumeratedValue::Unknown(n) => {
+                        Err(::asn1::aper::DecodeError::InvalidEnumerated(#num_root as i64 + n as i64))
+                    }
+                }
+            }
+
+            fn to_aper(&self, constraints: ::asn1::aper::Constraints)
+                       -> Result<::asn1::aper::Encoding, ::asn1::aper::EncodeError> {
+                ::asn1::aper::Encoder::encode(|enc| ::asn1::aper::APerElement::write_aper(self, enc, constraints))
+            }
+
+            fn write_aper(&self, enc: &mut ::asn1::aper::Encoder, constraints: ::asn1::aper::Constraints)
+                          -> Result<(), ::asn1::aper::EncodeError> {
+                let value = ::asn1::EnumeratedValue::Known(match *self {
+                    #(#copy)*
+                });
+                ::asn1::aper::APerElement::write_aper(&value, enc, constraints)
+            }
+        }
+    })
+}
@@ -0,0 +1,194 @@
+//! Custom derives for the `asn1` crate.
+//!
+//! The SEQUENCE, SET, CHOICE and ENUMERATED derives are provided by `#[derive(APerElement)]`, which picks one based on
+//! what it's applied to. Named bits are derived separately with `#[derive(NamedBit)]`.
+//!
+//! # SEQUENCE
+//!
+//! Deriving `APerElement` on a struct treats it as an ASN.1 SEQUENCE, whose components are encoded in declaration
+//! order. `Option<T>` fields are OPTIONAL components, and fields with a `default` are DEFAULT components. Each of
+//! these has a bit in the sequence preamble which indicates if the component is present in the encoding.
+//!
+//! A struct with `#[aper(extensible)]` has an extension marker. Fields marked `#[aper(extension)]` are extension
+//! additions, which must be `Option<T>` fields declared after all of the root components, and imply `extensible`.
+//! An extension addition group is a single addition whose type is a struct holding the group's components. Additions
+//! that aren't known to the struct are skipped when decoding.
+//!
+//! Constraints on a field are given with the `aper` attribute. Bounds are inclusive, as in ASN.1 notation, and
+//! either bound of a range can be left open. Any constraint not given falls back to the field type's `CONSTRAINTS`.
+//!
+//! | Attribute                   | ASN.1                 |
+//! |-----------------------------|-----------------------|
+//! | `#[aper(value(0..255))]`    | `INTEGER (0..255)`    |
+//! | `#[aper(value(0..7, ...))]` | `INTEGER (0..7, ...)` |
+//! | `#[aper(from("0123"))]`     | `FROM ("0123")`       |
+//! | `#[aper(from('A'..'Z'))]`   | `FROM ("A".."Z")`     |
+//! | `#[aper(size(4))]`          | `SIZE (4)`            |
+//! | `#[aper(size(1..))]`        | `SIZE (1..MAX)`       |
+//! | `#[aper(size(1..4, ...))]`  | `SIZE (1..4, ...)`    |
+//! | `#[aper(default = 5)]`      | `DEFAULT 5`           |
+//! | `#[aper(extension)]`        | after `...`           |
+//!
+//! The components of a REAL are limited with `with_components`, which takes any of `mantissa`, `base` and `exponent`,
+//! as in `#[aper(with_components(mantissa(-16777215..16777215), base(2), exponent(-125..128)))]`.
+//!
+//! ```
+//! #[macro_use]
+//! extern crate asn1_derive;
+//! extern crate asn1;
+//! use asn1::BitString;
+//!
+//! // Foo ::= SEQUENCE {
+//! //     bar BIT STRING (SIZE(4)),
+//! //     baz INTEGER (0..255) OPTIONAL,
+//! //     qux INTEGER (0..7) DEFAULT 3
+//! // }
+//! #[derive(APerElement)]
+//! struct Foo {
+//!     #[aper(size(4))]
+//!     bar: BitString,
+//!     #[aper(value(0..255))]
+//!     baz: Option<u8>,
+//!     #[aper(value(0..7), default = 3)]
+//!     qux: u8,
+//! }
+//! ```
+//!
+//! # SET
+//!
+//! A struct with `#[aper(set)]` is an ASN.1 SET, which is encoded like a SEQUENCE whose root components are sorted
+//! into the canonical order of their tags. Each component's tag is given with `tag`, such as `#[aper(tag(2))]` for
+//! `[2]` or `#[aper(tag(application 1))]` for `[APPLICATION 1]`, and the classes `universal` and `private` are also
+//! accepted. Either every root component has a tag or none does, in which case they're automatically tagged and stay
+//! in declaration order. Extension additions are always encoded in declaration order.
+//!
+//! ```
+//! // Bar ::= SET {
+//! //     a [1] INTEGER (0..255),
+//! //     b [0] BOOLEAN
+//! // }
+//! #[derive(APerElement)]
+//! #[aper(set)]
+//! struct Bar {
+//!     #[aper(tag(1), value(0..255))]
+//!     a: u8,
+//!     #[aper(tag(0))]
+//!     b: bool,
+//! }
+//! ```
+//!
+//! # CHOICE
+//!
+//! Deriving `APerElement` on an enum treats it as an ASN.1 CHOICE, whose alternatives are the variants. The fields
+//! of a variant are encoded like the components of a SEQUENCE, so a tuple variant with a single field simply holds the
+//! value of that alternative, and a unit variant holds NULL.
+//!
+//! An enum with `#[aper(extensible)]` has an extension marker. Variants marked `#[aper(extension)]` are extension
+//! additions, which must be declared after all of the root alternatives and imply `extensible`. Decoding an
+//! alternative that isn't known to the enum fails with `DecodeError::InvalidChoice`.
+//!
+//! ```
+//! // MyMsg ::= CHOICE {
+//! //     foo BIT STRING (SIZE(4)),
+//! //     bar SEQUENCE { a INTEGER (0..255), b BOOLEAN },
+//! //     ...,
+//! //     baz INTEGER (0..65535)
+//! // }
+//! #[derive(APerElement)]
+//! enum MyMsg {
+//!     Foo(#[aper(size(4))] BitString),
+//!     Bar { a: u8, b: bool },
+//!     #[aper(extension)]
+//!     Baz(u16),
+//! }
+//! ```
+//!
+//! # ENUMERATED
+//!
+//! Deriving `APerElement` on a C-like enum with `#[aper(enumerated)]` treats it as an ASN.1 ENUMERATED, and also
+//! implements `asn1::Enumerated` for it. Root items are given enumeration indexes in ascending order of their values,
+//! which must be integer literals, whatever order they're declared in. As with a CHOICE, `extensible` and
+//! `extension` give the extension marker and the extension additions. Decoding an addition that isn't known to the
+//! enum fails with `DecodeError::InvalidEnumerated`, so use `asn1::EnumeratedValue` where unknown additions should be
+//! kept.
+//!
+//! ```
+//! // Color ::= ENUMERATED { red (0), green (3), ..., blue (4) }
+//! #[derive(APerElement)]
+//! #[aper(enumerated)]
+//! enum Color {
+//!     Red = 0,
+//!     Green = 3,
+//!     #[aper(extension)]
+//!     Blue = 4,
+//! }
+//! ```
+//!
+//! # Named bits
+//!
+//! Deriving `NamedBit` on a C-like enum implements `asn1::NamedBit` for it, so that `asn1::NamedBits<T>` can hold a
+//! BIT STRING with those named bits. The discriminant of each item is the number of its bit.
+//!
+//! ```
+//! // Algorithms ::= BIT STRING { ciphering (0), integrity (1) } (SIZE (8))
+//! #[derive(NamedBit)]
+//! enum Algorithm {
+//!     Ciphering = 0,
+//!     Integrity = 1,
+//! }
+//!
+//! #[derive(APerElement)]
+//! struct Capabilities {
+//!     #[aper(size(8))]
+//!     algorithms: NamedBits<Algorithm>,
+//! }
+//! ```
+extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use]
+extern crate quote;
+#[macro_use]
+extern crate syn;
+
+mod attr;
+mod choice;
+mod enumerated;
+mod named_bit;
+mod sequence;
+
+use attr::TypeAttrs;
+use proc_macro::TokenStream;
+use syn::{Data, DeriveInput};
+
+#[proc_macro_derive(APerElement, attributes(aper))]
+pub fn derive_aper_element(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let ret = match input.data {
+        Data::Struct(ref s) => sequence::derive(&input, &s.fields),
+        Data::Enum(ref e) => {
+            match TypeAttrs::parse(&input.attrs) {
+                Ok(ref attrs) if attrs.enumerated => enumerated::derive(&input, e),
+                Ok(_) => choice::derive(&input, e),
+                Err(e) => Err(e),
+            }
+        }
+        Data::Union(_) => Err(syn::Error::new_spanned(&input.ident, "APerElement can not be derived for unions")),
+    };
+    match ret {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+#[proc_macro_derive(NamedBit)]
+pub fn derive_named_bit(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let ret = match input.data {
+        Data::Enum(ref e) => named_bit::derive(&input, e),
+        _ => Err(syn::Error::new_spanned(&input.ident, "NamedBit can only be derived for enums")),
+    };
+    match ret {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
@@ -0,0 +1,35 @@
+use proc_macro2::TokenStream;
+use syn::{self, DataEnum, DeriveInput, Fields};
+
+/// Derive `NamedBit` for a C-like enum, whose discriminants are the numbers of its bits.
+pub fn derive(input: &DeriveInput, data: &DataEnum) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    for variant in &data.variants {
+        match variant.fields {
+            Fields::Unit => {}
+            _ => return Err(syn::Error::new_spanned(variant, "named bits can't have fields")),
+        }
+    }
+    let items: Vec<_> = data.variants.iter().map(|v| &v.ident).collect();
+    let from_bit = items.iter().map(|item| quote!(i if i == #name::#item as usize => Some(#name::#item),));
+    let bit = items.iter().map(|item| quote!(#name::#item => #name::#item as usize,));
+
+    Ok(quote! {
+        impl #impl_generics ::asn1::NamedBit for #name #ty_generics #where_clause {
+            fn from_bit(i: usize) -> Option<Self> {
+                match i {
+                    #(#from_bit)*
+                    _ => None,
+                }
+            }
+
+            fn bit(&self) -> usize {
+                match *self {
+                    #(#bit)*
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,303 @@
+use attr::{FieldAttrs, TypeAttrs};
+use proc_macro2::{Span, TokenStream};
+use syn::{self, DeriveInput, Fields, GenericArgument, Ident, Index, Member, PathArguments, Type};
+
+/// A component of a SEQUENCE.
+pub struct Component {
+    /// How the component is accessed on `self`.
+    pub member: Member,
+    /// The local binding used for the component while decoding.
+    pub binding: Ident,
+    /// The type of the component's value, with any `Option` stripped.
+    pub ty: Type,
+    /// Whether the component is OPTIONAL, i.e. an `Option<T>` field.
+    pub optional: bool,
+    pub attrs: FieldAttrs,
+}
+
+impl Component {
+    /// Whether the component has a bit in the sequence preamble.
+    pub fn in_preamble(&self) -> bool {
+        self.optional || self.attrs.default.is_some()
+    }
+
+    /// Tokens for the `Constraints` of the component, falling back to the type's own constraints.
+    pub fn constraints(&self) -> TokenStream {
+        let ty = &self.ty;
+        let value = match self.attrs.value {
+            Some(ref c) => quote!(Some(#c)),
+            None => quote!(<#ty as ::asn1::aper::APerElement>::CONSTRAINTS.value),
+        };
+        let size = match self.attrs.size {
+            Some(ref c) => quote!(Some(#c)),
+            None => quote!(<#ty as ::asn1::aper::APerElement>::CONSTRAINTS.size),
+        };
+        quote!(::asn1::aper::Constraints { value: #value, size: #size })
+    }
+}
+
+/// Get the `T` of an `Option<T>`.
+fn option_inner(ty: &Type) -> Option<Type> {
+    let path = match *ty {
+        Type::Path(ref p) if p.qself.is_none() => &p.path,
+        _ => return None,
+    };
+    let seg = match path.segments.last() {
+        Some(seg) if seg.ident == "Option" => seg,
+        _ => return None,
+    };
+    match seg.arguments {
+        PathArguments::AngleBracketed(ref args) if args.args.len() == 1 => {
+            match args.args[0] {
+                GenericArgument::Type(ref t) => Some(t.clone()),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Collect the components of a struct.
+pub fn components(fields: &Fields) -> syn::Result<Vec<Component>> {
+    let mut ret = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let attrs = FieldAttrs::parse(&field.attrs)?;
+        let (member, binding) = match field.ident {
+            Some(ref ident) => (Member::Named(ident.clone()), ident.clone()),
+            None => (Member::Unnamed(Index::from(i)), Ident::new(&format!("__field{}", i), Span::call_site())),
+        };
+        let (ty, optional) = match option_inner(&field.ty) {
+            Some(inner) => (inner, true),
+            None => (field.ty.clone(), false),
+        };
+        if optional && attrs.default.is_some() {
+            return Err(syn::Error::new_spanned(field, "a component can not be both OPTIONAL and DEFAULT"));
+        }
+        ret.push(Component {
+            member,
+            binding,
+            ty,
+            optional,
+            attrs,
+        });
+    }
+    Ok(ret)
+}
+
+/// Statements that decode each component into its binding, preamble first.
+pub fn decode_components(components: &[Component]) -> TokenStream {
+    let num_preamble = components.iter().filter(|c| c.in_preamble()).count();
+    let preamble = if num_preamble > 0 {
+        quote!(let __preamble = ::asn1::SequencePreamble::decode(decoder, #num_preamble)?;)
+    } else {
+        quote!()
+    };
+    let fields = preamble_indexes(components).map(|(c, i)| {
+        let binding = &c.binding;
+        let ty = &c.ty;
+        let constraints = c.constraints();
+        if c.optional {
+            quote! {
+                let #binding = if __preamble.is_present(#i) {
+                    <Option<#ty> as ::asn1::aper::APerElement>::from_aper(decoder, #constraints)?
+                } else {
+                    None
+                };
+            }
+        } else if let Some(ref default) = c.attrs.default {
+            quote! {
+                let #binding: #ty = if __preamble.is_present(#i) {
+                    <#ty as ::asn1::aper::APerElement>::from_aper(decoder, #constraints)?
+                } else {
+                    #default
+                };
+            }
+        } else {
+            quote!(let #binding = <#ty as ::asn1::aper::APerElement>::from_aper(decoder, #constraints)?;)
+        }
+    });
+    quote! {
+        #preamble
+        #(#fields)*
+    }
+}
+
+/// Statements that write each component to `enc`, preamble first. `value` gives an expression that evaluates to a
+/// reference to a component's value.
+pub fn encode_components<F>(components: &[Component], value: F) -> TokenStream
+    where F: Fn(&Component) -> TokenStream
+{
+    let num_preamble = components.iter().filter(|c| c.in_preamble()).count();
+    let push = components.iter().filter(|c| c.in_preamble()).map(|c| {
+        let v = value(c);
+        match c.attrs.default {
+            Some(ref default) => quote!(__preamble.push(*#v != #default);),
+            None => quote!(__preamble.push_optional(#v);),
+        }
+    });
+    let preamble = if num_preamble > 0 {
+        quote! {
+            let mut __preamble = ::asn1::SequencePreamble::new();
+            #(#push)*
+            ::asn1::aper::APerElement::write_aper(&__preamble, enc, ::asn1::aper::UNCONSTRAINED)?;
+        }
+    } else {
+        quote!()
+    };
+    let fields = preamble_indexes(components).map(|(c, i)| {
+        let v = value(c);
+        let ty = &c.ty;
+        let constraints = c.constraints();
+        if c.optional {
+            quote!(<Option<#ty> as ::asn1::aper::APerElement>::write_aper(#v, enc, #constraints)?;)
+        } else if c.attrs.default.is_some() {
+            quote! {
+                if __preamble.is_present(#i) {
+                    <#ty as ::asn1::aper::APerElement>::write_aper(#v, enc, #constraints)?;
+                }
+            }
+        } else {
+            quote!(<#ty as ::asn1::aper::APerElement>::write_aper(#v, enc, #constraints)?;)
+        }
+    });
+    quote! {
+        #preamble
+        #(#fields)*
+    }
+}
+
+/// Pair each component with the index of its bit in the sequence preamble, if it has one.
+fn preamble_indexes(components: &[Component]) -> impl Iterator<Item = (&Component, usize)> {
+    components.iter().scan(0, |next, c| {
+        let i = *next;
+        if c.in_preamble() {
+            *next += 1;
+        }
+        Some((c, i))
+    })
+}
+
+/// Tokens that construct `path` from the bindings of its components.
+pub fn construct(path: TokenStream, fields: &Fields, components: &[Component]) -> TokenStream {
+    let bindings = components.iter().map(|c| &c.binding);
+    let members = components.iter().map(|c| &c.member);
+    match *fields {
+        Fields::Named(_) => quote!(#path { #(#members: #bindings),* }),
+        Fields::Unnamed(_) => quote!(#path(#(#bindings),*)),
+        Fields::Unit => quote!(#path),
+    }
+}
+
+/// Sort the root components of a SET into the canonical order of their tags, in which PER encodes them. Without any
+/// tags, the components are automatically tagged and so stay in the order they're declared.
+fn canonical_order(root: &mut [Component]) -> syn::Result<()> {
+    if root.iter().all(|c| c.attrs.tag.is_none()) {
+        return Ok(());
+    }
+    for c in root.iter() {
+        if c.attrs.tag.is_none() {
+            return Err(syn::Error::new_spanned(&c.member, "every component of a SET with tags needs a tag"));
+        }
+    }
+    root.sort_by_key(|c| c.attrs.tag);
+    for pair in root.windows(2) {
+        if pair[0].attrs.tag == pair[1].attrs.tag {
+            return Err(syn::Error::new_spanned(&pair[1].member, "the components of a SET need distinct tags"));
+        }
+    }
+    Ok(())
+}
+
+/// Derive `APerElement` for a struct, treating it as a SEQUENCE, or as a SET with `#[aper(set)]`.
+pub fn derive(input: &DeriveInput, fields: &Fields) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let type_attrs = TypeAttrs::parse(&input.attrs)?;
+    let components = components(fields)?;
+
+    let mut in_additions = false;
+    for c in &components {
+        if c.attrs.extension {
+            if !c.optional {
+                return Err(syn::Error::new_spanned(&c.member, "an extension addition must be an Option"));
+            }
+            in_additions = true;
+        } else if in_additions {
+            return Err(syn::Error::new_spanned(&c.member, "root components must precede extension additions"));
+        }
+    }
+    let construct = construct(quote!(#name), fields, &components);
+    let (additions, mut root): (Vec<Component>, Vec<Component>) =
+        components.into_iter().partition(|c| c.attrs.extension);
+    if type_attrs.set {
+        canonical_order(&mut root)?;
+    }
+    let extensible = type_attrs.extensible || !additions.is_empty();
+
+    // Additions that aren't known are skipped, since each is wrapped in an open type
+    let (decode_extension, decode_additions, encode_extension, encode_additions) = if extensible {
+        let decode = additions.iter().enumerate().map(|(i, c)| {
+            let binding = &c.binding;
+            let ty = &c.ty;
+            let constraints = c.constraints();
+            quote!(let #binding = __ext.decode::<#ty>(#i, #constraints)?;)
+        });
+        // Each addition is encoded on its own, in the same variant and mode of PER as the rest
+        let push = additions.iter().map(|c| {
+            let member = &c.member;
+            let ty = &c.ty;
+            let constraints = c.constraints();
+            quote!(__ext.push::<#ty>(enc, &self.#member, #constraints)?;)
+        });
+        (quote!(let __is_ext = <bool as ::asn1::aper::APerElement>::from_aper(decoder, ::asn1::aper::UNCONSTRAINED)?;),
+         quote! {
+             let __ext = if __is_ext {
+                 <::asn1::aper::ExtensionAdditions as ::asn1::aper::APerElement>::from_aper(decoder,
+                                                                                          ::asn1::aper::UNCONSTRAINED)?
+             } else {
+                 ::asn1::aper::ExtensionAdditions::new()
+             };
+             #(#decode)*
+         },
+         quote! {
+             let mut __ext = ::asn1::aper::ExtensionAdditions::new();
+             #(#push)*
+             ::asn1::aper::APerElement::write_aper(&__ext.is_any_present(), enc, ::asn1::aper::UNCONSTRAINED)?;
+         },
+         quote!(::asn1::aper::APerElement::write_aper(&__ext, enc, ::asn1::aper::UNCONSTRAINED)?;))
+    } else {
+        (quote!(), quote!(), quote!(), quote!())
+    };
+    let decode = decode_components(&root);
+    let encode = encode_components(&root, |c| {
+        let member = &c.member;
+        quote!((&self.#member))
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::asn1::aper::APerElement for #name #ty_generics #where_clause {
+            const CONSTRAINTS: ::asn1::aper::Constraints = ::asn1::aper::UNCONSTRAINED;
+
+            fn from_aper(decoder: &mut ::asn1::aper::Decoder, _: ::asn1::aper::Constraints)
+                         -> Result<Self, ::asn1::aper::DecodeError> {
+                #decode_extension
+                #decode
+                #decode_additions
+                Ok(#construct)
+            }
+
+            fn to_aper(&self, constraints: ::asn1::aper::Constraints)
+                       -> Result<::asn1::aper::Encoding, ::asn1::aper::EncodeError> {
+                ::asn1::aper::Encoder::encode(|enc| ::asn1::aper::APerElement::write_aper(self, enc, constraints))
+            }
+
+            fn write_aper(&self, enc: &mut ::asn1::aper::Encoder, _: ::asn1::aper::Constraints)
+                          -> Result<(), ::asn1::aper::EncodeError> {
+                #encode_extension
+                #encode
+                #encode_additions
+                Ok(())
+            }
+        }
+    })
+}
@@ -1,19 +1,40 @@
-use byteorder::{ByteOrder, BigEndian};
+use std::cmp;
 use super::*;
+use utils::{range_bits, size_bounds, uint_octets};
 
 #[derive(Debug, PartialEq)]
 pub enum DecodeError {
-    InvalidChoice,
+    /// The index of a CHOICE alternative that the decoder does not know about.
+    InvalidChoice(i64),
+    /// The index of an ENUMERATED item that the decoder does not know about.
+    InvalidEnumerated(i64),
+    /// A character that isn't in the permitted alphabet of a string, or the index of one beyond the end of it.
+    InvalidCharacter(u32),
     MalformedLength,
     MalformedInt,
+    /// Padding bits before an octet boundary are not all 0, which is only checked if enabled with
+    /// [Decoder::set_check_padding()](struct.Decoder.html#method.set_check_padding).
+    MalformedPadding,
+    /// The octets of a string aren't valid in its character set, e.g. a `Utf8String` that isn't valid UTF-8.
+    MalformedString,
+    /// The contents of an OBJECT IDENTIFIER or RELATIVE-OID are malformed, or hold an arc that doesn't fit in a `u64`.
+    MalformedObjectIdentifier,
+    /// The contents of a REAL are malformed, or hold a mantissa or exponent that doesn't fit in an `i64`.
+    MalformedReal,
+    /// The string or fields of a time type aren't a valid time, or are a local GeneralizedTime.
+    MalformedTime,
     MissingSizeConstraint,
     MissingValueConstraint,
     NotEnoughBits,
     NotImplemented,
+    UnexpectedFragment,
 }
 
 /// A bit-wise cursor used to decode aligned PER messagses.
 ///
+/// A `Decoder` can also decode unaligned PER messages if it is constructed with
+/// [new_unaligned()](#method.new_unaligned). See the [uper](../uper/index.html) module.
+///
 /// # Examples
 ///
 /// ```
@@ -25,10 +46,13 @@ pub enum DecodeError {
 /// let x = i16::from_aper(&mut d, UNCONSTRAINED).unwrap();
 /// println!("x = {}", x); // Prints x = 43
 /// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Decoder<'a> {
     data: &'a [u8],
     len: usize,
     pos: usize,
+    aligned: bool,
+    check_padding: bool,
 }
 
 impl<'a> Decoder<'a> {
@@ -38,10 +62,75 @@ impl<'a> Decoder<'a> {
             data: data,
             len: 8 * data.len(),
             pos: 0,
+            aligned: true,
+            check_padding: false,
+        }
+    }
+
+    /// Construct a new `Decoder` for an unaligned PER encoding with an array of bytes.
+    pub fn new_unaligned(data: &'a [u8]) -> Decoder<'a> {
+        Decoder {
+            data,
+            len: 8 * data.len(),
+            pos: 0,
+            aligned: false,
+            check_padding: false,
+        }
+    }
+
+    /// Construct a new `Decoder` over `data` that follows the same variant of PER as this one. This is useful for
+    /// decoding a value encoded on its own, e.g. the contents of an open type.
+    pub fn nested<'b>(&self, data: &'b [u8]) -> Decoder<'b> {
+        Decoder {
+            data,
+            len: 8 * data.len(),
+            pos: 0,
+            aligned: self.aligned,
+            check_padding: self.check_padding,
+        }
+    }
+
+    /// Check if the `Decoder` follows the aligned variant of PER.
+    pub fn is_aligned(&self) -> bool {
+        self.aligned
+    }
+
+    /// Set whether [align()](#method.align) checks that the padding bits it skips are all 0.
+    pub fn set_check_padding(&mut self, check: bool) {
+        self.check_padding = check;
+    }
+
+    /// Skip the padding bits up to the next octet boundary, which aligned PER inserts before length determinants and
+    /// other octet-aligned fields. This does nothing if the `Decoder` follows the unaligned variant of PER.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let data = b"\x80\x2b";
+    /// let mut d = aper::Decoder::new(data);
+    /// let b = d.read(1).unwrap();
+    /// d.align().unwrap();
+    /// let x = d.read_u8().unwrap();
+    /// println!("b = {}, x = {}", b, x); // Prints b = 1, x = 43
+    /// ```
+    pub fn align(&mut self) -> Result<(), DecodeError> {
+        let n = (8 - self.pos % 8) % 8;
+        if !self.aligned || n == 0 {
+            return Ok(());
+        }
+
+        let ret = self.read(n);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        if self.check_padding && ret.unwrap() != 0 {
+            return Err(DecodeError::MalformedPadding);
         }
+        Ok(())
     }
 
-    /// Read `n` bits. Where `0 <= n <= 8`. See [read_to_vec()](#method.read_to_vec) for larger `n`.
+    /// Read `n` bits. Where `0 <= n <= 8`. See [read_bits()](#method.read_bits) and
+    /// [read_to_vec()](#method.read_to_vec) for larger `n`.
     /// Returns an `Err` if the read would consume more bits than are available. Else, returns the bits as a u8 with
     /// left-padding.
     ///
@@ -59,46 +148,69 @@ impl<'a> Decoder<'a> {
     /// println!("x = 0x{:X}"); // Prints x = 0x07
     /// ```
     pub fn read(&mut self, n: usize) -> Result<u8, DecodeError> {
-        if n == 0 {
-            return Ok(0);
+        let ret = self.read_bits(n);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
         }
+        Ok(ret.unwrap() as u8)
+    }
+
+    /// Read `n` bits, where `0 <= n <= 64`.
+    /// Returns an `Err` if the read would consume more bits than are available. Else, returns the bits as a u64 with
+    /// left-padding.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than 64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let data = b"\xff\xf3";
+    /// let mut d = aper::Decoder::new(data);
+    /// let x = d.read_bits(12).unwrap();
+    /// println!("x = 0x{:X}", x); // Prints x = 0xFFF
+    /// ```
+    pub fn read_bits(&mut self, n: usize) -> Result<u64, DecodeError> {
+        assert!(n <= 64, "can't read more than 64 bits at once");
         if self.pos + n > self.len {
             return Err(DecodeError::NotEnoughBits);
         }
 
-        let l_bucket = self.pos / 8;
-        let h_bucket = (self.pos + n) / 8;
-        let h_off = (self.pos + n) - h_bucket * 8;
-        let mut ret: u8;
+        let mut ret: u64 = 0;
+        let mut left = n;
 
-        if l_bucket == h_bucket {
-            let mask = (0xFF >> (8 - n)) << (8 - h_off);
-            ret = (self.data[l_bucket] & mask) >> (8 - h_off);
-        } else if l_bucket < h_bucket && h_off == 0 {
-            let mask = 0xFF >> (8 - n);
-            ret = self.data[l_bucket] & mask;
-        } else {
-            let l_mask = 0xFF >> (8 - (n - h_off));
-            let h_mask = 0xFF << (8 - h_off);
-            ret = (self.data[l_bucket] & l_mask) << h_off;
-            ret |= (self.data[h_bucket] & h_mask) >> (8 - h_off);
+        // The rest of the current byte
+        let off = self.pos % 8;
+        if off > 0 && left > 0 {
+            let k = cmp::min(8 - off, left);
+            ret = (self.data[self.pos / 8] as u64 >> (8 - off - k)) & ((1 << k) - 1);
+            self.pos += k;
+            left -= k;
+        }
+
+        while left >= 8 {
+            ret = (ret << 8) | self.data[self.pos / 8] as u64;
+            self.pos += 8;
+            left -= 8;
+        }
+
+        if left > 0 {
+            ret = (ret << left) | (self.data[self.pos / 8] as u64 >> (8 - left));
+            self.pos += left;
         }
-        self.pos += n;
         Ok(ret)
     }
 
     /// Read a byte.
     pub fn read_u8(&mut self) -> Result<u8, DecodeError> {
-        let ret = self.read(8);
-        if ret.is_err() {
-            return Err(DecodeError::NotEnoughBits);
-        }
-        Ok(ret.unwrap())
+        self.read(8)
     }
 
     /// Read `len` bits into `content`.
-    /// Returns an `Err` if the read would consume more bits than are available. Else, the bits as a `u8`s with
-    /// left-padding are pushed onto `content`.
+    /// Returns an `Err` if the read would consume more bits than are available. Else, the bits are pushed onto
+    /// `content` a byte at a time, with left-padding in the last byte if `len` isn't a multiple of 8. Whole bytes are
+    /// copied straight from the input if the `Decoder` is on an octet boundary.
     ///
     /// # Examples
     ///
@@ -108,40 +220,71 @@ impl<'a> Decoder<'a> {
     /// let data = b"\xff\xf3";
     /// let mut d = aper::Decoder::new(data);
     /// let mut x: Vec<u8> = Vec::with_capacity(2);
-    /// self.read_to_vec(&mut content, 12).unwrap();
-    /// println!("x = {:?}"); // Prints x = [255, 15]
+    /// d.read_to_vec(&mut x, 12).unwrap();
+    /// println!("x = {:?}", x); // Prints x = [255, 15]
     /// ```
     pub fn read_to_vec(&mut self, content: &mut Vec<u8>, len: usize) -> Result<(), DecodeError> {
-        if len == 0 {
-            return Ok(());
-        }
         if self.pos + len > self.len {
             return Err(DecodeError::NotEnoughBits);
         }
 
-        if len < 8 {
-            content.push(self.read(len).unwrap());
+        let (num_bytes, rest) = (len / 8, len % 8);
+        let off = self.pos % 8;
+        if off == 0 {
+            let first = self.pos / 8;
+            content.extend_from_slice(&self.data[first..first + num_bytes]);
+            self.pos += num_bytes * 8;
         } else {
-            let num_bytes = (len as f64 / 8.).ceil() as usize;
-            for i in 0..num_bytes {
-                println!("read byte {}", i);
-                content.push(self.read_u8().unwrap());
+            content.reserve(num_bytes + 1);
+            for _ in 0..num_bytes {
+                content.push(self.read_bits(8).unwrap() as u8);
             }
-            self.pos -= len % 8;
+        }
+
+        if rest > 0 {
+            content.push(self.read_bits(rest).unwrap() as u8);
         }
         Ok(())
     }
 
-    /// Decode an aligned PER length determinant
+    /// Decode an aligned PER length determinant.
+    ///
+    /// Returns `DecodeError::UnexpectedFragment` if the determinant is the first of a fragmented length. See
+    /// [decode_fragmented()](#method.decode_fragmented) for content that may exceed 16K items.
     pub fn decode_length(&mut self) -> Result<usize, DecodeError> {
+        let ret = self.decode_length_fragment();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+
+        let (len, more) = ret.unwrap();
+        if more {
+            return Err(DecodeError::UnexpectedFragment);
+        }
+        Ok(len)
+    }
+
+    /// Decode a single aligned PER length determinant, which may describe one fragment of a larger length.
+    /// Returns the number of items that follow the determinant, along with `true` if another length determinant
+    /// follows those items.
+    pub fn decode_length_fragment(&mut self) -> Result<(usize, bool), DecodeError> {
+        let ret = self.align();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+
         let mut ret = self.read_u8();
         if ret.is_err() {
             return Err(DecodeError::MalformedLength);
         }
 
         let mut b = ret.unwrap();
-        if b & LENGTH_DET_FRAG > 0 {
-            return Err(DecodeError::NotImplemented);
+        if b & LENGTH_DET_FRAG == LENGTH_DET_FRAG {
+            let units = (b & LENGTH_MASK_FRAG) as usize;
+            if units == 0 || units > LENGTH_FRAG_MAX_UNITS {
+                return Err(DecodeError::MalformedLength);
+            }
+            return Ok((units * LENGTH_FRAG_UNIT, true));
         } else if b & LENGTH_DET_LONG > 0 {
             let len: usize = (b & LENGTH_MASK_LONG) as usize;
             ret = self.read_u8();
@@ -149,16 +292,191 @@ impl<'a> Decoder<'a> {
                 return Err(DecodeError::MalformedLength);
             }
             b = ret.unwrap();
-            return Ok((len << 8) + b as usize);
+            return Ok(((len << 8) + b as usize, false));
         }
-        Ok((b & LENGTH_MASK_SHORT) as usize)
+        Ok(((b & LENGTH_MASK_SHORT) as usize, false))
+    }
+
+    /// Decode a length-prefixed run of items whose length determinant may be fragmented.
+    ///
+    /// `f` is called once per fragment with the number of items in that fragment, and is expected to consume them
+    /// from the decoder. Returns the total number of items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let data = b"\x02\x46\x4f";
+    /// let mut d = aper::Decoder::new(data);
+    /// let mut content: Vec<u8> = Vec::new();
+    /// let len = d.decode_fragmented(|d, n| {
+    ///     for _ in 0..n {
+    ///         content.push(d.read_u8()?);
+    ///     }
+    ///     Ok(())
+    /// }).unwrap();
+    /// println!("len = {}", len); // Prints len = 2
+    /// ```
+    pub fn decode_fragmented<F>(&mut self, mut f: F) -> Result<usize, DecodeError>
+        where F: FnMut(&mut Decoder<'a>, usize) -> Result<(), DecodeError>
+    {
+        let mut total: usize = 0;
+        loop {
+            let ret = self.decode_length_fragment();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+
+            let (len, more) = ret.unwrap();
+            if len > 0 {
+                let res = f(self, len);
+                if res.is_err() {
+                    return Err(res.err().unwrap());
+                }
+            }
+            total += len;
+
+            if !more {
+                return Ok(total);
+            }
+        }
+    }
+
+    /// Decode a run of items preceded by the length that the SIZE constraint `size` calls for. See
+    /// [Encoder::encode_sized()](struct.Encoder.html#method.encode_sized) for how the length is encoded.
+    ///
+    /// `f` is called as for [decode_fragmented()](#method.decode_fragmented), and only once with all of the items if
+    /// they aren't fragmented. Returns the total number of items, or `DecodeError::MalformedLength` if it violates a
+    /// constraint that isn't extensible.
+    pub fn decode_sized<F>(&mut self, size: Option<Constraint>, mut f: F) -> Result<usize, DecodeError>
+        where F: FnMut(&mut Decoder<'a>, usize) -> Result<(), DecodeError>
+    {
+        let (lb, ub, extensible) = size_bounds(size);
+        if extensible {
+            let ret = self.read(1);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            if ret.unwrap() == 1 {
+                return self.decode_fragmented(f);
+            }
+        }
+
+        let len = match ub {
+            Some(ub) if ub <= LENGTH_CONSTRAINED_MAX => {
+                let len = if lb == ub {
+                    ub
+                } else {
+                    let ret = self.decode_int(Some(lb as i64), Some(ub as i64));
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                    ret.unwrap() as usize
+                };
+                if len > 0 {
+                    let ret = f(self, len);
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                }
+                len
+            }
+            _ => {
+                let ret = self.decode_fragmented(f);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                ret.unwrap()
+            }
+        };
+
+        if len < lb || ub.is_some_and(|ub| len > ub) {
+            return Err(DecodeError::MalformedLength);
+        }
+        Ok(len)
+    }
+
+    /// Decode a normally small non-negative whole number, such as the index of a CHOICE extension alternative.
+    pub fn decode_normally_small(&mut self) -> Result<u64, DecodeError> {
+        let ret = self.read(1);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+
+        if ret.unwrap() == 0 {
+            let ret = self.read(6);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            return Ok(ret.unwrap() as u64);
+        }
+
+        let ret = self.decode_length();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+
+        let len = ret.unwrap();
+        if len == 0 || len > 8 {
+            return Err(DecodeError::MalformedInt);
+        }
+        self.read_bits(len * 8)
+    }
+
+    /// Decode a normally small length, such as the length of the extension addition bitmap of a SEQUENCE.
+    ///
+    /// Lengths up to 64 are encoded in 7 bits, and anything larger as a length determinant.
+    pub fn decode_normally_small_length(&mut self) -> Result<usize, DecodeError> {
+        let ret = self.read(1);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+
+        if ret.unwrap() == 0 {
+            let ret = self.read(6);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            return Ok(ret.unwrap() as usize + 1);
+        }
+
+        let ret = self.decode_length();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        if ret.as_ref().unwrap() == &0 {
+            return Err(DecodeError::MalformedLength);
+        }
+        ret
+    }
+
+    /// Decode an open type, returning its contents octets. The value held in an open type is a complete encoding of
+    /// its own, and can be decoded from the contents octets with a new `Decoder`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let data = b"\x02\x80\x2b";
+    /// let mut d = aper::Decoder::new(data);
+    /// let content = d.decode_open_type().unwrap();
+    /// let x = i16::from_aper(&mut aper::Decoder::new(&content), UNCONSTRAINED).unwrap();
+    /// println!("x = {}", x); // Prints x = 43
+    /// ```
+    pub fn decode_open_type(&mut self) -> Result<Vec<u8>, DecodeError> {
+        let mut content: Vec<u8> = Vec::new();
+        let ret = self.decode_fragmented(|decoder, n| decoder.read_to_vec(&mut content, n * 8));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(content)
     }
 
     /// Decode an Aligned PER integer between `min` and `max`
     ///
-    /// You can decode the Rust primitive (u)ints: `i8`, `i16`, `i32`, `u8`, `u16`, and `u32` using their respective
-    /// `from_aper` constructors. `decode_int` is useful if you want to decode an integer field that exists somewhere
-    /// between or beyond the primitive widths.
+    /// You can decode the Rust primitive (u)ints: `i8` to `i128` and `u8` to `u128` using their respective `from_aper`
+    /// constructors. `decode_int` is useful if you want to decode an integer field that exists somewhere between or
+    /// beyond the primitive widths. Values that don't fit in an `i64` fail with `DecodeError::MalformedInt`, so decode
+    /// those as an [asn1::BigInt](../struct.BigInt.html), as do values out of bounds. See
+    /// [encode_int()](fn.encode_int.html) for how each kind of integer is encoded.
     ///
     /// # Examples
     ///
@@ -174,81 +492,112 @@ impl<'a> Decoder<'a> {
     /// println!("y = {}", y); // Prints y = 503
     /// ```
     pub fn decode_int(&mut self, min: Option<i64>, max: Option<i64>) -> Result<i64, DecodeError> {
-        if min.is_some() && max.is_some() {
-            // constrained
-            let l = min.unwrap();
-            let h = max.unwrap();
-            let range = h - l + 1;
-            let n_bits = (range as f64).log2().ceil() as usize;
+        if !self.aligned {
+            return self.decode_int_unaligned(min, max);
+        }
 
-            if n_bits < 8 {
-                let ret = self.read(n_bits);
+        if let (Some(l), Some(h)) = (min, max) {
+            // constrained
+            let range = h.wrapping_sub(l) as u64 as u128 + 1;
+            let v = if range <= 65536 {
+                // A bit-field of up to 255 values, else one or two octets
+                let n = if range <= 255 {
+                    range_bits(l, h)
+                } else if range == 256 {
+                    8
+                } else {
+                    16
+                };
+                if range > 255 {
+                    let ret = self.align();
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                }
+                let ret = self.read_bits(n);
                 if ret.is_err() {
                     return Err(ret.err().unwrap());
                 }
-                return Ok(ret.unwrap() as i64 + l);
-            }
-
-            // Simple case, no length determinant
-            if n_bits <= 16 {
-                let mut ret = self.read_u8();
+                ret.unwrap()
+            } else {
+                // The number of octets is a constrained integer in [1, octets of range]
+                let ret = self.decode_int(Some(1), Some(uint_octets((range - 1) as u64) as i64));
                 if ret.is_err() {
                     return Err(ret.err().unwrap());
                 }
+                let len = ret.unwrap() as usize;
 
-                let mut b: u16 = ret.unwrap() as u16;
-                if n_bits > 8 {
-                    ret = self.read_u8();
-                    if ret.is_err() {
-                        return Err(ret.err().unwrap());
-                    }
-                    b = (ret.unwrap() as u16) + (b << 8);
+                let ret = self.align();
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
                 }
-                return Ok(b as i64 + l);
-            }
+                let ret = self.read_bits(len * 8);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                ret.unwrap()
+            };
 
-            // Need to decode length determinant
-            let ret = self.decode_length();
-            if ret.is_err() {
-                return Err(ret.err().unwrap());
+            if v as u128 >= range {
+                return Err(DecodeError::MalformedInt);
             }
+            return Ok(l.wrapping_add(v as i64));
+        }
 
-            let len: usize = ret.unwrap();
-            if len > 8 {
-                unimplemented!();
-            }
+        self.decode_unconstrained_int(min)
+    }
 
-            let mut content: Vec<u8> = Vec::with_capacity(len);
-            let res = self.read_to_vec(&mut content, len * 8);
-            if res.is_err() {
-                return Err(res.err().unwrap());
+    /// Decode an unaligned PER integer between `min` and `max`.
+    fn decode_int_unaligned(&mut self, min: Option<i64>, max: Option<i64>) -> Result<i64, DecodeError> {
+        if let (Some(l), Some(h)) = (min, max) {
+            // constrained, always a minimal bit-field
+            let ret = self.read_bits(range_bits(l, h));
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
             }
 
-            let val = BigEndian::read_uint(&content.as_slice(), len) as i64 + l;
+            let val = l.wrapping_add(ret.unwrap() as i64);
             if val < l || val > h {
                 return Err(DecodeError::MalformedInt);
             }
             return Ok(val);
         }
 
+        self.decode_unconstrained_int(min)
+    }
+
+    /// Decode a semi-constrained or unconstrained integer from its minimal octets preceded by a length determinant.
+    fn decode_unconstrained_int(&mut self, min: Option<i64>) -> Result<i64, DecodeError> {
         let ret = self.decode_length();
         if ret.is_err() {
             return Err(ret.err().unwrap());
         }
 
+        // Wider values need a `BigInt`
         let len = ret.unwrap();
-        let mut content: Vec<u8> = Vec::with_capacity(len);
-        let res = self.read_to_vec(&mut content, len * 8);
-        if res.is_err() {
-            return Err(res.err().unwrap());
+        if len == 0 || len > 8 {
+            return Err(DecodeError::MalformedInt);
+        }
+        let ret = self.read_bits(len * 8);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
         }
 
-        if min.is_none() {
-            // unconstrained
-            Ok(BigEndian::read_int(&content, len))
-        } else {
+        let v = ret.unwrap();
+        match min {
             // semiconstrained
-            Ok(BigEndian::read_int(&content, len) + min.unwrap())
+            Some(l) => {
+                let val = l.wrapping_add(v as i64);
+                if val < l {
+                    return Err(DecodeError::MalformedInt);
+                }
+                Ok(val)
+            }
+            // unconstrained, sign-extend the two's complement value
+            None => {
+                let shift = 64 - len * 8;
+                Ok(((v << shift) as i64) >> shift)
+            }
         }
     }
 }
@@ -0,0 +1,524 @@
+use std::cmp;
+use super::*;
+use utils::{int_octets, range_bits, size_bounds, uint_octets};
+
+/// A streaming writer for PER encodings.
+///
+/// Elements write their encodings straight into an `Encoder` with
+/// [APerElement::write_aper()](trait.APerElement.html#method.write_aper), so a whole message is encoded into a single
+/// buffer without a separate allocation for each field. Like a [Decoder](struct.Decoder.html), an `Encoder` follows
+/// either the aligned or the unaligned variant of PER, and [align()](#method.align) only pads the aligned variant.
+///
+/// An `Encoder` may also be set to CANONICAL-PER with [set_canonical()](#method.set_canonical), in which case the
+/// elements of a [SetOf](../struct.SetOf.html) are sorted by their encodings, so that equal values always give the
+/// same bytes.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::aper::{self, APerElement, Encoder, UNCONSTRAINED};
+///
+/// let mut enc = Encoder::new();
+/// true.write_aper(&mut enc, UNCONSTRAINED).unwrap();
+/// enc.encode_int(43, Some(0), Some(65535)).unwrap();
+/// println!("enc = {:?}", *enc.into_encoding().bytes()); // Prints enc = [128, 0, 43]
+/// ```
+#[derive(Debug)]
+pub struct Encoder {
+    bytes: Vec<u8>,
+    r_padding: usize,
+    align: Option<usize>,
+    aligned: bool,
+    canonical: bool,
+}
+
+impl Default for Encoder {
+    fn default() -> Encoder {
+        Encoder::new()
+    }
+}
+
+impl Encoder {
+    /// Construct a new, empty `Encoder` for aligned PER.
+    pub fn new() -> Encoder {
+        Encoder {
+            bytes: Vec::new(),
+            r_padding: 0,
+            align: None,
+            aligned: true,
+            canonical: false,
+        }
+    }
+
+    /// Construct a new, empty `Encoder` for unaligned PER.
+    pub fn new_unaligned() -> Encoder {
+        Encoder {
+            bytes: Vec::new(),
+            r_padding: 0,
+            align: None,
+            aligned: false,
+            canonical: false,
+        }
+    }
+
+    /// Construct a new `Encoder` for aligned PER, starting with `bytes` and `r_pad` bits of right-padding.
+    pub fn with_bytes_and_padding(bytes: Vec<u8>, r_pad: usize) -> Encoder {
+        Encoder {
+            bytes,
+            r_padding: r_pad,
+            align: None,
+            aligned: true,
+            canonical: false,
+        }
+    }
+
+    /// Encode into a new aligned PER `Encoding` with `f`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let enc = Encoder::encode(|enc| enc.encode_length(3)).unwrap();
+    /// println!("enc = {:?}", *enc.bytes()); // Prints enc = [3]
+    /// ```
+    pub fn encode<F>(f: F) -> Result<Encoding, EncodeError>
+        where F: FnOnce(&mut Encoder) -> Result<(), EncodeError>
+    {
+        let mut enc = Encoder::new();
+        let ret = f(&mut enc);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(enc.into_encoding())
+    }
+
+    /// Encode into a new unaligned PER `Encoding` with `f`.
+    pub fn encode_unaligned<F>(f: F) -> Result<Encoding, EncodeError>
+        where F: FnOnce(&mut Encoder) -> Result<(), EncodeError>
+    {
+        let mut enc = Encoder::new_unaligned();
+        let ret = f(&mut enc);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(enc.into_encoding())
+    }
+
+    /// Construct a new, empty `Encoder` that follows the same variant of PER as this one, and is canonical if this one
+    /// is. This is useful for encoding a value on its own, e.g. as the contents of an open type.
+    pub fn nested(&self) -> Encoder {
+        let mut enc = if self.aligned {
+            Encoder::new()
+        } else {
+            Encoder::new_unaligned()
+        };
+        enc.canonical = self.canonical;
+        enc
+    }
+
+    /// Check if the `Encoder` follows the aligned variant of PER.
+    pub fn is_aligned(&self) -> bool {
+        self.aligned
+    }
+
+    /// Check if the `Encoder` produces CANONICAL-PER.
+    pub fn is_canonical(&self) -> bool {
+        self.canonical
+    }
+
+    /// Set whether the `Encoder` produces CANONICAL-PER.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut enc = Encoder::new();
+    /// enc.set_canonical(true);
+    /// SetOf(vec![3u8, 1, 2]).write_aper(&mut enc, UNCONSTRAINED).unwrap();
+    /// println!("enc = {:?}", *enc.into_encoding().bytes()); // Prints enc = [3, 1, 2, 3]
+    /// ```
+    pub fn set_canonical(&mut self, canonical: bool) {
+        self.canonical = canonical;
+    }
+
+    /// Get the number of bits written so far.
+    pub fn len(&self) -> usize {
+        self.bytes.len() * 8 - self.r_padding
+    }
+
+    /// Check if nothing has been written yet.
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Reserve capacity for at least `n` more octets.
+    pub fn reserve(&mut self, n: usize) {
+        self.bytes.reserve(n);
+    }
+
+    /// Get a reference to the bytes written so far.
+    pub fn bytes(&self) -> &Vec<u8> {
+        &self.bytes
+    }
+
+    /// Get the number of right-padding bits.
+    pub fn r_padding(&self) -> usize {
+        self.r_padding
+    }
+
+    /// Set the number of right-padding bits.
+    pub fn set_r_padding(&mut self, n: usize) {
+        self.r_padding = n;
+    }
+
+    /// Finish writing, and return what was written as an `Encoding`.
+    pub fn into_encoding(self) -> Encoding {
+        Encoding::from_encoder(self)
+    }
+
+    /// Write the `n` LSBs of `value`, where `0 <= n <= 64`.
+    pub fn write_bits(&mut self, value: u64, n: usize) {
+        let mut left = n;
+
+        // Fill the LSBs of the last byte first
+        if self.r_padding > 0 && left > 0 {
+            let k = cmp::min(self.r_padding, left);
+            let bits = (value >> (left - k)) as u8 & (0xff >> (8 - k));
+            let last = self.bytes.len() - 1;
+            self.bytes[last] |= bits << (self.r_padding - k);
+            self.r_padding -= k;
+            left -= k;
+        }
+
+        while left >= 8 {
+            left -= 8;
+            self.bytes.push((value >> left) as u8);
+        }
+
+        if left > 0 {
+            self.bytes.push((value as u8) << (8 - left));
+            self.r_padding = 8 - left;
+        }
+    }
+
+    /// Write all of the bits of `bytes`, without alignment.
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.write_bits_from(bytes, bytes.len() * 8);
+    }
+
+    /// Write the first `n` bits of `bytes`, without alignment.
+    pub fn write_bits_from(&mut self, bytes: &[u8], n: usize) {
+        let num_bytes = n.div_ceil(8);
+        if num_bytes == 0 {
+            return;
+        }
+        let bytes = &bytes[..num_bytes];
+        let r_padding = num_bytes * 8 - n;
+
+        if self.r_padding == 0 {
+            self.bytes.extend_from_slice(bytes);
+            let last = self.bytes.len() - 1;
+            self.bytes[last] &= 0xff << r_padding;
+            self.r_padding = r_padding;
+            return;
+        }
+
+        // Fill the LSBs of the last byte, then carry the remainder of each byte forward
+        let shift = self.r_padding;
+        self.bytes.reserve(num_bytes);
+        for b in bytes {
+            let last = self.bytes.len() - 1;
+            self.bytes[last] |= b >> (8 - shift);
+            self.bytes.push(b << shift);
+        }
+
+        self.r_padding = shift + r_padding;
+        if self.r_padding >= 8 {
+            self.bytes.pop();
+            self.r_padding -= 8;
+        }
+        let last = self.bytes.len() - 1;
+        self.bytes[last] &= 0xff << self.r_padding;
+    }
+
+    /// Write `bytes` starting on an octet boundary.
+    pub fn write_aligned_bytes(&mut self, bytes: &[u8]) {
+        self.align();
+        self.write_bytes(bytes);
+    }
+
+    /// Pad with 0 bits to an octet boundary, which aligned PER requires before length determinants and other
+    /// octet-aligned fields. This does nothing if the `Encoder` follows the unaligned variant of PER.
+    ///
+    /// The first alignment point is remembered, so that its padding can be recomputed when the resulting `Encoding`
+    /// is appended somewhere else.
+    pub fn align(&mut self) {
+        if !self.aligned {
+            return;
+        }
+        if self.align.is_none() {
+            self.align = Some(self.len());
+        }
+        self.r_padding = 0;
+    }
+
+    /// Append `other` to what has been written so far. If `other` has an alignment point, this is padded to an octet
+    /// boundary there instead.
+    pub fn append(&mut self, other: &Encoding) -> Result<(), EncodeError> {
+        let other = other.encoder();
+        let p = match other.align {
+            Some(p) => p,
+            None => {
+                self.write_bits_from(&other.bytes, other.len());
+                return Ok(());
+            }
+        };
+
+        // The bits before the alignment point, then the octets after the padding that follows it
+        self.write_bits_from(&other.bytes, p);
+        self.align();
+        let n = p.div_ceil(8);
+        if n < other.bytes.len() {
+            self.write_bits_from(&other.bytes[n..], other.len() - n * 8);
+        }
+        Ok(())
+    }
+
+    /// Write a length determinant, which is octet-aligned in aligned PER.
+    ///
+    /// Returns `EncodeError::FragmentationRequired` if `len` is 16K or more. See
+    /// [encode_fragmented()](#method.encode_fragmented) for content of that size.
+    pub fn encode_length(&mut self, len: usize) -> Result<(), EncodeError> {
+        if len >= LENGTH_FRAG_UNIT {
+            return Err(EncodeError::FragmentationRequired);
+        }
+
+        self.align();
+        if len < 128 {
+            self.write_bits((len as u64 & LENGTH_MASK_SHORT as u64) | LENGTH_DET_SHORT as u64, 8);
+        } else {
+            let upper = (len >> 8) as u8;
+            self.write_bits(((upper & LENGTH_MASK_LONG) | LENGTH_DET_LONG) as u64, 8);
+            self.write_bits(len as u64, 8);
+        }
+        Ok(())
+    }
+
+    /// Write a run of `len` items preceded by a length determinant, fragmenting it if necessary.
+    ///
+    /// Items are emitted in fragments of 16K, 32K, 48K or 64K items, each preceded by its own length determinant, and
+    /// the run is terminated by a regular length determinant for the remainder (which may be zero). `f` is called
+    /// once per fragment with the index of the first item and the number of items, and is expected to write them.
+    pub fn encode_fragmented<F>(&mut self, len: usize, mut f: F) -> Result<(), EncodeError>
+        where F: FnMut(&mut Encoder, usize, usize) -> Result<(), EncodeError>
+    {
+        let mut start: usize = 0;
+        while len - start >= LENGTH_FRAG_UNIT {
+            let units = cmp::min((len - start) / LENGTH_FRAG_UNIT, LENGTH_FRAG_MAX_UNITS);
+            self.align();
+            self.write_bits((LENGTH_DET_FRAG | units as u8) as u64, 8);
+
+            let n = units * LENGTH_FRAG_UNIT;
+            let ret = f(self, start, n);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            start += n;
+        }
+
+        let ret = self.encode_length(len - start);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+
+        if len > start {
+            let ret = f(self, start, len - start);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+        }
+        Ok(())
+    }
+
+    /// Write a run of `len` items preceded by the length that the SIZE constraint `size` calls for.
+    ///
+    /// A fixed size below 64K has no length at all, and any other upper bound below 64K has the length encoded as a
+    /// constrained whole number. Anything else has a length determinant which may be fragmented, as with
+    /// [encode_fragmented()](#method.encode_fragmented). If `size` is extensible, a bit first tells whether `len` is
+    /// outside of it, in which case the length is encoded as if there were no constraint. `f` is called as for
+    /// `encode_fragmented()`, and only once with all of the items if they aren't fragmented.
+    ///
+    /// Returns `EncodeError::InvalidValue` if `len` violates a constraint that isn't extensible.
+    pub fn encode_sized<F>(&mut self, len: usize, size: Option<Constraint>, mut f: F) -> Result<(), EncodeError>
+        where F: FnMut(&mut Encoder, usize, usize) -> Result<(), EncodeError>
+    {
+        let (lb, ub, extensible) = size_bounds(size);
+        let in_root = len >= lb && ub.is_none_or(|ub| len <= ub);
+        if extensible {
+            self.write_bits(!in_root as u64, 1);
+            if !in_root {
+                return self.encode_fragmented(len, f);
+            }
+        } else if !in_root {
+            return Err(EncodeError::InvalidValue);
+        }
+
+        match ub {
+            Some(ub) if ub <= LENGTH_CONSTRAINED_MAX => {
+                if lb != ub {
+                    let ret = self.encode_int(len as i64, Some(lb as i64), Some(ub as i64));
+                    if ret.is_err() {
+                        return Err(ret.err().unwrap());
+                    }
+                }
+                if len > 0 {
+                    return f(self, 0, len);
+                }
+                Ok(())
+            }
+            _ => self.encode_fragmented(len, f),
+        }
+    }
+
+    /// Write a normally small non-negative whole number, such as the index of a CHOICE extension alternative.
+    ///
+    /// Numbers up to 63 are encoded in 7 bits, and anything larger as a length-prefixed semi-constrained integer.
+    pub fn encode_normally_small(&mut self, n: u64) -> Result<(), EncodeError> {
+        if n < 64 {
+            self.write_bits(n, 7);
+            return Ok(());
+        }
+
+        self.write_bits(1, 1);
+        let len = uint_octets(n);
+        let ret = self.encode_length(len);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        self.write_bits(n, len * 8);
+        Ok(())
+    }
+
+    /// Write a normally small length, such as the length of the extension addition bitmap of a SEQUENCE.
+    ///
+    /// Lengths up to 64 are encoded in 7 bits, and anything larger as a length determinant. A normally small length
+    /// is never 0, so `n` must be at least 1.
+    pub fn encode_normally_small_length(&mut self, n: usize) -> Result<(), EncodeError> {
+        if n <= 64 {
+            self.write_bits((n - 1) as u64, 7);
+            return Ok(());
+        }
+
+        self.write_bits(1, 1);
+        self.encode_length(n)
+    }
+
+    /// Write an open type holding whatever `f` writes.
+    ///
+    /// The value is encoded with `f` as a complete encoding of its own, which is padded to a whole number of octets
+    /// (an empty encoding is replaced by a single zero octet) and preceded by its length in octets.
+    pub fn encode_open_type<F>(&mut self, f: F) -> Result<(), EncodeError>
+        where F: FnOnce(&mut Encoder) -> Result<(), EncodeError>
+    {
+        let mut inner = self.nested();
+        let ret = f(&mut inner);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        self.encode_open_type_bytes(&inner.bytes)
+    }
+
+    /// Write the complete encoding `bytes` as an open type. See [encode_open_type()](#method.encode_open_type).
+    pub fn encode_open_type_bytes(&mut self, bytes: &[u8]) -> Result<(), EncodeError> {
+        let bytes: &[u8] = if bytes.is_empty() {
+            &[0]
+        } else {
+            bytes
+        };
+        self.encode_fragmented(bytes.len(), |enc, start, n| {
+            enc.write_bytes(&bytes[start..start + n]);
+            Ok(())
+        })
+    }
+
+    /// Write an integer between `min` and `max`. See [aper::encode_int()](fn.encode_int.html) and
+    /// [uper::encode_int()](../uper/fn.encode_int.html) for how each kind of integer is encoded in either variant of
+    /// PER.
+    pub fn encode_int(&mut self, value: i64, min: Option<i64>, max: Option<i64>) -> Result<(), EncodeError> {
+        if !self.aligned {
+            return self.encode_int_unaligned(value, min, max);
+        }
+
+        if let (Some(l), Some(h)) = (min, max) {
+            // constrained
+            if value < l || value > h {
+                return Err(EncodeError::InvalidValue);
+            }
+            let v = value.wrapping_sub(l) as u64;
+            let range = h.wrapping_sub(l) as u64 as u128 + 1;
+
+            // A bit-field, without alignment
+            if range <= 255 {
+                self.write_bits(v, range_bits(l, h));
+                return Ok(());
+            }
+
+            // One or two octet-aligned octets, no length determinant
+            if range <= 65536 {
+                let len = if range == 256 { 1 } else { 2 };
+                self.align();
+                self.write_bits(v, len * 8);
+                return Ok(());
+            }
+
+            // The minimum number of octets, preceded by their number as a constrained integer in [1, octets of range]
+            let len = uint_octets(v);
+            let ret = self.encode_int(len as i64, Some(1), Some(uint_octets((range - 1) as u64) as i64));
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            self.align();
+            self.write_bits(v, len * 8);
+            return Ok(());
+        }
+
+        self.encode_unconstrained_int(value, min)
+    }
+
+    /// Write an unaligned PER integer between `min` and `max`, where a constrained integer is always a minimal
+    /// bit-field.
+    fn encode_int_unaligned(&mut self, value: i64, min: Option<i64>, max: Option<i64>) -> Result<(), EncodeError> {
+        if let (Some(l), Some(h)) = (min, max) {
+            // constrained
+            if value < l || value > h {
+                return Err(EncodeError::InvalidValue);
+            }
+            self.write_bits(value.wrapping_sub(l) as u64, range_bits(l, h));
+            return Ok(());
+        }
+
+        self.encode_unconstrained_int(value, min)
+    }
+
+    /// Write a semi-constrained or unconstrained integer as its minimal octets preceded by a length determinant.
+    fn encode_unconstrained_int(&mut self, value: i64, min: Option<i64>) -> Result<(), EncodeError> {
+        let (v, len) = match min {
+            // semiconstrained
+            Some(l) => {
+                if value < l {
+                    return Err(EncodeError::InvalidValue);
+                }
+                let v = value.wrapping_sub(l) as u64;
+                (v, uint_octets(v))
+            }
+            // unconstrained
+            None => (value as u64, int_octets(value)),
+        };
+
+        let ret = self.encode_length(len);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        self.write_bits(v, len * 8);
+        Ok(())
+    }
+}
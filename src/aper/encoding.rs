@@ -1,18 +1,25 @@
-use byteorder::{BigEndian, WriteBytesExt};
 use super::*;
-use utils::shift_bytes_left;
 
 #[derive(Debug, PartialEq)]
 pub enum EncodeError {
+    /// The value is not permitted by its type or constraints.
+    InvalidValue,
     MissingSizeConstraint,
     MissingValueConstraint,
+    FragmentationRequired,
     NotImplemented,
     WriteError,
 }
 
-/// A wrapper for an aligned PER encoding.
+/// A wrapper for the result of encoding something.
 ///
-/// An `Encoding` is just a vector of bytes with right-padding at the end if necessary.
+/// An `Encoding` is just a vector of bytes with right-padding at the end if necessary, as written by an
+/// [Encoder](struct.Encoder.html).
+///
+/// Aligned PER pads some fields, such as length determinants, to start on an octet boundary of the whole encoding.
+/// Since an `Encoding` doesn't know where it will end up until it's appended to another, it remembers its first
+/// alignment point (see [align()](#method.align)), and [append()](#method.append) recomputes the padding before that
+/// point. Everything after the first alignment point is then padded correctly as it is.
 ///
 /// # Examples
 ///
@@ -23,28 +30,25 @@ pub enum EncodeError {
 /// let mut enc = Encoding::new();
 /// enc.append(&true.to_aper(UNCONSTRAINED).unwrap()).unwrap();
 /// println!("enc = {:?}", *enc.bytes()); // Prints enc = [128]
+///
+/// // An octet-aligned field
+/// enc.append(&Encoding::with_aligned_bytes(vec![0x2b])).unwrap();
+/// println!("enc = {:?}", *enc.bytes()); // Prints enc = [128, 43]
 /// ```
 #[derive(Debug)]
 pub struct Encoding {
-    bytes: Vec<u8>,
-    r_padding: usize,
+    encoder: Encoder,
 }
 
 impl Encoding {
     /// Construct a new, empty `Encoding`.
     pub fn new() -> Encoding {
-        Encoding {
-            bytes: Vec::new(),
-            r_padding: 0,
-        }
+        Self::from_encoder(Encoder::new())
     }
 
     /// Construct a new `Encoding` with `bytes` and `r_pad` bits of right-padding.
     pub fn with_bytes_and_padding(bytes: Vec<u8>, r_pad: usize) -> Encoding {
-        Encoding {
-            bytes: bytes,
-            r_padding: r_pad,
-        }
+        Self::from_encoder(Encoder::with_bytes_and_padding(bytes, r_pad))
     }
 
     /// Construct a new `Encoding` with `bytes` and zero bits of right-padding.
@@ -52,84 +56,150 @@ impl Encoding {
         Self::with_bytes_and_padding(bytes, 0)
     }
 
-    /// Append `other` to the end of `self`, starting with the `r_padding`th LSB of `self`.
-    pub fn append(&mut self, other: &Encoding) -> Result<(), EncodeError> {
-        let mut bytes = other.bytes().clone();
-        let r_padding = other.r_padding();
-
-        let n = self.bytes.len();
-
-        if n == 0 {
-            self.bytes.append(&mut bytes);
-            self.r_padding = r_padding;
-            return Ok(());
-        }
+    /// Construct a new `Encoding` with `bytes`, which must start on an octet boundary.
+    pub fn with_aligned_bytes(bytes: Vec<u8>) -> Encoding {
+        let mut enc = Encoder::new();
+        enc.write_aligned_bytes(&bytes);
+        Self::from_encoder(enc)
+    }
 
-        if bytes.len() == 0 {
-            return Ok(());
+    pub(crate) fn from_encoder(encoder: Encoder) -> Encoding {
+        Encoding {
+            encoder,
         }
+    }
 
-        // Fill LSBs of self.bytes first
-        if self.r_padding > 0 {
-            let mask = 0xFF << r_padding;
-            self.bytes[n - 1] |= (bytes[0] & mask) >> (8 - self.r_padding);
-
-            shift_bytes_left(&mut bytes, self.r_padding);
-
-            let len = 8 - r_padding;
-            if len <= self.r_padding {
-                self.r_padding -= len;
-                bytes.remove(0);
-            }
-        } else {
-            self.r_padding = r_padding;
-        }
+    pub(crate) fn encoder(&self) -> &Encoder {
+        &self.encoder
+    }
 
-        // Just append everything else
-        self.bytes.append(&mut bytes);
+    /// Append `other` to the end of `self`, starting with the `r_padding`th LSB of `self`. If `other` has an
+    /// alignment point, `self` is padded to an octet boundary there instead.
+    pub fn append(&mut self, other: &Encoding) -> Result<(), EncodeError> {
+        self.encoder.append(other)
+    }
 
-        Ok(())
+    /// Pad `self` with 0 bits to an octet boundary, which aligned PER requires before length determinants and other
+    /// octet-aligned fields. The first alignment point of `self` is remembered, so that its padding can be
+    /// recomputed when `self` is appended to another `Encoding`.
+    pub fn align(&mut self) {
+        self.encoder.align();
     }
 
     /// Get a reference to the bytes of an encoding.
     pub fn bytes(&self) -> &Vec<u8> {
-        &self.bytes
+        self.encoder.bytes()
     }
 
     /// Get a mutable reference to the bytes of an encoding.
     pub fn bytes_mut(&self) -> &Vec<u8> {
-        &self.bytes
+        self.encoder.bytes()
     }
 
     /// Get the number of right-padding bits.
     pub fn r_padding(&self) -> usize {
-        self.r_padding
+        self.encoder.r_padding()
     }
 
     /// Set the number of right-padding bits.
     pub fn set_r_padding(&mut self, n: usize) {
-        self.r_padding = n;
+        self.encoder.set_r_padding(n);
     }
 }
 
 /// Encode an aligned PER length determinant.
+///
+/// Returns `EncodeError::FragmentationRequired` if `len` is 16K or more. See
+/// [encode_fragmented()](fn.encode_fragmented.html) for content of that size.
 pub fn encode_length(len: usize) -> Result<Encoding, EncodeError> {
-    if len < 128 {
-        return Ok(Encoding::with_bytes(vec![(len as u8 & LENGTH_MASK_SHORT) | LENGTH_DET_SHORT]));
-    } else if len < 65535 {
-        let upper = (len >> 8) as u8;
-        let lower = len as u8;
-        return Ok(Encoding::with_bytes(vec![(upper & LENGTH_MASK_LONG) | LENGTH_DET_LONG, lower]));
-    } else {
-        return Err(EncodeError::NotImplemented);
-    }
+    Encoder::encode(|enc| enc.encode_length(len))
+}
+
+/// Encode a run of `len` items preceded by an aligned PER length determinant, fragmenting it if necessary.
+///
+/// Items are emitted in fragments of 16K, 32K, 48K or 64K items, each preceded by its own length determinant, and
+/// the run is terminated by a regular length determinant for the remainder (which may be zero). `f` is called once
+/// per fragment with the index of the first item and the number of items, and is expected to append them to the
+/// `Encoding` it is given. See [Encoder::encode_fragmented()](struct.Encoder.html#method.encode_fragmented) to write
+/// the items directly instead.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::aper::{self, Encoding, encode_fragmented};
+///
+/// let v: Vec<u8> = vec![0x46, 0x4f, 0x4f];
+/// let enc = encode_fragmented(v.len(), |enc, start, n| {
+///     enc.append(&Encoding::with_bytes(v[start..start + n].to_vec()))
+/// }).unwrap();
+/// println!("{:?}", enc.bytes()); // Prints [3, 70, 79, 79]
+/// ```
+pub fn encode_fragmented<F>(len: usize, f: F) -> Result<Encoding, EncodeError>
+    where F: FnMut(&mut Encoding, usize, usize) -> Result<(), EncodeError>
+{
+    Encoder::encode(|enc| fragmented(enc, len, f))
+}
+
+/// Write a run of `len` items with `f`, which appends each fragment to an `Encoding` of its own.
+pub(crate) fn fragmented<F>(encoder: &mut Encoder, len: usize, mut f: F) -> Result<(), EncodeError>
+    where F: FnMut(&mut Encoding, usize, usize) -> Result<(), EncodeError>
+{
+    encoder.encode_fragmented(len, |enc, start, n| {
+        let mut items = Encoding::new();
+        let ret = f(&mut items, start, n);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        enc.append(&items)
+    })
+}
+
+/// Encode a normally small non-negative whole number, such as the index of a CHOICE extension alternative.
+///
+/// Numbers up to 63 are encoded in 7 bits, and anything larger as a length-prefixed semi-constrained integer.
+pub fn encode_normally_small(n: u64) -> Result<Encoding, EncodeError> {
+    Encoder::encode(|enc| enc.encode_normally_small(n))
+}
+
+/// Encode a normally small length, such as the length of the extension addition bitmap of a SEQUENCE.
+///
+/// Lengths up to 64 are encoded in 7 bits, and anything larger as a length determinant. A normally small length is
+/// never 0, so `n` must be at least 1.
+pub fn encode_normally_small_length(n: usize) -> Result<Encoding, EncodeError> {
+    Encoder::encode(|enc| enc.encode_normally_small_length(n))
+}
+
+/// Encode `inner` as an open type.
+///
+/// The content of an open type is a complete encoding padded to a whole number of octets (an empty encoding is
+/// replaced by a single zero octet), preceded by its length in octets.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::aper::{self, APerElement, encode_open_type, UNCONSTRAINED};
+///
+/// let inner = true.to_aper(UNCONSTRAINED).unwrap();
+/// println!("{:?}", encode_open_type(&inner).unwrap().bytes()); // Prints [1, 128]
+/// ```
+pub fn encode_open_type(inner: &Encoding) -> Result<Encoding, EncodeError> {
+    Encoder::encode(|enc| enc.encode_open_type_bytes(inner.bytes()))
 }
 
 /// Encode an aligned PER integer between `min` and `max`.
 ///
-/// You can encode the Rust primitive (u)ints: `i8`, `i16`, `i32`, `u8`, `u16`, and `u32` using their respective
-/// `to_aper` functions. `encode_int` is useful if you want to encode an integer field that exists somewhere
-/// between or beyond the primitive widths.
+/// You can encode the Rust primitive (u)ints: `i8` to `i128` and `u8` to `u128` using their respective `to_aper`
+/// functions. `encode_int` is useful if you want to encode an integer field that exists somewhere between or beyond
+/// the primitive widths. See [asn1::BigInt](../struct.BigInt.html) for values that don't fit in an `i64`.
+///
+/// A constrained integer is encoded as its offset from `min`: in no bits at all if the range holds a single value, as
+/// a minimal bit-field if the range holds up to 255 values, in one octet for 256 values, in two octets for up to 64K
+/// values, and otherwise in the minimum number of octets preceded by that number as a bit-field. Octets are always
+/// octet-aligned. Semi-constrained and unconstrained integers are encoded in the minimum number of octets (the offset
+/// from `min`, or the 2's-complement value) preceded by a length determinant. Returns `EncodeError::InvalidValue` if
+/// `value` is out of bounds.
 ///
 /// # Examples
 ///
@@ -141,76 +211,8 @@ pub fn encode_length(len: usize) -> Result<Encoding, EncodeError> {
 /// use asn1::aper::{self, APerElement, Constraint, Constraints, Encoding, encode_int, UNCONSTRAINED};
 ///
 /// let x = 501;
-/// println!("{:?}", encode_int(x, Some(500), Some(503).unwrap().bytes()); // Prints [64]
+/// println!("{:?}", encode_int(x, Some(500), Some(503)).unwrap().bytes()); // Prints [64]
 /// ```
 pub fn encode_int(value: i64, min: Option<i64>, max: Option<i64>) -> Result<Encoding, EncodeError> {
-    if min.is_some() && max.is_some() {
-        // constrained
-        let l = min.unwrap();
-        let h = max.unwrap();
-        let v = value - l;
-        let range = h - l + 1;
-        let n_bits = (range as f64).log2().ceil() as usize;
-
-        // No alignment
-        if n_bits < 8 {
-            return Ok(Encoding::with_bytes_and_padding(vec![(v as u8) << (8 - n_bits)], 8 - n_bits));
-        }
-
-        // Simple case, no length determinant
-        if n_bits <= 16 {
-            let mut bytes = vec![v as u8];
-
-            if n_bits > 8 {
-                bytes.insert(0, (v >> 8) as u8);
-            }
-            return Ok(Encoding::with_bytes(bytes));
-        }
-
-        // Need to encode with length determinant
-        let len = (n_bits as f64 / 8.).ceil() as usize;
-        let ret = encode_length(len);
-        if ret.is_err() {
-            return Err(ret.err().unwrap());
-        }
-        let mut enc = ret.unwrap();
-        let mut bytes: Vec<u8> = Vec::new();
-        let res = bytes.write_uint::<BigEndian>(v as u64, len);
-        if res.is_err() {
-            return Err(EncodeError::WriteError);
-        }
-        let ret = enc.append(&Encoding::with_bytes(bytes));
-        if ret.is_err() {
-            return Err(ret.err().unwrap());
-        }
-        return Ok(enc);
-    }
-
-    let n_bits = (value as f64).log2().ceil() as usize;
-    let len = (n_bits as f64 / 8.).ceil() as usize;
-    let res = encode_length(len);
-    if res.is_err() {
-        return Err(res.err().unwrap());
-    }
-    let mut enc = res.unwrap();
-    let mut bytes: Vec<u8> = Vec::new();
-
-    if min.is_none() {
-        // unconstrained
-        let ret = bytes.write_uint::<BigEndian>(value as u64, len);
-        if ret.is_err() {
-            return Err(EncodeError::WriteError);
-        }
-    } else {
-        // semiconstrained
-        let ret = bytes.write_uint::<BigEndian>((value - min.unwrap()) as u64, len);
-        if ret.is_err() {
-            return Err(EncodeError::WriteError);
-        }
-    }
-    let ret = enc.append(&Encoding::with_bytes(bytes));
-    if ret.is_err() {
-        return Err(ret.err().unwrap());
-    }
-    Ok(enc)
+    Encoder::encode(|enc| enc.encode_int(value, min, max))
 }
@@ -0,0 +1,256 @@
+use super::*;
+
+/// The extension additions of a SEQUENCE, which follow its root components when its extension bit is set.
+///
+/// The additions are encoded as a bitmap giving the presence of each addition, followed by each present addition
+/// wrapped in an open type. An extension addition group (`[[ ... ]]`) takes a single place in the bitmap, and is
+/// encoded as if it were a SEQUENCE of the group's components.
+///
+/// Since every addition is wrapped in an open type, a decoder can skip additions that it doesn't know about, i.e.
+/// ones from a newer version of the protocol. `from_aper` reads all of the additions, so any after the ones known to
+/// the decoder are simply ignored.
+///
+/// # Examples
+///
+/// Consider a SEQUENCE `Foo` with one root component and two extension additions, the second being a group.
+///
+/// ```
+/// Foo ::= SEQUENCE {
+///     bar BOOLEAN,
+///     ...,
+///     baz INTEGER(0..255),
+///     [[ qux BOOLEAN, quux BOOLEAN ]]
+/// }
+/// ```
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::aper::{self, APerElement, Constraint, Constraints, ExtensionAdditions, UNCONSTRAINED};
+///
+/// struct Foo {
+///     bar: bool,
+///     baz: Option<u8>,
+///     qux_quux: Option<(bool, bool)>,
+/// }
+///
+/// impl APerElement for Foo {
+///     const CONSTRAINTS: Constraints = UNCONSTRAINED;
+///
+///     fn from_aper(decoder: &mut aper::Decoder, _: Constraints) -> Result<Self, aper::DecodeError> {
+///         let is_ext = bool::from_aper(decoder, UNCONSTRAINED)?;
+///         let bar = bool::from_aper(decoder, UNCONSTRAINED)?;
+///         let ext = if is_ext {
+///             ExtensionAdditions::from_aper(decoder, UNCONSTRAINED)?
+///         } else {
+///             ExtensionAdditions::new()
+///         };
+///         let baz = ext.decode(0, Constraints {
+///             value: Some(Constraint::new(Some(0), Some(255))),
+///             size: None,
+///         })?;
+///         let qux_quux = match ext.decoder(1) {
+///             Some(mut d) => {
+///                 let qux = bool::from_aper(&mut d, UNCONSTRAINED)?;
+///                 Some((qux, bool::from_aper(&mut d, UNCONSTRAINED)?))
+///             }
+///             None => None,
+///         };
+///         Ok(Foo { bar: bar, baz: baz, qux_quux: qux_quux })
+///     }
+///
+///     fn to_aper(&self, constraints: Constraints) -> Result<aper::Encoding, aper::EncodeError> {
+///         aper::Encoder::encode(|enc| self.write_aper(enc, constraints))
+///     }
+///
+///     fn write_aper(&self, encoder: &mut aper::Encoder, _: Constraints) -> Result<(), aper::EncodeError> {
+///         let mut ext = ExtensionAdditions::new();
+///         ext.push(encoder, &self.baz, Constraints {
+///             value: Some(Constraint::new(Some(0), Some(255))),
+///             size: None,
+///         })?;
+///         ext.push_encoding(match self.qux_quux {
+///             Some((qux, quux)) => {
+///                 let mut group = encoder.nested();
+///                 qux.write_aper(&mut group, UNCONSTRAINED)?;
+///                 quux.write_aper(&mut group, UNCONSTRAINED)?;
+///                 Some(group.into_encoding())
+///             }
+///             None => None,
+///         });
+///
+///         ext.is_any_present().write_aper(encoder, UNCONSTRAINED)?;
+///         self.bar.write_aper(encoder, UNCONSTRAINED)?;
+///         ext.write_aper(encoder, UNCONSTRAINED)
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtensionAdditions {
+    additions: Vec<Option<Vec<u8>>>,
+    /// An empty `Decoder` like the one that the additions were read with, which the `Decoder` over each addition is
+    /// nested in.
+    outer: Decoder<'static>,
+}
+
+impl ExtensionAdditions {
+    /// Construct an empty `ExtensionAdditions`.
+    pub fn new() -> ExtensionAdditions {
+        ExtensionAdditions {
+            additions: Vec::new(),
+            outer: Decoder::new(&[]),
+        }
+    }
+
+    /// Get the number of additions in the bitmap, present or not.
+    pub fn len(&self) -> usize {
+        self.additions.len()
+    }
+
+    /// Check if there are no additions in the bitmap.
+    pub fn is_empty(&self) -> bool {
+        self.additions.is_empty()
+    }
+
+    /// Check if any addition is present. This is the value of the extension bit of the SEQUENCE, and
+    /// [to_aper()](#method.to_aper) only encodes anything if it's set.
+    pub fn is_any_present(&self) -> bool {
+        self.additions.iter().any(|a| a.is_some())
+    }
+
+    /// Check if the `i`th addition is present. Additions beyond the end of the bitmap are absent.
+    pub fn is_present(&self, i: usize) -> bool {
+        self.get(i).is_some()
+    }
+
+    /// Get the contents of the open type holding the `i`th addition, if it's present.
+    pub fn get(&self, i: usize) -> Option<&[u8]> {
+        self.additions.get(i).and_then(|a| a.as_ref()).map(|content| content.as_slice())
+    }
+
+    /// Get a `Decoder` over the `i`th addition, if it's present. This is useful for decoding the components of an
+    /// extension addition group. The `Decoder` is [nested](struct.Decoder.html#method.nested) in the one that the
+    /// additions were read with.
+    pub fn decoder(&self, i: usize) -> Option<Decoder<'_>> {
+        self.get(i).map(|content| self.outer.nested(content))
+    }
+
+    /// Decode the `i`th addition, which is `None` if it's absent.
+    pub fn decode<T: APerElement>(&self, i: usize, constraints: Constraints) -> Result<Option<T>, DecodeError> {
+        match self.decoder(i) {
+            Some(mut d) => {
+                let ret = T::from_aper(&mut d, constraints);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                Ok(Some(ret.unwrap()))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Get the number of present additions from the `known`th on, i.e. those that a decoder which knows about `known`
+    /// additions skips.
+    pub fn num_unknown(&self, known: usize) -> usize {
+        self.additions.iter().skip(known).filter(|a| a.is_some()).count()
+    }
+
+    /// Add the next addition, which is absent if `value` is `None`. It's encoded on its own with an `Encoder`
+    /// [nested](struct.Encoder.html#method.nested) in `encoder`, the one that the SEQUENCE is written to, so that it
+    /// follows the same variant of PER.
+    pub fn push<T: APerElement>(&mut self, encoder: &Encoder, value: &Option<T>, constraints: Constraints)
+                                -> Result<(), EncodeError> {
+        match *value {
+            Some(ref x) => {
+                let mut enc = encoder.nested();
+                let ret = x.write_aper(&mut enc, constraints);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                self.push_encoding(Some(enc.into_encoding()));
+            }
+            None => self.additions.push(None),
+        }
+        Ok(())
+    }
+
+    /// Add the next addition from its encoding, which is absent if `enc` is `None`. This is useful for encoding the
+    /// components of an extension addition group, which should be written to an `Encoder` nested in the one that the
+    /// SEQUENCE is written to.
+    pub fn push_encoding(&mut self, enc: Option<Encoding>) {
+        self.additions.push(enc.map(|e| if e.bytes().is_empty() {
+            vec![0]
+        } else {
+            e.bytes().clone()
+        }));
+    }
+}
+
+impl Default for ExtensionAdditions {
+    fn default() -> ExtensionAdditions {
+        ExtensionAdditions::new()
+    }
+}
+
+impl APerElement for ExtensionAdditions {
+    const CONSTRAINTS: Constraints = UNCONSTRAINED;
+
+    /// Read the extension additions of a SEQUENCE whose extension bit is set from an aligned PER encoding.
+    fn from_aper(decoder: &mut Decoder, _: Constraints) -> Result<Self, DecodeError> {
+        let ret = decoder.decode_normally_small_length();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let n = ret.unwrap();
+
+        let mut bitmap = Vec::with_capacity(n);
+        for _ in 0..n {
+            let ret = decoder.read(1);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            bitmap.push(ret.unwrap() > 0);
+        }
+
+        let mut additions = Vec::with_capacity(n);
+        for present in bitmap {
+            if !present {
+                additions.push(None);
+                continue;
+            }
+            let ret = decoder.decode_open_type();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            additions.push(Some(ret.unwrap()));
+        }
+        Ok(ExtensionAdditions {
+            additions,
+            outer: decoder.nested(&[]),
+        })
+    }
+
+    fn to_aper(&self, constraints: Constraints) -> Result<Encoding, EncodeError> {
+        Encoder::encode(|enc| self.write_aper(enc, constraints))
+    }
+
+    fn write_aper(&self, encoder: &mut Encoder, _: Constraints) -> Result<(), EncodeError> {
+        if !self.is_any_present() {
+            return Ok(());
+        }
+
+        let ret = encoder.encode_normally_small_length(self.additions.len());
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        for a in &self.additions {
+            encoder.write_bits(a.is_some() as u64, 1);
+        }
+        for content in self.additions.iter().filter_map(|a| a.as_ref()) {
+            let ret = encoder.encode_open_type_bytes(content);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,7 +1,13 @@
 mod decoder;
+mod encoder;
 mod encoding;
+mod extensions;
 pub use self::decoder::{Decoder, DecodeError};
-pub use self::encoding::{Encoding, EncodeError, encode_int, encode_length};
+pub use self::encoder::Encoder;
+pub use self::encoding::{Encoding, EncodeError, encode_fragmented, encode_int, encode_length, encode_normally_small,
+                         encode_normally_small_length, encode_open_type};
+pub(crate) use self::encoding::fragmented;
+pub use self::extensions::ExtensionAdditions;
 
 pub const LENGTH_DET_SHORT: u8 = 0b0000_0000;
 pub const LENGTH_DET_LONG: u8 = 0b1000_0000;
@@ -9,24 +15,84 @@ pub const LENGTH_DET_FRAG: u8 = 0b1100_0000;
 
 pub const LENGTH_MASK_SHORT: u8 = 0b0111_1111;
 pub const LENGTH_MASK_LONG: u8 = 0b0011_1111;
+pub const LENGTH_MASK_FRAG: u8 = 0b0011_1111;
+
+/// The number of items in each unit of a fragmented length (16K).
+pub const LENGTH_FRAG_UNIT: usize = 16384;
+/// The maximum number of units in a single fragment (64K items).
+pub const LENGTH_FRAG_MAX_UNITS: usize = 4;
+
+/// The largest upper bound of a SIZE constraint for which a length is encoded as a constrained whole number rather than
+/// a length determinant (64K - 1).
+pub const LENGTH_CONSTRAINED_MAX: usize = 65535;
 
 /// An interval that desribes the limits on some value.
 /// To indicate something is unbounded, set `min` and `max` to `None`.
+///
+/// The value constraint of a character string is its permitted alphabet. This can be an interval of character codes,
+/// as in `FROM ("A".."Z")`, or a set of characters made with [new_alphabet()](#method.new_alphabet). The value
+/// constraint of a REAL limits its components, and is made with [new_real()](#method.new_real).
 #[derive(Debug, Copy, Clone)]
 pub struct Constraint {
     min: Option<i64>,
     max: Option<i64>,
+    extensible: bool,
+    alphabet: Option<&'static str>,
+    real: Option<RealComponents>,
 }
 
 impl Constraint {
     /// Construct a new `Constraint`.
-    pub fn new(min: Option<i64>, max: Option<i64>) -> Constraint {
+    pub const fn new(min: Option<i64>, max: Option<i64>) -> Constraint {
         Constraint {
             min: min,
             max: max,
+            extensible: false,
+            alphabet: None,
+            real: None,
+        }
+    }
+
+    /// Construct a new `Constraint` with an extension marker, such as `SIZE (1..4, ...)` or `INTEGER (0..7, ...)`. Only
+    /// SIZE constraints and the value constraints of INTEGERs can be extensible for now.
+    pub const fn new_extensible(min: Option<i64>, max: Option<i64>) -> Constraint {
+        Constraint {
+            min,
+            max,
+            extensible: true,
+            alphabet: None,
+            real: None,
+        }
+    }
+
+    /// Construct a new `Constraint` on the characters of a string, such as `FROM ("0123456789#*")`.
+    pub const fn new_alphabet(chars: &'static str) -> Constraint {
+        Constraint {
+            min: None,
+            max: None,
+            extensible: false,
+            alphabet: Some(chars),
+            real: None,
+        }
+    }
+
+    /// Construct a new `Constraint` on the components of a REAL, such as
+    /// `WITH COMPONENTS { mantissa (-16777215..16777215), base (2), exponent (-125..128) }`.
+    pub const fn new_real(components: RealComponents) -> Constraint {
+        Constraint {
+            min: None,
+            max: None,
+            extensible: false,
+            alphabet: None,
+            real: Some(components),
         }
     }
 
+    /// Check if the constraint has an extension marker.
+    pub fn is_extensible(&self) -> bool {
+        self.extensible
+    }
+
     /// Get the lower bound.
     pub fn min(&self) -> Option<i64> {
         self.min
@@ -36,6 +102,28 @@ impl Constraint {
     pub fn max(&self) -> Option<i64> {
         self.max
     }
+
+    /// Get the permitted characters, if the constraint is a set of them.
+    pub fn alphabet(&self) -> Option<&'static str> {
+        self.alphabet
+    }
+
+    /// Get the limits on the components of a REAL, if the constraint is on them.
+    pub fn real(&self) -> Option<RealComponents> {
+        self.real
+    }
+}
+
+/// The limits on the components of a REAL in a `WITH COMPONENTS` constraint, where `None` leaves a component
+/// unconstrained. The bounds of `mantissa` and `exponent` are inclusive, and `base` is 2 or 10.
+///
+/// A value is permitted if it can be written as `mantissa × base^exponent` with each component within its limits. The
+/// special values, such as PLUS-INFINITY, are always permitted.
+#[derive(Debug, Copy, Clone)]
+pub struct RealComponents {
+    pub mantissa: (Option<i64>, Option<i64>),
+    pub base: Option<u32>,
+    pub exponent: (Option<i64>, Option<i64>),
 }
 
 /// A pair of `Constraint`s that describes the constraints on the value (if applicable) and encoded size of a type.
@@ -159,7 +247,7 @@ pub const UNCONSTRAINED: Constraints = Constraints {
 ///             0 => {
 ///                 let bs = BitString::from_aper(decoder , Constraints {
 ///                     value: None,
-///                     size: Some(Constraint::new(None, Some(4))),
+///                     size: Some(Constraint::new(Some(4), Some(4))),
 ///                 });
 ///                 if bs.is_err() {
 ///                     Err(bs.err().unwrap())
@@ -189,7 +277,7 @@ pub const UNCONSTRAINED: Constraints = Constraints {
 ///                     Ok(MyMsg::baz{ a: a.unwrap(), b: b.unwrap(), })
 ///                 }
 ///             }
-///             _ => Err(aper::DecodeError::InvalidChoice)
+///             c => Err(aper::DecodeError::InvalidChoice(c))
 ///         }
 ///     }
 ///
@@ -200,7 +288,7 @@ pub const UNCONSTRAINED: Constraints = Constraints {
 ///                 enc.append(&encode_int(0, Some(0), Some(2)).unwrap());
 ///                 enc.append(&a.to_aper(Constraints {
 ///                     value: None,
-///                     size: Some(Constraint::new(None, Some(4))),
+///                     size: Some(Constraint::new(Some(4), Some(4))),
 ///                 }).unwrap());
 ///             },
 ///             Foo::bar{a: ref a} => {
@@ -226,4 +314,17 @@ pub trait APerElement: Sized {
 
     /// For use with `Encoding::append`
     fn to_aper(&self, constraints: Constraints) -> Result<encoding::Encoding, encoding::EncodeError>;
+
+    /// Write the aligned PER encoding of `self` to `encoder`.
+    ///
+    /// The default implementation appends the result of `to_aper`. Implementing this directly avoids allocating an
+    /// `Encoding` for each element, and `to_aper` can then be implemented with
+    /// [Encoder::encode()](struct.Encoder.html#method.encode).
+    fn write_aper(&self, encoder: &mut Encoder, constraints: Constraints) -> Result<(), encoding::EncodeError> {
+        let ret = self.to_aper(constraints);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        encoder.append(&ret.unwrap())
+    }
 }
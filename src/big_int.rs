@@ -0,0 +1,334 @@
+use aper::{APerElement, Constraint, Constraints, Decoder, DecodeError, Encoder, Encoding, EncodeError, UNCONSTRAINED};
+use utils::{decode_int_bounds, encode_int_bounds};
+
+/// An integer of arbitrary size, for INTEGER types whose values don't fit in any of the primitive integers.
+///
+/// A `BigInt` holds the minimal 2's-complement big-endian octets of its value, which is exactly how the value of an
+/// unconstrained INTEGER is encoded. Semi-constrained INTEGERs are encoded as the minimal non-negative binary integer
+/// of their offset from the lower bound. A constrained `BigInt` must fit in an `i64`, since so do its bounds.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::BigInt;
+/// use asn1::aper::{self, APerElement, UNCONSTRAINED};
+///
+/// // 2^72
+/// let x = BigInt::from_signed_bytes_be(&[1, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+/// let enc = x.to_aper(UNCONSTRAINED).unwrap();
+/// println!("{:?}", enc.bytes()); // Prints [10, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+///
+/// let mut d = aper::Decoder::new(enc.bytes());
+/// let y = BigInt::from_aper(&mut d, UNCONSTRAINED).unwrap();
+/// println!("{}", y == x); // Prints true
+/// println!("{:?}", y.to_i64()); // Prints None
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    bytes: Vec<u8>,
+}
+
+impl BigInt {
+    /// Construct a `BigInt` from the 2's-complement big-endian octets of its value. An empty slice is 0.
+    pub fn from_signed_bytes_be(bytes: &[u8]) -> BigInt {
+        let mut bytes = bytes.to_vec();
+        if bytes.is_empty() {
+            bytes.push(0);
+        }
+        minimize(&mut bytes);
+        BigInt { bytes }
+    }
+
+    /// Construct a non-negative `BigInt` from the big-endian octets of its value. An empty slice is 0.
+    pub fn from_unsigned_bytes_be(bytes: &[u8]) -> BigInt {
+        let mut v = Vec::with_capacity(bytes.len() + 1);
+        v.push(0);
+        v.extend_from_slice(bytes);
+        Self::from_signed_bytes_be(&v)
+    }
+
+    /// Get the minimal 2's-complement big-endian octets of the value.
+    pub fn to_signed_bytes_be(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Get the minimal big-endian octets of the value, if it's non-negative.
+    pub fn to_unsigned_bytes_be(&self) -> Option<&[u8]> {
+        if self.is_negative() {
+            return None;
+        }
+        if self.bytes.len() > 1 && self.bytes[0] == 0 {
+            return Some(&self.bytes[1..]);
+        }
+        Some(&self.bytes)
+    }
+
+    /// Check if the value is less than 0.
+    pub fn is_negative(&self) -> bool {
+        self.bytes[0] & 0x80 != 0
+    }
+
+    /// Get the value as an `i64`, if it fits.
+    pub fn to_i64(&self) -> Option<i64> {
+        self.to_i128().and_then(|v| if v >= i64::MIN as i128 && v <= i64::MAX as i128 {
+            Some(v as i64)
+        } else {
+            None
+        })
+    }
+
+    /// Get the value as a `u64`, if it fits.
+    pub fn to_u64(&self) -> Option<u64> {
+        self.to_u128().and_then(|v| if v <= u64::MAX as u128 { Some(v as u64) } else { None })
+    }
+
+    /// Get the value as an `i128`, if it fits.
+    pub fn to_i128(&self) -> Option<i128> {
+        if self.bytes.len() > 16 {
+            return None;
+        }
+        let fill = if self.is_negative() { 0xff } else { 0 };
+        let mut buf = [fill; 16];
+        buf[16 - self.bytes.len()..].copy_from_slice(&self.bytes);
+        Some(i128::from_be_bytes(buf))
+    }
+
+    /// Get the value as a `u128`, if it fits.
+    pub fn to_u128(&self) -> Option<u128> {
+        let ret = self.to_unsigned_bytes_be();
+        match ret {
+            Some(bytes) if bytes.len() <= 16 => {
+                let mut buf = [0; 16];
+                buf[16 - bytes.len()..].copy_from_slice(bytes);
+                Some(u128::from_be_bytes(buf))
+            }
+            _ => None,
+        }
+    }
+
+    /// Add `other` to the value.
+    fn add(&self, other: &BigInt) -> BigInt {
+        // Sign-extend both operands to one octet more than the longer of them, which can't overflow
+        let n = if self.bytes.len() > other.bytes.len() { self.bytes.len() } else { other.bytes.len() } + 1;
+        let a = sign_extend(&self.bytes, n);
+        let b = sign_extend(&other.bytes, n);
+
+        let mut sum = vec![0; n];
+        let mut carry = 0u16;
+        for i in (0..n).rev() {
+            let s = a[i] as u16 + b[i] as u16 + carry;
+            sum[i] = s as u8;
+            carry = s >> 8;
+        }
+        minimize(&mut sum);
+        BigInt { bytes: sum }
+    }
+
+    /// Check if the value is between `min` and `max`.
+    fn is_within(&self, min: Option<i64>, max: Option<i64>) -> bool {
+        match self.to_i128() {
+            Some(v) => min.iter().all(|&l| v >= l as i128) && max.iter().all(|&h| v <= h as i128),
+            // Beyond either bound of an `i64`
+            None => {
+                if self.is_negative() {
+                    min.is_none()
+                } else {
+                    max.is_none()
+                }
+            }
+        }
+    }
+
+    /// Read a `BigInt` between `min` and `max` with `decoder`, whose `decode_int` handles both variants of PER.
+    fn decode(decoder: &mut Decoder, min: Option<i64>, max: Option<i64>) -> Result<BigInt, DecodeError> {
+        if min.is_some() && max.is_some() {
+            let ret = decoder.decode_int(min, max);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            return Ok(BigInt::from(ret.unwrap()));
+        }
+
+        let ret = decoder.decode_length();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let len = ret.unwrap();
+        if len == 0 {
+            return Err(DecodeError::MalformedInt);
+        }
+
+        let mut content: Vec<u8> = Vec::with_capacity(len);
+        let ret = decoder.read_to_vec(&mut content, len * 8);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+
+        match min {
+            // semiconstrained
+            Some(l) => Ok(BigInt::from_unsigned_bytes_be(&content).add(&BigInt::from(l))),
+            // unconstrained
+            None => Ok(BigInt::from_signed_bytes_be(&content)),
+        }
+    }
+
+    /// Write a `BigInt` between `min` and `max` to `encoder`.
+    fn encode(&self, encoder: &mut Encoder, min: Option<i64>, max: Option<i64>) -> Result<(), EncodeError> {
+        if min.is_some() && max.is_some() {
+            // constrained, so the value must fit in an `i64` too
+            return match self.to_i64() {
+                Some(v) => encoder.encode_int(v, min, max),
+                None => Err(EncodeError::InvalidValue),
+            };
+        }
+
+        let offset;
+        let content = match min {
+            // semiconstrained
+            Some(l) => {
+                offset = self.add(&BigInt::from(-(l as i128)));
+                match offset.to_unsigned_bytes_be() {
+                    Some(bytes) => bytes,
+                    None => return Err(EncodeError::InvalidValue),
+                }
+            }
+            // unconstrained
+            None => &self.bytes[..],
+        };
+
+        let ret = encoder.encode_length(content.len());
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        encoder.write_bytes(content);
+        Ok(())
+    }
+}
+
+/// Remove redundant leading sign octets from `bytes`, which must not be empty.
+fn minimize(bytes: &mut Vec<u8>) {
+    let mut n = 0;
+    while n + 1 < bytes.len() &&
+          ((bytes[n] == 0 && bytes[n + 1] & 0x80 == 0) || (bytes[n] == 0xff && bytes[n + 1] & 0x80 != 0)) {
+        n += 1;
+    }
+    bytes.drain(..n);
+}
+
+/// Sign-extend the 2's-complement octets `bytes` to `n` octets.
+fn sign_extend(bytes: &[u8], n: usize) -> Vec<u8> {
+    let fill = if bytes[0] & 0x80 != 0 { 0xff } else { 0 };
+    let mut ret = vec![fill; n - bytes.len()];
+    ret.extend_from_slice(bytes);
+    ret
+}
+
+macro_rules! big_int_from {
+    ($t:ident) => {
+        impl From<$t> for BigInt {
+            fn from(v: $t) -> BigInt {
+                if $t::MIN == 0 {
+                    BigInt::from_unsigned_bytes_be(&v.to_be_bytes())
+                } else {
+                    BigInt::from_signed_bytes_be(&v.to_be_bytes())
+                }
+            }
+        }
+    };
+}
+
+big_int_from!(i8);
+big_int_from!(i16);
+big_int_from!(i32);
+big_int_from!(i64);
+big_int_from!(i128);
+big_int_from!(u8);
+big_int_from!(u16);
+big_int_from!(u32);
+big_int_from!(u64);
+big_int_from!(u128);
+
+/// Get the bounds of an INTEGER from its value constraint, and whether they're extensible.
+fn bounds(constraints: Constraints) -> (Option<i64>, Option<i64>, bool) {
+    match constraints.value {
+        Some(c) => (c.min(), c.max(), c.is_extensible()),
+        None => (None, None, false),
+    }
+}
+
+/// Read a `BigInt` within the value constraint of `constraints`.
+fn decode_constrained(decoder: &mut Decoder, constraints: Constraints) -> Result<BigInt, DecodeError> {
+    let (min, max, extensible) = bounds(constraints);
+    let ret = decode_int_bounds(decoder, min, max, extensible);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    let (min, max) = ret.unwrap();
+    BigInt::decode(decoder, min, max)
+}
+
+/// Write `value` within the value constraint of `constraints` to `encoder`.
+fn encode_constrained(value: &BigInt, encoder: &mut Encoder, constraints: Constraints) -> Result<(), EncodeError> {
+    let (min, max, extensible) = bounds(constraints);
+    let (min, max) = encode_int_bounds(encoder, min, max, extensible, value.is_within(min, max));
+    value.encode(encoder, min, max)
+}
+
+impl APerElement for BigInt {
+    const CONSTRAINTS: Constraints = UNCONSTRAINED;
+
+    /// Read a `BigInt` from an aligned PER encoding.
+    fn from_aper(decoder: &mut Decoder, constraints: Constraints) -> Result<Self, DecodeError> {
+        decode_constrained(decoder, constraints)
+    }
+
+    fn to_aper(&self, constraints: Constraints) -> Result<Encoding, EncodeError> {
+        Encoder::encode(|enc| self.write_aper(enc, constraints))
+    }
+
+    fn write_aper(&self, encoder: &mut Encoder, constraints: Constraints) -> Result<(), EncodeError> {
+        encode_constrained(self, encoder, constraints)
+    }
+}
+
+/// The constraints of the unsigned 64 and 128-bit integers, which are semi-constrained INTEGERs.
+const NON_NEGATIVE: Constraints = Constraints {
+    value: Some(Constraint::new(Some(0), None)),
+    size: None,
+};
+
+macro_rules! wide_int_impl {
+    ($t:ident, $to:ident, $constraints:expr) => {
+        impl APerElement for $t {
+            const CONSTRAINTS: Constraints = $constraints;
+
+            /// Read an `$t` from an aligned PER encoding.
+            fn from_aper(decoder: &mut Decoder, constraints: Constraints) -> Result<Self, DecodeError> {
+                let constraints = if constraints.value.is_some() { constraints } else { $constraints };
+                let ret = decode_constrained(decoder, constraints);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                match ret.unwrap().$to() {
+                    Some(v) => Ok(v),
+                    None => Err(DecodeError::MalformedInt),
+                }
+            }
+
+            fn to_aper(&self, constraints: Constraints) -> Result<Encoding, EncodeError> {
+                Encoder::encode(|enc| self.write_aper(enc, constraints))
+            }
+
+            fn write_aper(&self, encoder: &mut Encoder, constraints: Constraints) -> Result<(), EncodeError> {
+                let constraints = if constraints.value.is_some() { constraints } else { $constraints };
+                encode_constrained(&BigInt::from(*self), encoder, constraints)
+            }
+        }
+    };
+}
+
+wide_int_impl!(i64, to_i64, UNCONSTRAINED);
+wide_int_impl!(i128, to_i128, UNCONSTRAINED);
+wide_int_impl!(u64, to_u64, NON_NEGATIVE);
+wide_int_impl!(u128, to_u128, NON_NEGATIVE);
@@ -1,9 +1,23 @@
-use aper::{APerElement, Constraints, Decoder, DecodeError, Encoding, EncodeError};
-use std::cmp;
-use utils::shift_bytes_left;
+use aper::{APerElement, Constraints, Decoder, DecodeError, Encoder, Encoding, EncodeError};
+use std::{cmp, fmt, iter, ops};
+use std::str::FromStr;
+use utils::size_bounds;
 
 /// A bit string.
 ///
+/// Bits are numbered as in X.680: bit 0 is the leading bit, which is the first to be encoded. As bytes, the bits are
+/// packed from the most significant bit of the first byte, so bit 0 is the MSB of the first byte and bit 8 is the MSB
+/// of the second. Integers are converted with their most significant bit as the leading bit, e.g. a 4-bit `BitString`
+/// of 0b1100 has bits 0 and 1 set.
+///
+/// A `BitString` can be parsed from, and is displayed in, ASN.1 `bstring` notation such as `'0101'B`. It can also be
+/// parsed from `hstring` notation such as `'5'H`, which holds 4 bits per hex digit.
+///
+/// Bit strings with a fixed size of up to 16 bits aren't octet-aligned, and any other non-empty bit strings are. A
+/// missing size constraint, or one without an upper bound below 64K, is treated as unbounded and the bits are preceded
+/// by a length determinant which may be fragmented. If the size constraint is extensible, bit strings of a size
+/// outside of it are encoded as if it were unbounded.
+///
 /// # Examples
 ///
 /// ```
@@ -13,25 +27,42 @@ use utils::shift_bytes_left;
 /// let mut b = BitString::with_len(64);
 /// b.set(0, true);
 /// println!("b[0] = {}", b.is_set(0)); // Prints b[0] = true
+///
+/// let id = BitString::from_uint(0x3a5, 10);
+/// println!("{}", id); // Prints '1110100101'B
+/// println!("{:?}", id.to_uint()); // Prints Some(933)
+/// println!("{}", id.slice(..4)); // Prints '1110'B
+///
+/// let mask: BitString = "'1100110011'B".parse().unwrap();
+/// println!("{}", &id & &mask); // Prints '1100100001'B
 /// ```
-#[derive(Debug)]
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
 pub struct BitString {
+    // Packed from the MSB of the first byte. Any unused bits of the last byte are always 0.
     data: Vec<u8>,
     num_bits: usize,
 }
 
+/// An error from parsing a `BitString` in `bstring` or `hstring` notation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseBitStringError;
+
 impl BitString {
+    /// Construct an empty `BitString`.
+    pub fn new() -> BitString {
+        BitString::default()
+    }
+
     /// Consturct a `BitString` of length `n` with all values set to 0.
     pub fn with_len(n: usize) -> BitString {
-        let mut ret = BitString {
-            data: Vec::<u8>::with_capacity(n / 8),
-            num_bits: 0,
-        };
-        ret.set_num_bits(n);
-        ret
+        BitString {
+            data: vec![0; n.div_ceil(8)],
+            num_bits: n,
+        }
     }
 
-    /// Consturct a `BitString` of length `n` with initial values contained in `data`.
+    /// Consturct a `BitString` of length `n` with initial values contained in `data`, which holds the bits packed from
+    /// the MSB of its first byte. Any bits of `data` past `n` are ignored, and any bits missing from it are 0.
     ///
     /// # Examples
     ///
@@ -44,11 +75,68 @@ impl BitString {
     /// println!("b[0] = {}", b.is_set(0)); // Prints b[0] = false
     /// println!("b[14] = {}", b.is_set(14)); // Prints b[14] = true
     /// ```
-    pub fn with_bytes_and_len(data: &Vec<u8>, n: usize) -> BitString {
-        BitString {
-            data: data.clone(),
+    pub fn with_bytes_and_len(data: &[u8], n: usize) -> BitString {
+        let num_bytes = n.div_ceil(8);
+        let mut ret = BitString {
+            data: data[..cmp::min(data.len(), num_bytes)].to_vec(),
             num_bits: n,
+        };
+        ret.data.resize(num_bytes, 0);
+        ret.clear_unused_bits();
+        ret
+    }
+
+    /// Construct a `BitString` holding all of the bits of `data`.
+    pub fn from_bytes(data: &[u8]) -> BitString {
+        BitString {
+            data: data.to_vec(),
+            num_bits: data.len() * 8,
+        }
+    }
+
+    /// Construct a `BitString` of length `n` from the `n` least significant bits of `value`, with the most significant
+    /// of those as bit 0. This is handy for identifiers such as a 22 to 32-bit gNB ID.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than 64.
+    pub fn from_uint(value: u64, n: usize) -> BitString {
+        assert!(n <= 64, "can't take more than 64 bits from a u64");
+        let mut ret = BitString::with_len(n);
+        for i in 0..n {
+            ret.set(i, (value >> (n - 1 - i)) & 1 == 1);
         }
+        ret
+    }
+
+    /// Get the bits as an unsigned integer, with bit 0 as the most significant bit. Returns `None` if there are more
+    /// than 64 bits.
+    pub fn to_uint(&self) -> Option<u64> {
+        if self.num_bits > 64 {
+            return None;
+        }
+        Some(self.iter().fold(0, |acc, b| (acc << 1) | b as u64))
+    }
+
+    /// Get the bits as a `Vec<bool>`, starting with bit 0.
+    pub fn to_bools(&self) -> Vec<bool> {
+        self.iter().collect()
+    }
+
+    /// Get the bits packed from the MSB of the first byte, with any unused bits of the last byte set to 0.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Get the bits in `hstring` notation without the quotes, as upper case hex digits. If the length isn't a multiple
+    /// of 4, the last digit is padded with 0 bits.
+    pub fn to_hex(&self) -> String {
+        let mut s = String::with_capacity(self.data.len() * 2);
+        for b in &self.data {
+            s.push_str(&format!("{:02X}", b));
+        }
+        s.truncate(self.num_bits.div_ceil(4));
+        s
     }
 
     /// Get the length of a `BitString`
@@ -56,103 +144,378 @@ impl BitString {
         self.num_bits
     }
 
+    /// Get the length of a `BitString`
+    pub fn len(&self) -> usize {
+        self.num_bits
+    }
+
+    /// Check if a `BitString` has no bits.
+    pub fn is_empty(&self) -> bool {
+        self.num_bits == 0
+    }
+
     /// Set the length of a `BitString` and initialize any new values to 0
     pub fn set_num_bits(&mut self, n: usize) {
         self.num_bits = n;
-        self.data.resize(n, 0);
+        self.data.resize(n.div_ceil(8), 0);
+        self.clear_unused_bits();
     }
 
-    /// Check if bit `i` is set.
-    pub fn is_set(&self, i: usize) -> bool {
-        let mut bucket = i / 8;
-        let pos = (i as i64 - bucket as i64 * 8) as usize;
-        if bucket > self.data.len() {
-            return false;
+    /// Remove any 0 bits from the end, as is done to a BIT STRING with named bits before it's encoded.
+    pub fn trim_trailing_zeros(&mut self) {
+        let n = match self.data.iter().rposition(|&b| b != 0) {
+            Some(i) => i * 8 + 8 - self.data[i].trailing_zeros() as usize,
+            None => 0,
+        };
+        self.set_num_bits(n);
+    }
+
+    /// Get bit `i`, or `None` if it's out of bounds.
+    pub fn get(&self, i: usize) -> Option<bool> {
+        if i >= self.num_bits {
+            return None;
         }
+        Some(self.data[i / 8] & (0x80 >> (i % 8)) > 0)
+    }
 
-        bucket = cmp::max(0, self.data.len() as i64 - bucket as i64 - 1) as usize;
-        (self.data[bucket] & (1 << pos)) > 0
+    /// Check if bit `i` is set. Bits out of bounds are never set.
+    pub fn is_set(&self, i: usize) -> bool {
+        self.get(i).unwrap_or(false)
     }
 
-    /// Set bit `i` to `val`.
+    /// Set bit `i` to `val`. This does nothing if `i` is out of bounds.
     pub fn set(&mut self, i: usize, val: bool) {
-        let mut bucket = i / 8;
-        let pos = (i as i64 - bucket as i64 * 8) as usize;
-        if bucket > self.data.len() {
+        if i >= self.num_bits {
             return;
         }
-
-        bucket = cmp::max(0, self.data.len() as i64 - bucket as i64 - 1) as usize;
         if val {
-            self.data[bucket] |= 1 << pos;
+            self.data[i / 8] |= 0x80 >> (i % 8);
         } else {
-            self.data[bucket] &= 0xFF & !(1 << pos);
+            self.data[i / 8] &= !(0x80 >> (i % 8));
         }
     }
-}
 
-impl APerElement for BitString {
-    const CONSTRAINTS: Constraints = Constraints {
-        value: None,
-        size: None,
-    };
+    /// Count the bits that are set.
+    pub fn count_ones(&self) -> usize {
+        self.data.iter().map(|b| b.count_ones() as usize).sum()
+    }
 
-    /// Construct a `BitString` from an aligned PER encoding.
-    fn from_aper(decoder: &mut Decoder, constraints: Constraints) -> Result<Self, DecodeError> {
-        if constraints.size.is_none() {
-            return Err(DecodeError::MissingSizeConstraint);
+    /// Add a bit to the end.
+    pub fn push(&mut self, val: bool) {
+        let i = self.num_bits;
+        self.set_num_bits(i + 1);
+        self.set(i, val);
+    }
+
+    /// Add the bits of `other` to the end.
+    pub fn append(&mut self, other: &BitString) {
+        let shift = self.num_bits % 8;
+        if shift == 0 {
+            self.data.extend_from_slice(&other.data);
+        } else {
+            for b in &other.data {
+                let last = self.data.len() - 1;
+                self.data[last] |= b >> shift;
+                self.data.push(b << (8 - shift));
+            }
+        }
+        self.num_bits += other.num_bits;
+        self.data.truncate(self.num_bits.div_ceil(8));
+    }
+
+    /// Get a copy of the bits in `range`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds, as with slices.
+    pub fn slice<R: ops::RangeBounds<usize>>(&self, range: R) -> BitString {
+        let start = match range.start_bound() {
+            ops::Bound::Included(&i) => i,
+            ops::Bound::Excluded(&i) => i + 1,
+            ops::Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            ops::Bound::Included(&i) => i + 1,
+            ops::Bound::Excluded(&i) => i,
+            ops::Bound::Unbounded => self.num_bits,
+        };
+        assert!(start <= end && end <= self.num_bits, "bit range {}..{} out of bounds", start, end);
+
+        if start.is_multiple_of(8) {
+            return BitString::with_bytes_and_len(&self.data[start / 8..], end - start);
         }
+        (start..end).map(|i| self.is_set(i)).collect()
+    }
 
-        let sz_constr = constraints.size.unwrap();
-        if sz_constr.max().is_none() || sz_constr.max().unwrap() == 0 {
-            return Ok(BitString::with_len(0));
+    /// Iterate over the bits, starting with bit 0.
+    pub fn iter(&self) -> BitIter<'_> {
+        BitIter {
+            bits: self,
+            range: 0..self.num_bits,
         }
+    }
 
-        let len = sz_constr.max().unwrap() as usize;
-        if len >= 65535 {
-            unimplemented!();
+    /// Clear the unused bits of the last byte, so that equal `BitString`s hold equal bytes.
+    fn clear_unused_bits(&mut self) {
+        if !self.num_bits.is_multiple_of(8) {
+            let last = self.data.len() - 1;
+            self.data[last] &= 0xff << (8 - self.num_bits % 8);
         }
+    }
+
+    /// Check if the bits are octet-aligned under `constraints`, which they are unless the size is fixed at 16 bits or
+    /// fewer.
+    fn is_aligned(constraints: Constraints) -> bool {
+        let (lb, ub, _) = size_bounds(constraints.size);
+        ub.is_none_or(|ub| ub != lb || ub > 16)
+    }
 
-        let num_bytes = (len as f64 / 8.).ceil() as usize;
-        let mut content: Vec<u8> = Vec::with_capacity(num_bytes);
-        let ret = decoder.read_to_vec(&mut content, len);
+    /// Read a `BitString` from `decoder`.
+    fn decode(decoder: &mut Decoder, constraints: Constraints) -> Result<Self, DecodeError> {
+        let aligned = Self::is_aligned(constraints);
+        let mut content: Vec<u8> = Vec::new();
+        let ret = decoder.decode_sized(constraints.size, |decoder, n| {
+            if aligned {
+                let ret = decoder.align();
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+            }
+
+            // Fragments are whole octets, so only the last run of bits can end partway through an octet
+            content.reserve(n.div_ceil(8));
+            let ret = decoder.read_to_vec(&mut content, n - n % 8);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            if n % 8 > 0 {
+                let ret = decoder.read(n % 8);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                content.push(ret.unwrap() << (8 - n % 8));
+            }
+            Ok(())
+        });
         if ret.is_err() {
             return Err(ret.err().unwrap());
         }
 
-        let delta = num_bytes * 8 - len;
-        if delta > 0 && num_bytes > 1 {
-            shift_bytes_left(&mut content, delta);
+        Ok(BitString {
+            data: content,
+            num_bits: ret.unwrap(),
+        })
+    }
+
+    /// Write a `BitString` to `encoder`.
+    fn encode(&self, encoder: &mut Encoder, constraints: Constraints) -> Result<(), EncodeError> {
+        let aligned = Self::is_aligned(constraints);
+        encoder.encode_sized(self.num_bits, constraints.size, |enc, start, n| {
+            if aligned {
+                enc.align();
+            }
+            enc.write_bits_from(&self.data[start / 8..], n);
+            Ok(())
+        })
+    }
+}
+
+/// An iterator over the bits of a `BitString`, created by [BitString::iter()](struct.BitString.html#method.iter).
+pub struct BitIter<'a> {
+    bits: &'a BitString,
+    range: ops::Range<usize>,
+}
+
+impl<'a> Iterator for BitIter<'a> {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<bool> {
+        self.range.next().map(|i| self.bits.is_set(i))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl<'a> DoubleEndedIterator for BitIter<'a> {
+    fn next_back(&mut self) -> Option<bool> {
+        self.range.next_back().map(|i| self.bits.is_set(i))
+    }
+}
+
+impl<'a> ExactSizeIterator for BitIter<'a> {}
+
+impl<'a> IntoIterator for &'a BitString {
+    type Item = bool;
+    type IntoIter = BitIter<'a>;
+
+    fn into_iter(self) -> BitIter<'a> {
+        self.iter()
+    }
+}
+
+impl iter::FromIterator<bool> for BitString {
+    fn from_iter<I: IntoIterator<Item = bool>>(bits: I) -> BitString {
+        let mut ret = BitString::new();
+        ret.extend(bits);
+        ret
+    }
+}
+
+impl iter::Extend<bool> for BitString {
+    fn extend<I: IntoIterator<Item = bool>>(&mut self, bits: I) {
+        for b in bits {
+            self.push(b);
         }
+    }
+}
 
-        Ok(BitString::with_bytes_and_len(&content, len))
+impl<'a> From<&'a [bool]> for BitString {
+    fn from(bits: &'a [bool]) -> BitString {
+        bits.iter().cloned().collect()
     }
+}
 
-    fn to_aper(&self, constraints: Constraints) -> Result<Encoding, EncodeError> {
-        if constraints.size.is_none() {
-            return Err(EncodeError::MissingSizeConstraint);
+impl From<Vec<bool>> for BitString {
+    fn from(bits: Vec<bool>) -> BitString {
+        BitString::from(&bits[..])
+    }
+}
+
+impl FromStr for BitString {
+    type Err = ParseBitStringError;
+
+    /// Parse a `BitString` in `bstring` notation such as `'0101'B`, or `hstring` notation such as `'5'H`. As in
+    /// ASN.1, white space between the quotes is ignored.
+    fn from_str(s: &str) -> Result<BitString, ParseBitStringError> {
+        let s = s.trim();
+        if s.len() < 3 || !s.starts_with('\'') || !s.is_char_boundary(s.len() - 2) {
+            return Err(ParseBitStringError);
+        }
+        let (body, suffix) = s[1..].split_at(s.len() - 3);
+        let digits = body.chars().filter(|c| !c.is_whitespace());
+
+        let mut ret = BitString::new();
+        match suffix {
+            "'B" => {
+                for c in digits {
+                    match c {
+                        '0' => ret.push(false),
+                        '1' => ret.push(true),
+                        _ => return Err(ParseBitStringError),
+                    }
+                }
+            }
+            "'H" => {
+                for c in digits {
+                    if !c.is_ascii_hexdigit() || c.is_ascii_lowercase() {
+                        return Err(ParseBitStringError);
+                    }
+                    ret.append(&BitString::from_uint(c.to_digit(16).unwrap() as u64, 4));
+                }
+            }
+            _ => return Err(ParseBitStringError),
+        }
+        Ok(ret)
+    }
+}
+
+impl fmt::Display for BitString {
+    /// Format the bits in `bstring` notation, such as `'0101'B`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s: String = self.iter().map(|b| if b { '1' } else { '0' }).collect();
+        write!(f, "'{}'B", s)
+    }
+}
+
+impl fmt::Debug for BitString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "BitString({})", self)
+    }
+}
+
+impl ops::Not for BitString {
+    type Output = BitString;
+
+    fn not(mut self) -> BitString {
+        for b in &mut self.data {
+            *b = !*b;
         }
+        self.clear_unused_bits();
+        self
+    }
+}
+
+impl ops::Not for &BitString {
+    type Output = BitString;
 
-        let sz_constr = constraints.size.unwrap();
-        if sz_constr.max().is_none() || sz_constr.max().unwrap() == 0 {
-            return Ok(Encoding::new());
+    fn not(self) -> BitString {
+        !self.clone()
+    }
+}
+
+// Binary operators work bit by bit. If the operands differ in length, the shorter one is extended with 0 bits.
+macro_rules! bit_op_impl {
+    ($op:ident, $f:ident, $op_assign:ident, $f_assign:ident, $e:tt) => {
+        impl<'a> ops::$op_assign<&'a BitString> for BitString {
+            fn $f_assign(&mut self, other: &'a BitString) {
+                if other.num_bits > self.num_bits {
+                    self.set_num_bits(other.num_bits);
+                }
+                for (i, b) in self.data.iter_mut().enumerate() {
+                    *b = *b $e other.data.get(i).cloned().unwrap_or(0);
+                }
+            }
         }
 
-        let len = sz_constr.max().unwrap() as usize;
-        if len >= 65535 {
-            return Err(EncodeError::NotImplemented);
+        impl ops::$op_assign for BitString {
+            fn $f_assign(&mut self, other: BitString) {
+                ops::$op_assign::$f_assign(self, &other);
+            }
         }
 
-        let mut l_padding = 0;
-        let r_padding = 0;
-        if self.num_bits < 8 {
-            l_padding = 8 - self.num_bits;
-        } else if self.num_bits <= 16 {
-            l_padding = 16 - self.num_bits;
+        impl<'a, 'b> ops::$op<&'b BitString> for &'a BitString {
+            type Output = BitString;
+
+            fn $f(self, other: &'b BitString) -> BitString {
+                let mut ret = self.clone();
+                ops::$op_assign::$f_assign(&mut ret, other);
+                ret
+            }
         }
-        let mut bytes = self.data.clone();
-        shift_bytes_left(&mut bytes, l_padding); // XXX: this is incorrect for n_bits > 8
-        let enc = Encoding::with_bytes_and_padding(bytes, r_padding + l_padding);
-        Ok(enc)
+
+        impl ops::$op for BitString {
+            type Output = BitString;
+
+            fn $f(mut self, other: BitString) -> BitString {
+                ops::$op_assign::$f_assign(&mut self, &other);
+                self
+            }
+        }
+    }
+}
+
+bit_op_impl!(BitAnd, bitand, BitAndAssign, bitand_assign, &);
+bit_op_impl!(BitOr, bitor, BitOrAssign, bitor_assign, |);
+bit_op_impl!(BitXor, bitxor, BitXorAssign, bitxor_assign, ^);
+
+impl APerElement for BitString {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Construct a `BitString` from an aligned PER encoding.
+    fn from_aper(decoder: &mut Decoder, constraints: Constraints) -> Result<Self, DecodeError> {
+        Self::decode(decoder, constraints)
+    }
+
+    fn to_aper(&self, constraints: Constraints) -> Result<Encoding, EncodeError> {
+        Encoder::encode(|enc| self.write_aper(enc, constraints))
+    }
+
+    fn write_aper(&self, encoder: &mut Encoder, constraints: Constraints) -> Result<(), EncodeError> {
+        self.encode(encoder, constraints)
     }
 }
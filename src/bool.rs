@@ -1,4 +1,4 @@
-use aper::{APerElement, Constraints, Decoder, DecodeError, Encoding, EncodeError};
+use aper::{APerElement, Constraints, Decoder, DecodeError, Encoder, Encoding, EncodeError};
 
 impl APerElement for bool {
     const CONSTRAINTS: Constraints = Constraints {
@@ -15,7 +15,12 @@ impl APerElement for bool {
         Ok(ret.unwrap() > 0)
     }
 
-    fn to_aper(&self, _: Constraints) -> Result<Encoding, EncodeError> {
-        Ok(Encoding::with_bytes_and_padding(vec![(*self as u8) << 7], 7))
+    fn to_aper(&self, constraints: Constraints) -> Result<Encoding, EncodeError> {
+        Encoder::encode(|enc| self.write_aper(enc, constraints))
+    }
+
+    fn write_aper(&self, encoder: &mut Encoder, _: Constraints) -> Result<(), EncodeError> {
+        encoder.write_bits(*self as u64, 1);
+        Ok(())
     }
 }
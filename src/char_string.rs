@@ -0,0 +1,399 @@
+use aper::{APerElement, Constraint, Constraints, Decoder, DecodeError, Encoder, Encoding, EncodeError, UNCONSTRAINED};
+use std::{char, cmp, fmt};
+use utils::{range_bits, size_bounds};
+
+/// The characters that a string may hold, as sorted and disjoint ranges of their codes.
+struct Alphabet {
+    ranges: Vec<(u32, u32)>,
+}
+
+impl Alphabet {
+    /// Get the effective permitted alphabet of a string type whose characters are `chars`, under the value
+    /// constraint `constraint`.
+    fn new(chars: &[(u32, u32)], constraint: Option<Constraint>) -> Alphabet {
+        let c = match constraint {
+            Some(c) => c,
+            None => return Alphabet { ranges: chars.to_vec() },
+        };
+
+        let mut ranges: Vec<(u32, u32)> = Vec::new();
+        match c.alphabet() {
+            Some(s) => {
+                let mut codes: Vec<u32> = s.chars()
+                    .map(|c| c as u32)
+                    .filter(|&c| chars.iter().any(|&(lo, hi)| lo <= c && c <= hi))
+                    .collect();
+                codes.sort();
+                codes.dedup();
+                for c in codes {
+                    match ranges.last_mut() {
+                        Some(&mut (_, ref mut hi)) if *hi + 1 == c => *hi = c,
+                        _ => ranges.push((c, c)),
+                    }
+                }
+            }
+            None => {
+                // An interval of codes, as in FROM ("A".."Z")
+                let min = cmp::max(c.min().unwrap_or(0), 0);
+                let max = c.max().unwrap_or(u32::MAX as i64);
+                for &(lo, hi) in chars {
+                    let lo = cmp::max(lo as i64, min);
+                    let hi = cmp::min(hi as i64, max);
+                    if lo <= hi {
+                        ranges.push((lo as u32, hi as u32));
+                    }
+                }
+            }
+        }
+        Alphabet { ranges }
+    }
+
+    /// Get the number of characters.
+    fn len(&self) -> u64 {
+        self.ranges.iter().map(|&(lo, hi)| (hi - lo) as u64 + 1).sum()
+    }
+
+    /// Get the number of bits that each character is encoded in. Aligned PER rounds this up to a power of 2.
+    fn char_bits(&self, aligned: bool) -> usize {
+        let n = self.len();
+        if n <= 1 {
+            return 0;
+        }
+        let b = range_bits(0, n as i64 - 1);
+        if aligned { b.next_power_of_two() } else { b }
+    }
+
+    /// Check if characters are encoded as their index in the alphabet, which is the case when the largest code doesn't
+    /// fit in `bits`.
+    fn is_indexed(&self, bits: usize) -> bool {
+        match self.ranges.last() {
+            Some(&(_, hi)) => hi as u64 > (1u64 << bits) - 1,
+            None => false,
+        }
+    }
+
+    /// Get the index of the character with the code `c`, if it's in the alphabet.
+    fn index(&self, c: u32) -> Option<u64> {
+        let mut i = 0;
+        for &(lo, hi) in &self.ranges {
+            if c < lo {
+                return None;
+            }
+            if c <= hi {
+                return Some(i + (c - lo) as u64);
+            }
+            i += (hi - lo) as u64 + 1;
+        }
+        None
+    }
+
+    /// Get the code of the character at index `i`.
+    fn code(&self, mut i: u64) -> Option<u32> {
+        for &(lo, hi) in &self.ranges {
+            let n = (hi - lo) as u64 + 1;
+            if i < n {
+                return Some(lo + i as u32);
+            }
+            i -= n;
+        }
+        None
+    }
+}
+
+/// Check if the characters of a known-multiplier string are octet-aligned, which they are unless the size constraint
+/// has an upper bound and the characters fit in 16 bits at that size.
+fn is_aligned(constraints: Constraints, bits: usize) -> bool {
+    let (_, ub, _) = size_bounds(constraints.size);
+    ub.is_none_or(|ub| ub.saturating_mul(bits) > 16)
+}
+
+/// Read a string whose characters are `chars` from `decoder`.
+fn decode_chars(decoder: &mut Decoder, chars: &[(u32, u32)], constraints: Constraints) -> Result<String, DecodeError> {
+    let alphabet = Alphabet::new(chars, constraints.value);
+    let bits = alphabet.char_bits(decoder.is_aligned());
+    let indexed = alphabet.is_indexed(bits);
+    let aligned = is_aligned(constraints, bits);
+
+    let mut s = String::new();
+    let ret = decoder.decode_sized(constraints.size, |decoder, n| {
+        if aligned {
+            let ret = decoder.align();
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+        }
+        for _ in 0..n {
+            let ret = decoder.read_bits(bits);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let v = ret.unwrap();
+            let code = if indexed {
+                alphabet.code(v)
+            } else {
+                alphabet.index(v as u32).map(|_| v as u32)
+            };
+            match code.and_then(char::from_u32) {
+                Some(c) => s.push(c),
+                None => return Err(DecodeError::InvalidCharacter(v as u32)),
+            }
+        }
+        Ok(())
+    });
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    Ok(s)
+}
+
+/// Write `s`, a string whose characters are `chars`, to `encoder`.
+fn encode_chars(encoder: &mut Encoder, s: &str, chars: &[(u32, u32)], constraints: Constraints)
+                -> Result<(), EncodeError> {
+    let alphabet = Alphabet::new(chars, constraints.value);
+    let bits = alphabet.char_bits(encoder.is_aligned());
+    let indexed = alphabet.is_indexed(bits);
+    let aligned = is_aligned(constraints, bits);
+
+    let mut values: Vec<u64> = Vec::with_capacity(s.len());
+    for c in s.chars() {
+        match alphabet.index(c as u32) {
+            Some(i) if indexed => values.push(i),
+            Some(_) => values.push(c as u64),
+            None => return Err(EncodeError::InvalidValue),
+        }
+    }
+
+    encoder.encode_sized(values.len(), constraints.size, |enc, start, n| {
+        if aligned {
+            enc.align();
+        }
+        for &v in &values[start..start + n] {
+            enc.write_bits(v, bits);
+        }
+        Ok(())
+    })
+}
+
+/// Read the octets of a string that isn't a known-multiplier character string from `decoder`, checking that they're
+/// valid UTF-8.
+fn decode_octets(decoder: &mut Decoder) -> Result<String, DecodeError> {
+    let mut content: Vec<u8> = Vec::new();
+    let ret = decoder.decode_sized(None, |decoder, n| {
+        let ret = decoder.align();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        decoder.read_to_vec(&mut content, n * 8)
+    });
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    String::from_utf8(content).map_err(|_| DecodeError::MalformedString)
+}
+
+/// Write `bytes`, the octets of a string that isn't a known-multiplier character string, to `encoder`.
+fn encode_octets(encoder: &mut Encoder, bytes: &[u8]) -> Result<(), EncodeError> {
+    encoder.encode_sized(bytes.len(), None, |enc, start, n| {
+        enc.align();
+        enc.write_bytes(&bytes[start..start + n]);
+        Ok(())
+    })
+}
+
+/// An IA5String, which holds characters with codes 0 to 127, i.e. ASCII.
+///
+/// `Ia5String`, `PrintableString`, `NumericString`, `VisibleString`, `BmpString` and `UniversalString` are the
+/// known-multiplier character strings, whose characters are each encoded in the same number of bits. That number
+/// depends on how many characters the string may hold, which can be reduced with a permitted alphabet constraint given
+/// as the value constraint. If the codes of the permitted characters don't fit in that many bits, each character is
+/// encoded as its index in the permitted alphabet instead. Aligned PER rounds the number of bits up to a power of 2.
+///
+/// The length of the string is encoded as for a `BitString`. The characters are octet-aligned unless the size
+/// constraint has an upper bound at which they fit in 16 bits.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::Ia5String;
+/// use asn1::aper::{APerElement, Constraint, Constraints, UNCONSTRAINED};
+///
+/// let x = Ia5String::from("FOO");
+/// println!("{:?}", x.to_aper(UNCONSTRAINED).unwrap().bytes()); // Prints [3, 70, 79, 79]
+///
+/// // Digits ::= IA5String (FROM ("0123456789") SIZE (4))
+/// let constraints = Constraints {
+///     value: Some(Constraint::new_alphabet("0123456789")),
+///     size: Some(Constraint::new(Some(4), Some(4))),
+/// };
+/// let x = Ia5String::from("1234");
+/// println!("{:?}", x.to_aper(constraints).unwrap().bytes()); // Prints [18, 52]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Ia5String(pub String);
+
+/// A PrintableString, which holds letters, digits, space and `'()+,-./:=?`. See [Ia5String](struct.Ia5String.html)
+/// for how it's encoded.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct PrintableString(pub String);
+
+/// A NumericString, which holds digits and space. See [Ia5String](struct.Ia5String.html) for how it's encoded.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct NumericString(pub String);
+
+/// A VisibleString, which holds the printing ASCII characters and space. See [Ia5String](struct.Ia5String.html) for
+/// how it's encoded.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct VisibleString(pub String);
+
+/// A BMPString, which holds the characters of the Basic Multilingual Plane in 16 bits each. See
+/// [Ia5String](struct.Ia5String.html) for how it's encoded.
+///
+/// Surrogate code points can't be held in a `String`, so decoding one fails with `DecodeError::InvalidCharacter`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BmpString(pub String);
+
+/// A UniversalString, which holds any character in 32 bits. See [Ia5String](struct.Ia5String.html) for how it's
+/// encoded.
+///
+/// Codes that aren't Unicode scalar values can't be held in a `String`, so decoding one fails with
+/// `DecodeError::InvalidCharacter`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct UniversalString(pub String);
+
+/// A UTF8String.
+///
+/// A UTF8String isn't a known-multiplier character string, so its constraints aren't PER-visible. It's encoded as its
+/// octets, preceded by an unconstrained length determinant which counts them. Decoding octets that aren't valid UTF-8
+/// fails with `DecodeError::MalformedString`.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::Utf8String;
+/// use asn1::aper::{APerElement, UNCONSTRAINED};
+///
+/// let x = Utf8String::from("né");
+/// println!("{:?}", x.to_aper(UNCONSTRAINED).unwrap().bytes()); // Prints [3, 110, 195, 169]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Utf8String(pub String);
+
+/// A GraphicString, which is encoded like a [Utf8String](struct.Utf8String.html).
+///
+/// The ISO 2022 character sets and escape sequences of a GraphicString, GeneralString or TeletexString aren't
+/// interpreted, so the octets are held as they are. Decoding fails with `DecodeError::MalformedString` if they aren't
+/// valid UTF-8, such as with 8-bit characters from a G1 set.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct GraphicString(pub String);
+
+/// A GeneralString, which is encoded like a [Utf8String](struct.Utf8String.html). See
+/// [GraphicString](struct.GraphicString.html) for how its characters are handled.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct GeneralString(pub String);
+
+/// A TeletexString (T61String), which is encoded like a [Utf8String](struct.Utf8String.html). See
+/// [GraphicString](struct.GraphicString.html) for how its characters are handled.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct TeletexString(pub String);
+
+const IA5_CHARS: &[(u32, u32)] = &[(0x00, 0x7f)];
+const PRINTABLE_CHARS: &[(u32, u32)] = &[(0x20, 0x20), (0x27, 0x29), (0x2b, 0x3a), (0x3d, 0x3d), (0x3f, 0x3f),
+                                         (0x41, 0x5a), (0x61, 0x7a)];
+const NUMERIC_CHARS: &[(u32, u32)] = &[(0x20, 0x20), (0x30, 0x39)];
+const VISIBLE_CHARS: &[(u32, u32)] = &[(0x20, 0x7e)];
+const BMP_CHARS: &[(u32, u32)] = &[(0x0000, 0xffff)];
+const UNIVERSAL_CHARS: &[(u32, u32)] = &[(0x0000_0000, 0xffff_ffff)];
+
+macro_rules! string_impl {
+    ($t:ident) => {
+        impl $t {
+            /// Get the characters as a `&str`.
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl<'a> From<&'a str> for $t {
+            fn from(s: &'a str) -> $t {
+                $t(s.to_string())
+            }
+        }
+
+        impl From<String> for $t {
+            fn from(s: String) -> $t {
+                $t(s)
+            }
+        }
+
+        impl fmt::Display for $t {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+    }
+}
+
+macro_rules! known_multiplier_impl {
+    ($t:ident, $chars:expr) => {
+        string_impl!($t);
+
+        impl APerElement for $t {
+            const CONSTRAINTS: Constraints = UNCONSTRAINED;
+
+            fn from_aper(decoder: &mut Decoder, constraints: Constraints) -> Result<Self, DecodeError> {
+                let ret = decode_chars(decoder, $chars, constraints);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                Ok($t(ret.unwrap()))
+            }
+
+            fn to_aper(&self, constraints: Constraints) -> Result<Encoding, EncodeError> {
+                Encoder::encode(|enc| self.write_aper(enc, constraints))
+            }
+
+            fn write_aper(&self, encoder: &mut Encoder, constraints: Constraints) -> Result<(), EncodeError> {
+                encode_chars(encoder, &self.0, $chars, constraints)
+            }
+        }
+    }
+}
+
+macro_rules! octets_impl {
+    ($t:ident) => {
+        string_impl!($t);
+
+        impl APerElement for $t {
+            const CONSTRAINTS: Constraints = UNCONSTRAINED;
+
+            fn from_aper(decoder: &mut Decoder, _: Constraints) -> Result<Self, DecodeError> {
+                let ret = decode_octets(decoder);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                Ok($t(ret.unwrap()))
+            }
+
+            fn to_aper(&self, constraints: Constraints) -> Result<Encoding, EncodeError> {
+                Encoder::encode(|enc| self.write_aper(enc, constraints))
+            }
+
+            fn write_aper(&self, encoder: &mut Encoder, _: Constraints) -> Result<(), EncodeError> {
+                encode_octets(encoder, self.0.as_bytes())
+            }
+        }
+    }
+}
+
+known_multiplier_impl!(Ia5String, IA5_CHARS);
+known_multiplier_impl!(PrintableString, PRINTABLE_CHARS);
+known_multiplier_impl!(NumericString, NUMERIC_CHARS);
+known_multiplier_impl!(VisibleString, VISIBLE_CHARS);
+known_multiplier_impl!(BmpString, BMP_CHARS);
+known_multiplier_impl!(UniversalString, UNIVERSAL_CHARS);
+octets_impl!(Utf8String);
+octets_impl!(GraphicString);
+octets_impl!(GeneralString);
+octets_impl!(TeletexString);
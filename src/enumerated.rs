@@ -0,0 +1,165 @@
+use aper::{APerElement, Constraints, Decoder, DecodeError, Encoder, Encoding, EncodeError, UNCONSTRAINED};
+
+/// Trait for C-like enums that correspond to an ASN.1 ENUMERATED type.
+///
+/// Each item has an enumeration index. The root items are indexed from 0 in ascending order of their values, and the
+/// extension additions follow from `NUM_ROOT` in the order they're declared. A root item is encoded as a constrained
+/// integer over the number of root items, and an extension addition as a normally small number, which is its index
+/// less `NUM_ROOT`.
+///
+/// `Enumerated` types can be decoded with [EnumeratedValue](enum.EnumeratedValue.html), which can also hold extension
+/// additions that aren't known to the decoder. `APerElement` can be derived for an `Enumerated` type with the
+/// `asn1-derive` crate, which fails to decode unknown additions.
+///
+/// # Examples
+///
+/// ```
+/// Color ::= ENUMERATED { red, green, ..., blue }
+/// ```
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::{Enumerated, EnumeratedValue};
+/// use asn1::aper::{self, APerElement, UNCONSTRAINED};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// enum Color {
+///     Red,
+///     Green,
+///     Blue,
+/// }
+///
+/// impl Enumerated for Color {
+///     const NUM_ROOT: usize = 2;
+///     const EXTENSIBLE: bool = true;
+///
+///     fn from_index(i: usize) -> Option<Self> {
+///         match i {
+///             0 => Some(Color::Red),
+///             1 => Some(Color::Green),
+///             2 => Some(Color::Blue),
+///             _ => None,
+///         }
+///     }
+///
+///     fn index(&self) -> usize {
+///         *self as usize
+///     }
+/// }
+///
+/// let enc = EnumeratedValue::Known(Color::Green).to_aper(UNCONSTRAINED).unwrap();
+/// println!("{:?}", enc.bytes()); // Prints [64]
+///
+/// // The second extension addition, which this version of `Color` doesn't know
+/// let data = b"\x81";
+/// let mut d = aper::Decoder::new(data);
+/// let x = EnumeratedValue::<Color>::from_aper(&mut d, UNCONSTRAINED).unwrap();
+/// println!("{:?}", x); // Prints Unknown(1)
+/// ```
+pub trait Enumerated: Sized {
+    /// The number of root items.
+    const NUM_ROOT: usize;
+    /// Whether the type has an extension marker.
+    const EXTENSIBLE: bool;
+
+    /// Get the item with the enumeration index `i`, if there is one.
+    fn from_index(i: usize) -> Option<Self>;
+
+    /// Get the enumeration index of the item.
+    fn index(&self) -> usize;
+}
+
+/// A decoded ENUMERATED value, which may be an extension addition that isn't known to the decoder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnumeratedValue<T> {
+    Known(T),
+    /// An unknown extension addition, with its index among the additions (i.e. its enumeration index less
+    /// `NUM_ROOT`).
+    Unknown(u64),
+}
+
+impl<T> EnumeratedValue<T> {
+    /// Get the item, if it's known.
+    pub fn known(self) -> Option<T> {
+        match self {
+            EnumeratedValue::Known(x) => Some(x),
+            EnumeratedValue::Unknown(_) => None,
+        }
+    }
+}
+
+impl<T: Enumerated> EnumeratedValue<T> {
+    /// Read an `EnumeratedValue` with `decoder`, whose `decode_int` handles both variants of PER.
+    fn decode(decoder: &mut Decoder) -> Result<Self, DecodeError> {
+        if T::EXTENSIBLE {
+            let ret = decoder.read(1);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            if ret.unwrap() > 0 {
+                let ret = decoder.decode_normally_small();
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                let n = ret.unwrap();
+                return match T::from_index(T::NUM_ROOT + n as usize) {
+                    Some(x) => Ok(EnumeratedValue::Known(x)),
+                    None => Ok(EnumeratedValue::Unknown(n)),
+                };
+            }
+        }
+
+        // The index of a root item takes no bits at all if there's just one
+        let mut i = 0;
+        if T::NUM_ROOT > 1 {
+            let ret = decoder.decode_int(Some(0), Some(T::NUM_ROOT as i64 - 1));
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            i = ret.unwrap();
+        }
+        match T::from_index(i as usize) {
+            Some(x) if (i as usize) < T::NUM_ROOT => Ok(EnumeratedValue::Known(x)),
+            _ => Err(DecodeError::InvalidEnumerated(i)),
+        }
+    }
+
+    /// Write an `EnumeratedValue` to `encoder`.
+    fn encode(&self, encoder: &mut Encoder) -> Result<(), EncodeError> {
+        let (root, addition) = match *self {
+            EnumeratedValue::Known(ref x) if x.index() < T::NUM_ROOT => (Some(x.index()), None),
+            EnumeratedValue::Known(ref x) => (None, Some((x.index() - T::NUM_ROOT) as u64)),
+            EnumeratedValue::Unknown(n) => (None, Some(n)),
+        };
+        if addition.is_some() && !T::EXTENSIBLE {
+            return Err(EncodeError::InvalidValue);
+        }
+
+        if T::EXTENSIBLE {
+            encoder.write_bits(addition.is_some() as u64, 1);
+        }
+
+        match (root, addition) {
+            (_, Some(n)) => encoder.encode_normally_small(n),
+            (Some(i), None) if T::NUM_ROOT > 1 => encoder.encode_int(i as i64, Some(0), Some(T::NUM_ROOT as i64 - 1)),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<T: Enumerated> APerElement for EnumeratedValue<T> {
+    const CONSTRAINTS: Constraints = UNCONSTRAINED;
+
+    /// Read an `EnumeratedValue` from an aligned PER encoding.
+    fn from_aper(decoder: &mut Decoder, _: Constraints) -> Result<Self, DecodeError> {
+        Self::decode(decoder)
+    }
+
+    fn to_aper(&self, constraints: Constraints) -> Result<Encoding, EncodeError> {
+        Encoder::encode(|enc| self.write_aper(enc, constraints))
+    }
+
+    fn write_aper(&self, encoder: &mut Encoder, _: Constraints) -> Result<(), EncodeError> {
+        self.encode(encoder)
+    }
+}
@@ -1,5 +1,16 @@
-use aper::{APerElement, Constraints, Decoder, DecodeError, Encoding, EncodeError, encode_int};
+use aper::{APerElement, Constraints, Decoder, DecodeError, Encoder, Encoding, EncodeError};
+use std::convert::TryFrom;
 use std::{i8, i16, i32, u8, u16, u32};
+use utils::{decode_int_bounds, encode_int_bounds};
+
+/// Get the bounds of an integer field, which default to `[min, max]` unless `constraints` has a value constraint, and
+/// whether they're extensible.
+fn bounds(constraints: Constraints, min: i64, max: i64) -> (Option<i64>, Option<i64>, bool) {
+    match constraints.value {
+        Some(c) => (c.min(), c.max(), c.is_extensible()),
+        None => (Some(min), Some(max), false),
+    }
+}
 
 macro_rules! int_impl {
     ($t:ident) => {
@@ -9,20 +20,31 @@ macro_rules! int_impl {
                 size: None,
             };
             /// Read an `$t` from an aligned PER encoding.
-            fn from_aper(decoder: &mut Decoder, _: Constraints) -> Result<Self, DecodeError> {
-                let ret = decoder.decode_int(Some($t::MIN as i64), Some($t::MAX as i64));
+            fn from_aper(decoder: &mut Decoder, constraints: Constraints) -> Result<Self, DecodeError> {
+                let (min, max, extensible) = bounds(constraints, $t::MIN as i64, $t::MAX as i64);
+                let ret = decode_int_bounds(decoder, min, max, extensible);
                 if ret.is_err() {
                     return Err(ret.err().unwrap());
                 }
-                Ok(ret.unwrap() as $t)
-            }
-
-            fn to_aper(&self, _: Constraints) -> Result<Encoding, EncodeError> {
-                let ret = encode_int(*self as i64, Some($t::MIN as i64), Some($t::MAX as i64));
+                let (min, max) = ret.unwrap();
+                let ret = decoder.decode_int(min, max);
                 if ret.is_err() {
                     return Err(ret.err().unwrap());
                 }
-                Ok(ret.unwrap())
+                // A value constraint may allow values that don't fit in the type
+                $t::try_from(ret.unwrap()).map_err(|_| DecodeError::MalformedInt)
+            }
+
+            fn to_aper(&self, constraints: Constraints) -> Result<Encoding, EncodeError> {
+                Encoder::encode(|enc| self.write_aper(enc, constraints))
+            }
+
+            fn write_aper(&self, encoder: &mut Encoder, constraints: Constraints) -> Result<(), EncodeError> {
+                let (min, max, extensible) = bounds(constraints, $t::MIN as i64, $t::MAX as i64);
+                let v = *self as i64;
+                let in_root = min.iter().all(|&l| v >= l) && max.iter().all(|&h| v <= h);
+                let (min, max) = encode_int_bounds(encoder, min, max, extensible, in_root);
+                encoder.encode_int(v, min, max)
             }
         }
     };
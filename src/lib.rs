@@ -4,38 +4,90 @@
 //!
 //! Below are the currently supported ASN.1 types and their corresponding types/constructs in Rust.
 //!
-//! | ASN.1 Type     | Rust Type             |
-//! |----------------|-----------------------|
-//! | BIT STRING     | BitString             |
-//! | INTEGER*       | i8,i16,i32,u8,u16,u32 |
-//! | NULL           | ()                    |
-//! | OCTET STRING   | Vec\<u8\>             |
-//! | SEQUENCE       | struct                |
-//! | SEQUENCE OF    | Vec\<T\>              |
-//! | CHOICE         | enum                  |
+//! | ASN.1 Type        | Rust Type                      |
+//! |-------------------|--------------------------------|
+//! | BIT STRING        | BitString                      |
+//! | BIT STRING {…}    | NamedBits\<T\>                 |
+//! | INTEGER*          | i8 to i128, u8 to u128, BigInt |
+//! | NULL              | ()                             |
+//! | REAL              | f64, Real                      |
+//! | OCTET STRING      | OctetString                    |
+//! | SEQUENCE          | struct                         |
+//! | OPTIONAL          | Option\<T\>                    |
+//! | SEQUENCE OF       | Vec\<T\>                       |
+//! | SET               | struct with #[aper(set)]       |
+//! | SET OF            | SetOf\<T\>                     |
+//! | CHOICE            | enum                           |
+//! | ENUMERATED        | C-like enum                    |
+//! | IA5String         | Ia5String                      |
+//! | NumericString     | NumericString                  |
+//! | PrintableString   | PrintableString                |
+//! | VisibleString     | VisibleString                  |
+//! | BMPString         | BmpString                      |
+//! | UniversalString   | UniversalString                |
+//! | UTF8String        | Utf8String                     |
+//! | GraphicString     | GraphicString                  |
+//! | GeneralString     | GeneralString                  |
+//! | TeletexString     | TeletexString                  |
+//! | OBJECT IDENTIFIER | ObjectIdentifier               |
+//! | RELATIVE-OID      | RelativeOid                    |
+//! | UTCTime           | UtcTime                        |
+//! | GeneralizedTime   | GeneralizedTime                |
+//! | DATE              | Date                           |
+//! | TIME-OF-DAY       | TimeOfDay                      |
+//! | DATE-TIME         | DateTime                       |
+//! | DURATION          | Duration                       |
+//! | open type         | OpenType\<T\>                  |
 //!
 //! *`INTEGER` fields of arbitrary widths (in PER encodings) can be decoded/encoded as long as they fit in an `i64`
 //! (see [aper::Decoder::decode_int](aper/struct.Decoder.html#method.decode_int) and
-//! [aper::encode_int](aper/fn.encode_int.html)).
+//! [aper::encode_int](aper/fn.encode_int.html)). Unconstrained and semi-constrained `INTEGER`s of any size can be
+//! decoded/encoded as a [BigInt](struct.BigInt.html). Without a value constraint, the 64 and 128-bit primitives are
+//! unconstrained (signed) or semi-constrained with a lower bound of 0 (unsigned).
+//!
+//! Each of the types above, including those that derive `APerElement` with `asn1_derive`, implements both
+//! [aper::APerElement](aper/trait.APerElement.html) and [uper::UPerElement](uper/trait.UPerElement.html), which share
+//! the same `Constraints` model.
 #![feature(associated_consts)]
 extern crate byteorder;
 
 /// Tools for encoding and decoding ASN.1 messages of the Aligned PER flavor.
 pub mod aper;
+/// Tools for encoding and decoding ASN.1 messages of the Unaligned PER flavor.
+pub mod uper;
 
+mod big_int;
 mod bit_string;
+mod char_string;
 mod integer;
 mod sequence_of;
 mod sequence;
 mod bool;
+mod enumerated;
 mod extensions;
+mod named_bits;
 mod null;
+mod object_identifier;
+mod octet_string;
+mod open_type;
+mod real;
+mod time;
 mod utils;
 
-pub use bit_string::BitString;
+pub use big_int::BigInt;
+pub use bit_string::{BitIter, BitString, ParseBitStringError};
 pub use bool::*;
+pub use char_string::{BmpString, GeneralString, GraphicString, Ia5String, NumericString, PrintableString, TeletexString,
+                      UniversalString, Utf8String, VisibleString};
+pub use enumerated::{Enumerated, EnumeratedValue};
 pub use extensions::*;
 pub use integer::*;
+pub use named_bits::{NamedBit, NamedBits, NamedBitsIter};
 pub use null::*;
+pub use object_identifier::{ObjectIdentifier, ParseOidError, RelativeOid};
+pub use octet_string::OctetString;
+pub use open_type::{OpenType, RawOpenType};
+pub use real::Real;
+pub use time::{Date, DateTime, Duration, GeneralizedTime, ParseTimeError, TimeOfDay, UtcTime};
 pub use sequence::*;
 pub use sequence_of::*;
@@ -0,0 +1,196 @@
+use aper::{APerElement, Constraints, Decoder, DecodeError, Encoder, Encoding, EncodeError, UNCONSTRAINED};
+use bit_string::{BitIter, BitString};
+use std::{fmt, iter};
+use std::marker::PhantomData;
+use utils::size_bounds;
+
+/// Trait for C-like enums whose items are the named bits of an ASN.1 BIT STRING, such as
+/// `BIT STRING { ciphering (0), integrity (1) }`.
+///
+/// The value of each item is the number of its bit. `NamedBit` can be derived with the `asn1-derive` crate, which uses
+/// the discriminants of the enum as bit numbers.
+pub trait NamedBit: Sized {
+    /// Get the item for bit `i`, if it's named.
+    fn from_bit(i: usize) -> Option<Self>;
+
+    /// Get the number of the item's bit.
+    fn bit(&self) -> usize;
+}
+
+/// A BIT STRING with named bits, which acts as a set of the `NamedBit`s `T`.
+///
+/// Trailing 0 bits don't change the value of a BIT STRING with named bits, so a `NamedBits<T>` never has any. When
+/// encoding, 0 bits are added back as far as the lower bound of the size constraint, if there is one. Bits that `T`
+/// doesn't name are kept, so that a decoded value holding them is encoded as it was received.
+///
+/// # Examples
+///
+/// ```
+/// SecurityAlgorithms ::= BIT STRING { ciphering (0), integrity (1), ... (5) } (SIZE (8))
+/// ```
+///
+/// ```
+/// #[macro_use]
+/// extern crate asn1_derive;
+/// extern crate asn1;
+/// use asn1::{NamedBit, NamedBits};
+/// use asn1::aper::{APerElement, Constraint, Constraints};
+///
+/// #[derive(NamedBit, Debug, Clone, Copy, PartialEq)]
+/// enum Algorithm {
+///     Ciphering = 0,
+///     Integrity = 1,
+///     Nulling = 5,
+/// }
+///
+/// let constraints = Constraints {
+///     value: None,
+///     size: Some(Constraint::new(Some(8), Some(8))),
+/// };
+/// let mut x = NamedBits::new();
+/// x.insert(Algorithm::Integrity);
+/// println!("{}", x); // Prints '01'B
+/// println!("{:?}", x.to_aper(constraints).unwrap().bytes()); // Prints [64]
+///
+/// let y: NamedBits<Algorithm> = vec![Algorithm::Ciphering, Algorithm::Nulling].into_iter().collect();
+/// println!("{:?}", y.iter().collect::<Vec<_>>()); // Prints [Ciphering, Nulling]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NamedBits<T> {
+    bits: BitString,
+    names: PhantomData<T>,
+}
+
+/// An iterator over the named bits that are set in a `NamedBits<T>`, created by
+/// [NamedBits::iter()](struct.NamedBits.html#method.iter).
+pub type NamedBitsIter<'a, T> = iter::FilterMap<iter::Enumerate<BitIter<'a>>, fn((usize, bool)) -> Option<T>>;
+
+impl<T: NamedBit> NamedBits<T> {
+    /// Construct a `NamedBits<T>` with no bits set.
+    pub fn new() -> NamedBits<T> {
+        NamedBits::from(BitString::new())
+    }
+
+    /// Check if `bit` is set.
+    pub fn contains(&self, bit: T) -> bool {
+        self.bits.is_set(bit.bit())
+    }
+
+    /// Set `bit`.
+    pub fn insert(&mut self, bit: T) {
+        self.set(bit, true);
+    }
+
+    /// Clear `bit`.
+    pub fn remove(&mut self, bit: T) {
+        self.set(bit, false);
+    }
+
+    /// Set `bit` to `val`.
+    pub fn set(&mut self, bit: T, val: bool) {
+        let i = bit.bit();
+        if i >= self.bits.len() {
+            if !val {
+                return;
+            }
+            self.bits.set_num_bits(i + 1);
+        }
+        self.bits.set(i, val);
+        self.bits.trim_trailing_zeros();
+    }
+
+    /// Check if no bits are set.
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    /// Iterate over the named bits that are set, in order of their numbers.
+    pub fn iter(&self) -> NamedBitsIter<'_, T> {
+        fn named<T: NamedBit>((i, set): (usize, bool)) -> Option<T> {
+            if set { T::from_bit(i) } else { None }
+        }
+        self.bits.iter().enumerate().filter_map(named::<T> as fn((usize, bool)) -> Option<T>)
+    }
+
+    /// Get the bits, without any trailing 0 bits.
+    pub fn as_bit_string(&self) -> &BitString {
+        &self.bits
+    }
+
+    /// Get the bits as they're encoded under `constraints`, i.e. with 0 bits added as far as the lower bound of the
+    /// size constraint.
+    pub fn to_bit_string(&self, constraints: Constraints) -> BitString {
+        let (lb, _, _) = size_bounds(constraints.size);
+        let mut bits = self.bits.clone();
+        if bits.len() < lb {
+            bits.set_num_bits(lb);
+        }
+        bits
+    }
+}
+
+impl<T: NamedBit> Default for NamedBits<T> {
+    fn default() -> NamedBits<T> {
+        NamedBits::new()
+    }
+}
+
+impl<T> From<BitString> for NamedBits<T> {
+    fn from(mut bits: BitString) -> NamedBits<T> {
+        bits.trim_trailing_zeros();
+        NamedBits {
+            bits,
+            names: PhantomData,
+        }
+    }
+}
+
+impl<T> From<NamedBits<T>> for BitString {
+    fn from(bits: NamedBits<T>) -> BitString {
+        bits.bits
+    }
+}
+
+impl<T: NamedBit> iter::FromIterator<T> for NamedBits<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(bits: I) -> NamedBits<T> {
+        let mut ret = NamedBits::new();
+        ret.extend(bits);
+        ret
+    }
+}
+
+impl<T: NamedBit> iter::Extend<T> for NamedBits<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, bits: I) {
+        for b in bits {
+            self.insert(b);
+        }
+    }
+}
+
+impl<T> fmt::Display for NamedBits<T> {
+    /// Format the bits in `bstring` notation, such as `'0101'B`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.bits, f)
+    }
+}
+
+impl<T: NamedBit> APerElement for NamedBits<T> {
+    const CONSTRAINTS: Constraints = UNCONSTRAINED;
+
+    /// Read a `NamedBits<T>` from an aligned PER encoding.
+    fn from_aper(decoder: &mut Decoder, constraints: Constraints) -> Result<Self, DecodeError> {
+        let ret = BitString::from_aper(decoder, constraints);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(NamedBits::from(ret.unwrap()))
+    }
+
+    fn to_aper(&self, constraints: Constraints) -> Result<Encoding, EncodeError> {
+        Encoder::encode(|enc| self.write_aper(enc, constraints))
+    }
+
+    fn write_aper(&self, encoder: &mut Encoder, constraints: Constraints) -> Result<(), EncodeError> {
+        self.to_bit_string(constraints).write_aper(encoder, constraints)
+    }
+}
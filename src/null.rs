@@ -1,4 +1,4 @@
-use aper::{APerElement, Constraints, Decoder, DecodeError, Encoding, EncodeError};
+use aper::{APerElement, Constraints, Decoder, DecodeError, Encoder, Encoding, EncodeError};
 
 impl APerElement for () {
     const CONSTRAINTS: Constraints = Constraints {
@@ -14,4 +14,8 @@ impl APerElement for () {
     fn to_aper(&self, _: Constraints) -> Result<Encoding, EncodeError> {
         Ok(Encoding::new())
     }
+
+    fn write_aper(&self, _: &mut Encoder, _: Constraints) -> Result<(), EncodeError> {
+        Ok(())
+    }
 }
@@ -0,0 +1,279 @@
+use aper::{APerElement, Constraints, Decoder, DecodeError, Encoder, Encoding, EncodeError, UNCONSTRAINED};
+use std::fmt;
+use std::str::FromStr;
+use utils::{decode_contents, encode_contents};
+
+/// An OBJECT IDENTIFIER, which is a sequence of at least 2 arcs.
+///
+/// As in X.660, the first arc is 0, 1 or 2, and the second arc is at most 39 unless the first is 2. An
+/// `ObjectIdentifier` is encoded as the contents octets of its X.690 encoding, preceded by an unconstrained length
+/// determinant. Arcs beyond the range of a `u64` can't be held, so decoding or parsing one fails.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::ObjectIdentifier;
+/// use asn1::aper::{APerElement, UNCONSTRAINED};
+///
+/// let oid: ObjectIdentifier = "1.2.840.113549".parse().unwrap();
+/// println!("{:?}", oid.arcs()); // Prints [1, 2, 840, 113549]
+/// println!("{:?}", oid.to_aper(UNCONSTRAINED).unwrap().bytes()); // Prints [6, 42, 134, 72, 134, 247, 13]
+/// println!("{}", ObjectIdentifier::new(&[0, 0, 8, 2250]).unwrap()); // Prints 0.0.8.2250
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ObjectIdentifier {
+    arcs: Vec<u64>,
+}
+
+/// A RELATIVE-OID, which is a sequence of arcs relative to some OBJECT IDENTIFIER.
+///
+/// A `RelativeOid` is encoded like an [ObjectIdentifier](struct.ObjectIdentifier.html), except that each arc has its
+/// own subidentifier.
+#[derive(Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RelativeOid {
+    arcs: Vec<u64>,
+}
+
+/// An error from parsing an `ObjectIdentifier` or `RelativeOid` in dotted notation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOidError;
+
+impl ObjectIdentifier {
+    /// Construct an `ObjectIdentifier` from its arcs, or `None` if they aren't a valid OBJECT IDENTIFIER.
+    pub fn new(arcs: &[u64]) -> Option<ObjectIdentifier> {
+        if arcs.len() < 2 || arcs[0] > 2 || (arcs[0] < 2 && arcs[1] > 39) {
+            return None;
+        }
+        Some(ObjectIdentifier { arcs: arcs.to_vec() })
+    }
+
+    /// Get the arcs.
+    pub fn arcs(&self) -> &[u64] {
+        &self.arcs
+    }
+
+    /// Get the `ObjectIdentifier` of `relative` under this one.
+    pub fn join(&self, relative: &RelativeOid) -> ObjectIdentifier {
+        let mut arcs = self.arcs.clone();
+        arcs.extend_from_slice(&relative.arcs);
+        ObjectIdentifier { arcs }
+    }
+
+    /// Get the contents octets of the X.690 encoding, in which the first two arcs share a subidentifier.
+    pub fn to_contents(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::new();
+        write_subidentifier(&mut bytes, self.arcs[0] as u128 * 40 + self.arcs[1] as u128);
+        for &arc in &self.arcs[2..] {
+            write_subidentifier(&mut bytes, arc as u128);
+        }
+        bytes
+    }
+
+    /// Construct an `ObjectIdentifier` from the contents octets of its X.690 encoding, or `None` if they're malformed
+    /// or hold an arc beyond the range of a `u64`.
+    pub fn from_contents(bytes: &[u8]) -> Option<ObjectIdentifier> {
+        let subids = read_subidentifiers(bytes)?;
+        if subids.is_empty() {
+            return None;
+        }
+
+        let first = subids[0];
+        let (a, b) = if first < 40 {
+            (0, first)
+        } else if first < 80 {
+            (1, first - 40)
+        } else {
+            (2, first - 80)
+        };
+        if b > u64::MAX as u128 {
+            return None;
+        }
+
+        let mut arcs = vec![a, b as u64];
+        for &subid in &subids[1..] {
+            if subid > u64::MAX as u128 {
+                return None;
+            }
+            arcs.push(subid as u64);
+        }
+        Some(ObjectIdentifier { arcs })
+    }
+}
+
+impl RelativeOid {
+    /// Construct a `RelativeOid` from its arcs.
+    pub fn new(arcs: &[u64]) -> RelativeOid {
+        RelativeOid { arcs: arcs.to_vec() }
+    }
+
+    /// Get the arcs.
+    pub fn arcs(&self) -> &[u64] {
+        &self.arcs
+    }
+
+    /// Get the contents octets of the X.690 encoding.
+    pub fn to_contents(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::new();
+        for &arc in &self.arcs {
+            write_subidentifier(&mut bytes, arc as u128);
+        }
+        bytes
+    }
+
+    /// Construct a `RelativeOid` from the contents octets of its X.690 encoding, or `None` if they're malformed or hold
+    /// an arc beyond the range of a `u64`.
+    pub fn from_contents(bytes: &[u8]) -> Option<RelativeOid> {
+        let mut arcs: Vec<u64> = Vec::new();
+        for subid in read_subidentifiers(bytes)? {
+            if subid > u64::MAX as u128 {
+                return None;
+            }
+            arcs.push(subid as u64);
+        }
+        Some(RelativeOid { arcs })
+    }
+}
+
+/// Append `v` to `bytes` as a subidentifier, i.e. in base 128 with the MSB set on all but the last octet.
+fn write_subidentifier(bytes: &mut Vec<u8>, v: u128) {
+    let mut n = 1;
+    while n < 19 && v >> (7 * n) > 0 {
+        n += 1;
+    }
+    for i in (0..n).rev() {
+        let more = if i > 0 { 0x80 } else { 0 };
+        bytes.push(((v >> (7 * i)) & 0x7f) as u8 | more);
+    }
+}
+
+/// Split `bytes` into subidentifiers, or return `None` if one isn't minimally encoded, runs past the end, or is beyond
+/// the range of a `u64` plus the 80 that the first subidentifier of an OBJECT IDENTIFIER can add.
+fn read_subidentifiers(bytes: &[u8]) -> Option<Vec<u128>> {
+    let mut subids: Vec<u128> = Vec::new();
+    let mut v: u128 = 0;
+    let mut start = true;
+    for &b in bytes {
+        if start && b == 0x80 {
+            return None;
+        }
+        v = (v << 7) | (b & 0x7f) as u128;
+        if v > u64::MAX as u128 + 80 {
+            return None;
+        }
+        start = b & 0x80 == 0;
+        if start {
+            subids.push(v);
+            v = 0;
+        }
+    }
+    if !start {
+        return None;
+    }
+    Some(subids)
+}
+
+/// Parse dotted notation such as `1.2.840` into arcs.
+fn parse_arcs(s: &str) -> Result<Vec<u64>, ParseOidError> {
+    let mut arcs: Vec<u64> = Vec::new();
+    for arc in s.split('.') {
+        if arc.is_empty() || !arc.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ParseOidError);
+        }
+        match arc.parse() {
+            Ok(v) => arcs.push(v),
+            Err(_) => return Err(ParseOidError),
+        }
+    }
+    Ok(arcs)
+}
+
+/// Format arcs in dotted notation.
+fn fmt_arcs(arcs: &[u64], f: &mut fmt::Formatter) -> fmt::Result {
+    for (i, arc) in arcs.iter().enumerate() {
+        if i > 0 {
+            f.write_str(".")?;
+        }
+        write!(f, "{}", arc)?;
+    }
+    Ok(())
+}
+
+impl FromStr for ObjectIdentifier {
+    type Err = ParseOidError;
+
+    /// Parse an `ObjectIdentifier` in dotted notation, such as `1.2.840`.
+    fn from_str(s: &str) -> Result<ObjectIdentifier, ParseOidError> {
+        let ret = parse_arcs(s);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        ObjectIdentifier::new(&ret.unwrap()).ok_or(ParseOidError)
+    }
+}
+
+impl FromStr for RelativeOid {
+    type Err = ParseOidError;
+
+    /// Parse a `RelativeOid` in dotted notation, such as `8.2250`.
+    fn from_str(s: &str) -> Result<RelativeOid, ParseOidError> {
+        let ret = parse_arcs(s);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(RelativeOid { arcs: ret.unwrap() })
+    }
+}
+
+impl fmt::Display for ObjectIdentifier {
+    /// Format the arcs in dotted notation, such as `1.2.840`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_arcs(&self.arcs, f)
+    }
+}
+
+impl fmt::Display for RelativeOid {
+    /// Format the arcs in dotted notation, such as `8.2250`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_arcs(&self.arcs, f)
+    }
+}
+
+impl fmt::Debug for ObjectIdentifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ObjectIdentifier({})", self)
+    }
+}
+
+impl fmt::Debug for RelativeOid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RelativeOid({})", self)
+    }
+}
+
+macro_rules! oid_impl {
+    ($t:ident) => {
+        impl APerElement for $t {
+            const CONSTRAINTS: Constraints = UNCONSTRAINED;
+
+            fn from_aper(decoder: &mut Decoder, _: Constraints) -> Result<Self, DecodeError> {
+                let ret = decode_contents(decoder);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                $t::from_contents(&ret.unwrap()).ok_or(DecodeError::MalformedObjectIdentifier)
+            }
+
+            fn to_aper(&self, constraints: Constraints) -> Result<Encoding, EncodeError> {
+                Encoder::encode(|enc| self.write_aper(enc, constraints))
+            }
+
+            fn write_aper(&self, encoder: &mut Encoder, _: Constraints) -> Result<(), EncodeError> {
+                encode_contents(encoder, &self.to_contents())
+            }
+        }
+    }
+}
+
+oid_impl!(ObjectIdentifier);
+oid_impl!(RelativeOid);
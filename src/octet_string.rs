@@ -0,0 +1,87 @@
+use aper::{APerElement, Constraints, Decoder, DecodeError, Encoder, Encoding, EncodeError, UNCONSTRAINED};
+use utils::size_bounds;
+
+/// An octet string.
+///
+/// Unlike a `Vec<u8>`, which is a SEQUENCE OF `INTEGER (0..255)`, the contents of an `OctetString` are encoded as a
+/// single run of octets. Strings with a fixed size of up to two octets aren't octet-aligned, and any other non-empty
+/// strings are. A missing size constraint, or one without an upper bound below 64K, is treated as unbounded and the
+/// contents are preceded by a length determinant which may be fragmented. If the size constraint is extensible, strings
+/// of a size outside of it are encoded as if it were unbounded.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::OctetString;
+/// use asn1::aper::{self, APerElement, Constraint, Constraints};
+///
+/// let constraints = Constraints {
+///     value: None,
+///     size: Some(Constraint::new(Some(1), Some(8))),
+/// };
+/// let x = OctetString(b"FOO".to_vec());
+/// let enc = x.to_aper(constraints).unwrap();
+/// println!("{:?}", enc.bytes()); // Prints [64, 70, 79, 79]
+///
+/// let mut d = aper::Decoder::new(enc.bytes());
+/// let y = OctetString::from_aper(&mut d, constraints).unwrap();
+/// println!("{:?}", y.0); // Prints [70, 79, 79]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OctetString(pub Vec<u8>);
+
+impl OctetString {
+    /// Check if the contents are octet-aligned under `constraints`, which they are unless the size is fixed at 2 octets
+    /// or fewer.
+    fn is_aligned(constraints: Constraints) -> bool {
+        let (lb, ub, _) = size_bounds(constraints.size);
+        ub.is_none_or(|ub| ub != lb || ub > 2)
+    }
+
+    fn decode(decoder: &mut Decoder, constraints: Constraints) -> Result<Self, DecodeError> {
+        let aligned = Self::is_aligned(constraints);
+        let mut content: Vec<u8> = Vec::new();
+        let ret = decoder.decode_sized(constraints.size, |decoder, n| {
+            if aligned {
+                let ret = decoder.align();
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+            }
+            decoder.read_to_vec(&mut content, n * 8)
+        });
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(OctetString(content))
+    }
+
+    fn encode(&self, encoder: &mut Encoder, constraints: Constraints) -> Result<(), EncodeError> {
+        let aligned = Self::is_aligned(constraints);
+        encoder.encode_sized(self.0.len(), constraints.size, |enc, start, n| {
+            if aligned {
+                enc.align();
+            }
+            enc.write_bytes(&self.0[start..start + n]);
+            Ok(())
+        })
+    }
+}
+
+impl APerElement for OctetString {
+    const CONSTRAINTS: Constraints = UNCONSTRAINED;
+
+    /// Read an `OctetString` from an aligned PER encoding.
+    fn from_aper(decoder: &mut Decoder, constraints: Constraints) -> Result<Self, DecodeError> {
+        Self::decode(decoder, constraints)
+    }
+
+    fn to_aper(&self, constraints: Constraints) -> Result<Encoding, EncodeError> {
+        Encoder::encode(|enc| self.write_aper(enc, constraints))
+    }
+
+    fn write_aper(&self, encoder: &mut Encoder, constraints: Constraints) -> Result<(), EncodeError> {
+        self.encode(encoder, constraints)
+    }
+}
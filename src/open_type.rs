@@ -0,0 +1,133 @@
+use aper::{APerElement, Constraints, Decoder, DecodeError, Encoder, Encoding, EncodeError, UNCONSTRAINED};
+use uper::UPerElement;
+
+/// An open type holding a value of type `T`.
+///
+/// An open type is a complete encoding of its value, padded to a whole number of octets, preceded by its length in
+/// octets. An empty encoding is replaced by a single zero octet. Constraints given to an `OpenType<T>` apply to its
+/// value.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::OpenType;
+/// use asn1::aper::{self, APerElement, UNCONSTRAINED};
+///
+/// let x = OpenType(43i16);
+/// let enc = x.to_aper(UNCONSTRAINED).unwrap();
+/// println!("{:?}", enc.bytes()); // Prints [2, 128, 43]
+///
+/// let mut d = aper::Decoder::new(enc.bytes());
+/// let y = OpenType::<i16>::from_aper(&mut d, UNCONSTRAINED).unwrap();
+/// println!("{}", y.0); // Prints 43
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenType<T>(pub T);
+
+impl<T: APerElement> APerElement for OpenType<T> {
+    const CONSTRAINTS: Constraints = T::CONSTRAINTS;
+
+    /// Read an `OpenType<T>` from an aligned PER encoding.
+    fn from_aper(decoder: &mut Decoder, constraints: Constraints) -> Result<Self, DecodeError> {
+        let ret = decoder.decode_open_type();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let content = ret.unwrap();
+
+        let ret = T::from_aper(&mut decoder.nested(&content), constraints);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(OpenType(ret.unwrap()))
+    }
+
+    fn to_aper(&self, constraints: Constraints) -> Result<Encoding, EncodeError> {
+        Encoder::encode(|enc| self.write_aper(enc, constraints))
+    }
+
+    fn write_aper(&self, encoder: &mut Encoder, constraints: Constraints) -> Result<(), EncodeError> {
+        encoder.encode_open_type(|enc| self.0.write_aper(enc, constraints))
+    }
+}
+
+/// An open type whose value hasn't been decoded, holding the contents octets of the open type.
+///
+/// This is useful when the type of the value isn't known until later, e.g. the value of a protocol IE whose type is
+/// given by its ID, or when the ID isn't known to the decoder at all.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::RawOpenType;
+/// use asn1::aper::{self, APerElement, UNCONSTRAINED};
+///
+/// let data = b"\x02\x80\x2b";
+/// let mut d = aper::Decoder::new(data);
+/// let x = RawOpenType::from_aper(&mut d, UNCONSTRAINED).unwrap();
+/// println!("{:?}", x.0); // Prints [128, 43]
+/// println!("{}", x.decode::<i16>(UNCONSTRAINED).unwrap()); // Prints 43
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawOpenType(pub Vec<u8>);
+
+impl RawOpenType {
+    /// Construct a `RawOpenType` holding the aligned PER encoding of `value`.
+    pub fn encode<T: APerElement>(value: &T, constraints: Constraints) -> Result<RawOpenType, EncodeError> {
+        let ret = value.to_aper(constraints);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let enc = ret.unwrap();
+        if enc.bytes().is_empty() {
+            return Ok(RawOpenType(vec![0]));
+        }
+        Ok(RawOpenType(enc.bytes().clone()))
+    }
+
+    /// Construct a `RawOpenType` holding the unaligned PER encoding of `value`.
+    pub fn encode_unaligned<T: UPerElement>(value: &T, constraints: Constraints) -> Result<RawOpenType, EncodeError> {
+        let ret = value.to_uper(constraints);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let enc = ret.unwrap();
+        if enc.bytes().is_empty() {
+            return Ok(RawOpenType(vec![0]));
+        }
+        Ok(RawOpenType(enc.bytes().clone()))
+    }
+
+    /// Decode the value held in the open type from its aligned PER encoding.
+    pub fn decode<T: APerElement>(&self, constraints: Constraints) -> Result<T, DecodeError> {
+        T::from_aper(&mut Decoder::new(&self.0), constraints)
+    }
+
+    /// Decode the value held in the open type from its unaligned PER encoding.
+    pub fn decode_unaligned<T: UPerElement>(&self, constraints: Constraints) -> Result<T, DecodeError> {
+        T::from_uper(&mut Decoder::new_unaligned(&self.0), constraints)
+    }
+}
+
+impl APerElement for RawOpenType {
+    const CONSTRAINTS: Constraints = UNCONSTRAINED;
+
+    /// Read a `RawOpenType` from an aligned PER encoding.
+    fn from_aper(decoder: &mut Decoder, _: Constraints) -> Result<Self, DecodeError> {
+        let ret = decoder.decode_open_type();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(RawOpenType(ret.unwrap()))
+    }
+
+    fn to_aper(&self, constraints: Constraints) -> Result<Encoding, EncodeError> {
+        Encoder::encode(|enc| self.write_aper(enc, constraints))
+    }
+
+    fn write_aper(&self, encoder: &mut Encoder, _: Constraints) -> Result<(), EncodeError> {
+        encoder.encode_open_type_bytes(&self.0)
+    }
+}
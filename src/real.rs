@@ -0,0 +1,451 @@
+use aper::{APerElement, Constraints, Decoder, DecodeError, Encoder, Encoding, EncodeError, RealComponents,
+           UNCONSTRAINED};
+use std::fmt;
+use utils::{decode_contents, encode_contents, int_octets, uint_octets};
+
+/// A REAL, which is either `mantissa × 2^exponent`, `mantissa × 10^exponent` or a special value.
+///
+/// Unlike an `f64`, a `Real` keeps the base it's written in, so a decimal value such as 0.1 is held exactly. Values
+/// made with [binary()](#method.binary) or [decimal()](#method.decimal), or decoded, are normalized so that the
+/// mantissa isn't a multiple of the base, and zero is always `Binary { mantissa: 0, exponent: 0 }`.
+///
+/// A `Real` is encoded as the contents octets of its CER/DER encoding, preceded by an unconstrained length determinant.
+/// Binary values use base 2 with an odd mantissa, and decimal values use the NR3 form, such as `15.E-1`. If a value
+/// constraint limits the components of a REAL, a `Real` must use the permitted base and be within the limits.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::Real;
+/// use asn1::aper::{APerElement, UNCONSTRAINED};
+///
+/// let x = Real::decimal(150, -2);
+/// println!("{:?}", x); // Prints Decimal { mantissa: 15, exponent: -1 }
+/// println!("{}", x); // Prints { mantissa 15, base 10, exponent -1 }
+/// println!("{:?}", x.to_aper(UNCONSTRAINED).unwrap().bytes()); // Prints [7, 3, 49, 53, 46, 69, 45, 49]
+/// println!("{:?}", 0.5f64.to_aper(UNCONSTRAINED).unwrap().bytes()); // Prints [3, 128, 255, 1]
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Real {
+    /// `mantissa × 2^exponent`.
+    Binary { mantissa: i64, exponent: i64 },
+    /// `mantissa × 10^exponent`.
+    Decimal { mantissa: i64, exponent: i64 },
+    PlusInfinity,
+    MinusInfinity,
+    NotANumber,
+    MinusZero,
+}
+
+impl Real {
+    /// Construct the binary `Real` `mantissa × 2^exponent`.
+    pub fn binary(mantissa: i64, exponent: i64) -> Real {
+        let (mantissa, exponent) = normalize(mantissa, exponent, 2);
+        Real::Binary { mantissa, exponent }
+    }
+
+    /// Construct the decimal `Real` `mantissa × 10^exponent`, which is binary if it's zero.
+    pub fn decimal(mantissa: i64, exponent: i64) -> Real {
+        if mantissa == 0 {
+            return Real::binary(0, 0);
+        }
+        let (mantissa, exponent) = normalize(mantissa, exponent, 10);
+        Real::Decimal { mantissa, exponent }
+    }
+
+    /// Get the nearest `f64`.
+    pub fn to_f64(&self) -> f64 {
+        match *self {
+            Real::Binary { mantissa, exponent } => scale(mantissa as f64, exponent),
+            Real::Decimal { mantissa, exponent } => format!("{}e{}", mantissa, exponent).parse().unwrap(),
+            Real::PlusInfinity => f64::INFINITY,
+            Real::MinusInfinity => f64::NEG_INFINITY,
+            Real::NotANumber => f64::NAN,
+            Real::MinusZero => -0.0,
+        }
+    }
+
+    /// Get the contents octets of the CER/DER encoding.
+    pub fn to_contents(&self) -> Vec<u8> {
+        match self.normalized() {
+            Real::Binary { mantissa: 0, .. } => Vec::new(),
+            Real::Binary { mantissa, exponent } => {
+                let mut bytes: Vec<u8> = vec![if mantissa < 0 { 0xc0 } else { 0x80 }];
+                let exp_len = int_octets(exponent);
+                if exp_len <= 3 {
+                    bytes[0] |= exp_len as u8 - 1;
+                } else {
+                    bytes[0] |= 0x03;
+                    bytes.push(exp_len as u8);
+                }
+                for i in (0..exp_len).rev() {
+                    bytes.push((exponent >> (8 * i)) as u8);
+                }
+                let n = mantissa.unsigned_abs();
+                for i in (0..uint_octets(n)).rev() {
+                    bytes.push((n >> (8 * i)) as u8);
+                }
+                bytes
+            }
+            Real::Decimal { mantissa, exponent } => {
+                // NR3 form, in which a zero exponent has a sign and no other does
+                let exponent = if exponent == 0 { "+0".to_string() } else { exponent.to_string() };
+                let mut bytes: Vec<u8> = vec![0x03];
+                bytes.extend(format!("{}.E{}", mantissa, exponent).bytes());
+                bytes
+            }
+            Real::PlusInfinity => vec![0x40],
+            Real::MinusInfinity => vec![0x41],
+            Real::NotANumber => vec![0x42],
+            Real::MinusZero => vec![0x43],
+        }
+    }
+
+    /// Construct a `Real` from the contents octets of its X.690 encoding, or `None` if they're malformed or hold a
+    /// mantissa or exponent that doesn't fit in an `i64`. Any of the forms of BER are accepted.
+    pub fn from_contents(bytes: &[u8]) -> Option<Real> {
+        if bytes.is_empty() {
+            return Some(Real::binary(0, 0));
+        }
+        let first = bytes[0];
+        if first & 0x80 != 0 {
+            return decode_binary(bytes);
+        }
+        if first & 0x40 != 0 {
+            if bytes.len() != 1 {
+                return None;
+            }
+            return match first {
+                0x40 => Some(Real::PlusInfinity),
+                0x41 => Some(Real::MinusInfinity),
+                0x42 => Some(Real::NotANumber),
+                0x43 => Some(Real::MinusZero),
+                _ => None,
+            };
+        }
+        if !(0x01..=0x03).contains(&first) {
+            return None;
+        }
+        let (mantissa, exponent) = parse_decimal(&bytes[1..])?;
+        Some(Real::decimal(mantissa, exponent))
+    }
+
+    /// Get the `Real` with its mantissa and exponent normalized.
+    fn normalized(&self) -> Real {
+        match *self {
+            Real::Binary { mantissa, exponent } => Real::binary(mantissa, exponent),
+            Real::Decimal { mantissa, exponent } => Real::decimal(mantissa, exponent),
+            x => x,
+        }
+    }
+
+    /// Check if the `Real` is permitted by the limits on its components.
+    fn is_permitted(&self, components: RealComponents) -> bool {
+        let (mantissa, exponent, base) = match self.normalized() {
+            Real::Binary { mantissa: 0, .. } => return in_range(0, components.mantissa),
+            Real::Binary { mantissa, exponent } => (mantissa, exponent, 2),
+            Real::Decimal { mantissa, exponent } => (mantissa, exponent, 10),
+            _ => return true,
+        };
+        if components.base.is_some_and(|b| b != base) {
+            return false;
+        }
+
+        // The value can also be written with the mantissa scaled up by base^k and the exponent down by k, which may
+        // bring the exponent within its limits. Start from the smallest such k.
+        let (e_min, e_max) = components.exponent;
+        let mut k = e_max.map_or(0, |max| (exponent as i128 - max as i128).max(0));
+        loop {
+            if e_min.is_some_and(|min| (exponent as i128 - k) < min as i128) {
+                return false;
+            }
+            let power = (base as i128).checked_pow(k.min(128) as u32);
+            let scaled = match power.and_then(|p| p.checked_mul(mantissa as i128)) {
+                Some(v) => v,
+                None => return false,
+            };
+            if in_range(scaled, components.mantissa) {
+                return true;
+            }
+            // Scaling up only moves further from the limits once past them
+            let (m_min, m_max) = components.mantissa;
+            if (scaled > 0 && m_max.is_some_and(|max| scaled > max as i128)) ||
+               (scaled < 0 && m_min.is_some_and(|min| scaled < min as i128)) {
+                return false;
+            }
+            k += 1;
+        }
+    }
+}
+
+/// Divide `mantissa` by `base` until it's no longer a multiple of it, adding to `exponent` to keep the same value.
+fn normalize(mut mantissa: i64, mut exponent: i64, base: i64) -> (i64, i64) {
+    if mantissa == 0 {
+        return (0, 0);
+    }
+    while mantissa % base == 0 && exponent < i64::MAX {
+        mantissa /= base;
+        exponent += 1;
+    }
+    (mantissa, exponent)
+}
+
+/// Check if `v` is within the inclusive `(min, max)` limits.
+fn in_range(v: i128, (min, max): (Option<i64>, Option<i64>)) -> bool {
+    min.is_none_or(|min| v >= min as i128) && max.is_none_or(|max| v <= max as i128)
+}
+
+/// Multiply `x` by `2^exponent`, in steps small enough that each one is exact until the result over- or underflows.
+fn scale(mut x: f64, mut exponent: i64) -> f64 {
+    while exponent > 1000 && x.is_finite() {
+        x *= 2f64.powi(1000);
+        exponent -= 1000;
+    }
+    while exponent < -1000 && x != 0.0 {
+        x *= 2f64.powi(-1000);
+        exponent += 1000;
+    }
+    x * 2f64.powi(exponent.clamp(-1000, 1000) as i32)
+}
+
+/// Decode the contents of a binary REAL, which is `N × 2^F × B^E` with a base `B` of 2, 8 or 16.
+fn decode_binary(bytes: &[u8]) -> Option<Real> {
+    let first = bytes[0];
+    let log_base = match (first >> 4) & 0x03 {
+        0 => 1,
+        1 => 3,
+        2 => 4,
+        _ => return None,
+    };
+    let (start, exp_len) = match first & 0x03 {
+        3 => (2, *bytes.get(1)? as usize),
+        n => (1, n as usize + 1),
+    };
+    if exp_len == 0 || exp_len > 8 || bytes.len() <= start + exp_len {
+        return None;
+    }
+
+    let exp_bytes = &bytes[start..start + exp_len];
+    let mut exponent: i64 = if exp_bytes[0] & 0x80 != 0 { -1 } else { 0 };
+    for &b in exp_bytes {
+        exponent = (exponent << 8) | b as i64;
+    }
+    let mut exponent = exponent.checked_mul(log_base)?.checked_add(((first >> 2) & 0x03) as i64)?;
+
+    let n_bytes: Vec<u8> = bytes[start + exp_len..].iter().cloned().skip_while(|&b| b == 0).collect();
+    if n_bytes.len() > 16 {
+        return None;
+    }
+    let mut n: u128 = 0;
+    for b in n_bytes {
+        n = (n << 8) | b as u128;
+    }
+    if n == 0 {
+        return Some(Real::binary(0, 0));
+    }
+    let zeros = n.trailing_zeros();
+    n >>= zeros;
+    exponent = exponent.checked_add(zeros as i64)?;
+    if n > i64::MAX as u128 {
+        return None;
+    }
+    let mantissa = if first & 0x40 != 0 { -(n as i64) } else { n as i64 };
+    Some(Real::Binary { mantissa, exponent })
+}
+
+/// Parse a decimal number in any of the ISO 6093 forms NR1 (`12`), NR2 (`1.2`) or NR3 (`12.E-1`) into a mantissa
+/// and exponent, or return `None` if it's malformed or either doesn't fit in an `i64`.
+fn parse_decimal(s: &[u8]) -> Option<(i64, i64)> {
+    let mut i = 0;
+    while i < s.len() && s[i] == b' ' {
+        i += 1;
+    }
+    let negative = i < s.len() && s[i] == b'-';
+    if i < s.len() && (s[i] == b'-' || s[i] == b'+') {
+        i += 1;
+    }
+
+    // Zeros after the last non-zero digit are only counted, so that they can go in the exponent
+    let mut mantissa: u64 = 0;
+    let mut zeros: u32 = 0;
+    let mut exponent: i64 = 0;
+    let mut digits = 0;
+    let mut fraction = false;
+    while i < s.len() {
+        match s[i] {
+            b'0'..=b'9' => {
+                digits += 1;
+                if fraction {
+                    exponent -= 1;
+                }
+                let d = (s[i] - b'0') as u64;
+                if d == 0 {
+                    if mantissa != 0 {
+                        zeros += 1;
+                    }
+                } else {
+                    mantissa = mantissa.checked_mul(10u64.checked_pow(zeros + 1)?)?.checked_add(d)?;
+                    zeros = 0;
+                }
+            }
+            b'.' | b',' if !fraction => fraction = true,
+            _ => break,
+        }
+        i += 1;
+    }
+    if digits == 0 {
+        return None;
+    }
+
+    if i < s.len() {
+        if s[i] != b'E' && s[i] != b'e' {
+            return None;
+        }
+        i += 1;
+        let exp_negative = i < s.len() && s[i] == b'-';
+        if i < s.len() && (s[i] == b'-' || s[i] == b'+') {
+            i += 1;
+        }
+        let start = i;
+        let mut e: i64 = 0;
+        while i < s.len() && s[i].is_ascii_digit() {
+            e = e.checked_mul(10)?.checked_add((s[i] - b'0') as i64)?;
+            i += 1;
+        }
+        if i == start || i != s.len() {
+            return None;
+        }
+        exponent = exponent.checked_add(if exp_negative { -e } else { e })?;
+    }
+
+    if mantissa == 0 {
+        return Some((0, 0));
+    }
+    if mantissa > i64::MAX as u64 {
+        return None;
+    }
+    let mantissa = if negative { -(mantissa as i64) } else { mantissa as i64 };
+    Some((mantissa, exponent.checked_add(zeros as i64)?))
+}
+
+impl From<f64> for Real {
+    /// Get the `Real` equal to `v`, which is binary unless it's a special value.
+    fn from(v: f64) -> Real {
+        if v.is_nan() {
+            return Real::NotANumber;
+        }
+        if v.is_infinite() {
+            return if v > 0.0 { Real::PlusInfinity } else { Real::MinusInfinity };
+        }
+        if v == 0.0 {
+            return if v.is_sign_negative() { Real::MinusZero } else { Real::binary(0, 0) };
+        }
+
+        let bits = v.to_bits();
+        let biased = ((bits >> 52) & 0x7ff) as i64;
+        let fraction = (bits & 0x000f_ffff_ffff_ffff) as i64;
+        // Subnormal values have no implicit leading 1
+        let (mantissa, exponent) = if biased == 0 {
+            (fraction, -1074)
+        } else {
+            (fraction | 1 << 52, biased - 1075)
+        };
+        Real::binary(if v < 0.0 { -mantissa } else { mantissa }, exponent)
+    }
+}
+
+impl From<Real> for f64 {
+    fn from(v: Real) -> f64 {
+        v.to_f64()
+    }
+}
+
+impl fmt::Display for Real {
+    /// Format the `Real` in ASN.1 value notation, such as `{ mantissa 15, base 10, exponent -1 }` or `PLUS-INFINITY`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Real::Binary { mantissa, exponent } => {
+                write!(f, "{{ mantissa {}, base 2, exponent {} }}", mantissa, exponent)
+            }
+            Real::Decimal { mantissa, exponent } => {
+                write!(f, "{{ mantissa {}, base 10, exponent {} }}", mantissa, exponent)
+            }
+            Real::PlusInfinity => f.write_str("PLUS-INFINITY"),
+            Real::MinusInfinity => f.write_str("MINUS-INFINITY"),
+            Real::NotANumber => f.write_str("NOT-A-NUMBER"),
+            Real::MinusZero => f.write_str("-0"),
+        }
+    }
+}
+
+/// Conversion to the `Real` that's encoded for a value.
+trait ToReal {
+    fn to_real(&self, constraints: Constraints) -> Real;
+}
+
+impl ToReal for Real {
+    fn to_real(&self, _: Constraints) -> Real {
+        *self
+    }
+}
+
+impl ToReal for f64 {
+    /// Get the `Real` equal to the `f64`, which is decimal if the base is constrained to 10. A decimal value has the
+    /// fewest digits that convert back to the same `f64`.
+    fn to_real(&self, constraints: Constraints) -> Real {
+        let real = Real::from(*self);
+        let base = constraints.value.and_then(|c| c.real()).and_then(|c| c.base);
+        match real {
+            Real::Binary { mantissa, .. } if mantissa != 0 && base == Some(10) => {
+                let (mantissa, exponent) = parse_decimal(format!("{:e}", self).as_bytes()).unwrap();
+                Real::decimal(mantissa, exponent)
+            }
+            _ => real,
+        }
+    }
+}
+
+fn decode_real(decoder: &mut Decoder) -> Result<Real, DecodeError> {
+    let ret = decode_contents(decoder);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    Real::from_contents(&ret.unwrap()).ok_or(DecodeError::MalformedReal)
+}
+
+fn encode_real(encoder: &mut Encoder, real: Real, constraints: Constraints) -> Result<(), EncodeError> {
+    if let Some(components) = constraints.value.and_then(|c| c.real()) {
+        if !real.is_permitted(components) {
+            return Err(EncodeError::InvalidValue);
+        }
+    }
+    encode_contents(encoder, &real.to_contents())
+}
+
+macro_rules! real_impl {
+    ($t:ident) => {
+        impl APerElement for $t {
+            const CONSTRAINTS: Constraints = UNCONSTRAINED;
+
+            fn from_aper(decoder: &mut Decoder, _: Constraints) -> Result<Self, DecodeError> {
+                let ret = decode_real(decoder);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                Ok($t::from(ret.unwrap()))
+            }
+
+            fn to_aper(&self, constraints: Constraints) -> Result<Encoding, EncodeError> {
+                Encoder::encode(|enc| self.write_aper(enc, constraints))
+            }
+
+            fn write_aper(&self, encoder: &mut Encoder, constraints: Constraints) -> Result<(), EncodeError> {
+                encode_real(encoder, self.to_real(constraints), constraints)
+            }
+        }
+    }
+}
+
+real_impl!(Real);
+real_impl!(f64);
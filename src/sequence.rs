@@ -1,4 +1,202 @@
-use super::BitString;
+use aper::{APerElement, Constraints, Decoder, DecodeError, Encoder, Encoding, EncodeError, UNCONSTRAINED};
 
 /// A preamble that describes the properties of the sequence type it preceeds.
-pub type SequencePreamble = BitString;
+///
+/// The preamble of a SEQUENCE holds a bit for each of its OPTIONAL and DEFAULT components, in the order they're
+/// declared. A bit is set if its component is present in the encoding.
+///
+/// # Examples
+///
+/// Consider a SEQUENCE `Foo` with two OPTIONAL components.
+///
+/// ```
+/// Foo ::= SEQUENCE {
+///     bar INTEGER(0..255) OPTIONAL,
+///     baz BOOLEAN OPTIONAL
+/// }
+/// ```
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::SequencePreamble;
+/// use asn1::aper::{self, APerElement, Constraint, Constraints, UNCONSTRAINED};
+///
+/// struct Foo {
+///     bar: Option<u8>,
+///     baz: Option<bool>,
+/// }
+///
+/// impl APerElement for Foo {
+///     const CONSTRAINTS: Constraints = UNCONSTRAINED;
+///
+///     fn from_aper(decoder: &mut aper::Decoder, _: Constraints) -> Result<Self, aper::DecodeError> {
+///         let preamble = SequencePreamble::decode(decoder, 2)?;
+///         let bar = if preamble.is_present(0) {
+///             Option::<u8>::from_aper(decoder, Constraints {
+///                 value: Some(Constraint::new(Some(0), Some(255))),
+///                 size: None,
+///             })?
+///         } else {
+///             None
+///         };
+///         let baz = if preamble.is_present(1) {
+///             Option::<bool>::from_aper(decoder, UNCONSTRAINED)?
+///         } else {
+///             None
+///         };
+///         Ok(Foo { bar: bar, baz: baz })
+///     }
+///
+///     fn to_aper(&self, _: Constraints) -> Result<aper::Encoding, aper::EncodeError> {
+///         let mut preamble = SequencePreamble::new();
+///         preamble.push_optional(&self.bar);
+///         preamble.push_optional(&self.baz);
+///
+///         let mut enc = preamble.to_aper(UNCONSTRAINED)?;
+///         enc.append(&self.bar.to_aper(Constraints {
+///             value: Some(Constraint::new(Some(0), Some(255))),
+///             size: None,
+///         })?)?;
+///         enc.append(&self.baz.to_aper(UNCONSTRAINED)?)?;
+///         Ok(enc)
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SequencePreamble {
+    bits: Vec<bool>,
+}
+
+impl SequencePreamble {
+    /// Construct an empty `SequencePreamble`.
+    pub fn new() -> SequencePreamble {
+        SequencePreamble { bits: Vec::new() }
+    }
+
+    /// Construct a `SequencePreamble` with `n` bits, all of which are clear.
+    pub fn with_len(n: usize) -> SequencePreamble {
+        SequencePreamble { bits: vec![false; n] }
+    }
+
+    /// Read a `SequencePreamble` with `n` bits, for a SEQUENCE with `n` OPTIONAL and DEFAULT components.
+    pub fn decode(decoder: &mut Decoder, n: usize) -> Result<SequencePreamble, DecodeError> {
+        // Preambles of 64K bits or more have a length determinant, which no sane SEQUENCE needs
+        if n >= 65536 {
+            return Err(DecodeError::NotImplemented);
+        }
+        let mut bits = Vec::with_capacity(n);
+        for _ in 0..n {
+            let ret = decoder.read(1);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            bits.push(ret.unwrap() > 0);
+        }
+        Ok(SequencePreamble { bits })
+    }
+
+    /// Get the number of bits in the preamble.
+    pub fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    /// Check if the preamble has no bits.
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    /// Check if the `i`th OPTIONAL or DEFAULT component is present. Components beyond the end of the preamble are
+    /// absent.
+    pub fn is_present(&self, i: usize) -> bool {
+        self.bits.get(i).cloned().unwrap_or(false)
+    }
+
+    /// Set whether the `i`th OPTIONAL or DEFAULT component is present.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is beyond the end of the preamble.
+    pub fn set_present(&mut self, i: usize, present: bool) {
+        self.bits[i] = present;
+    }
+
+    /// Get the presence of each OPTIONAL and DEFAULT component, in order.
+    pub fn bitmap(&self) -> &[bool] {
+        &self.bits
+    }
+
+    /// Add the bit for the next component.
+    pub fn push(&mut self, present: bool) {
+        self.bits.push(present);
+    }
+
+    /// Add the bit for the next component, which is OPTIONAL.
+    pub fn push_optional<T>(&mut self, value: &Option<T>) {
+        self.bits.push(value.is_some());
+    }
+
+    /// Add the bit for the next component, which is DEFAULT. The component is only encoded if it differs from its
+    /// default value.
+    pub fn push_default<T: PartialEq>(&mut self, value: &T, default: &T) {
+        self.bits.push(*value != *default);
+    }
+}
+
+impl APerElement for SequencePreamble {
+    const CONSTRAINTS: Constraints = UNCONSTRAINED;
+
+    /// Read a `SequencePreamble` from an aligned PER encoding. The number of bits is given by a fixed `size`
+    /// constraint. See [decode()](#method.decode).
+    fn from_aper(decoder: &mut Decoder, constraints: Constraints) -> Result<Self, DecodeError> {
+        match constraints.size {
+            Some(c) if c.max().is_some() && c.min() == c.max() => Self::decode(decoder, c.max().unwrap() as usize),
+            _ => Err(DecodeError::MissingSizeConstraint),
+        }
+    }
+
+    fn to_aper(&self, constraints: Constraints) -> Result<Encoding, EncodeError> {
+        Encoder::encode(|enc| self.write_aper(enc, constraints))
+    }
+
+    fn write_aper(&self, encoder: &mut Encoder, _: Constraints) -> Result<(), EncodeError> {
+        if self.bits.len() >= 65536 {
+            return Err(EncodeError::NotImplemented);
+        }
+        for &b in &self.bits {
+            encoder.write_bits(b as u64, 1);
+        }
+        Ok(())
+    }
+}
+
+/// An OPTIONAL component of a SEQUENCE.
+///
+/// Whether the component is present is given by the [SequencePreamble](struct.SequencePreamble.html), so decoding an
+/// `Option<T>` always reads a `T`. It should only be decoded if its bit in the preamble is set. Encoding `None` yields
+/// an empty encoding.
+impl<T: APerElement> APerElement for Option<T> {
+    const CONSTRAINTS: Constraints = T::CONSTRAINTS;
+
+    /// Read an `Option<T>` that is known to be present from an aligned PER encoding.
+    fn from_aper(decoder: &mut Decoder, constraints: Constraints) -> Result<Self, DecodeError> {
+        let ret = T::from_aper(decoder, constraints);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(Some(ret.unwrap()))
+    }
+
+    fn to_aper(&self, constraints: Constraints) -> Result<Encoding, EncodeError> {
+        match *self {
+            Some(ref x) => x.to_aper(constraints),
+            None => Ok(Encoding::new()),
+        }
+    }
+
+    fn write_aper(&self, encoder: &mut Encoder, constraints: Constraints) -> Result<(), EncodeError> {
+        match *self {
+            Some(ref x) => x.write_aper(encoder, constraints),
+            None => Ok(()),
+        }
+    }
+}
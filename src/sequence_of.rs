@@ -1,75 +1,166 @@
-use aper::{APerElement, Constraints, Decoder, DecodeError, Encoding, EncodeError, encode_length};
+use aper::{APerElement, Constraints, Decoder, DecodeError, Encoder, Encoding, EncodeError};
+use std::cmp::Ordering;
 
-impl<T: APerElement> APerElement for Vec<T> {
-    const CONSTRAINTS: Constraints = Constraints {
+/// Read the elements of a `Vec[T]` using `f` to decode each element.
+fn decode_sequence_of<T, F>(decoder: &mut Decoder, constraints: Constraints, mut f: F) -> Result<Vec<T>, DecodeError>
+    where F: FnMut(&mut Decoder, Constraints) -> Result<T, DecodeError>
+{
+    // XXX: This is terrible, but convenient. Either fix or document thoroughly.
+    let el_constrs = Constraints {
         value: None,
-        size: None,
+        size: constraints.value,
     };
 
-    /// Read a `Vec[T]` from an aligned PER encoding.
-    fn from_aper(decoder: &mut Decoder, constraints: Constraints) -> Result<Self, DecodeError> {
-        if constraints.size.is_none() {
-            return Err(DecodeError::MissingSizeConstraint);
-        }
-        let sz_constr = constraints.size.unwrap();
-
-        let mut min_len: usize = 0;
-        let mut max_len: usize = 0;
-        if sz_constr.min().is_some() {
-            min_len = sz_constr.min().unwrap() as usize;
-        }
-        if sz_constr.max().is_some() {
-            max_len = sz_constr.max().unwrap() as usize;
-        }
-
-        if max_len >= 65535 {
-            return Err(DecodeError::NotImplemented);
-        }
-
-        let len: usize;
-        if max_len == min_len {
-            len = max_len;
-        } else {
-            let ret = decoder.decode_length();
+    let mut content: Vec<T> = Vec::new();
+    let ret = decoder.decode_sized(constraints.size, |decoder, n| {
+        content.reserve(n);
+        for _ in 0..n {
+            let ret = f(decoder, el_constrs);
             if ret.is_err() {
                 return Err(ret.err().unwrap());
             }
-            len = ret.unwrap();
+            content.push(ret.unwrap());
         }
+        Ok(())
+    });
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+
+    Ok(content)
+}
 
-        // XXX: This is terrible, but convenient. Either fix or document thoroughly.
-        let el_constrs = Constraints {
-            value: None,
-            size: constraints.value,
-        };
-        let mut content: Vec<T> = Vec::with_capacity(len);
-        for _ in 0..len {
-            let ret = T::from_aper(decoder, el_constrs);
+/// Write the elements of `v` to `encoder`, using `f` to write each element.
+fn encode_sequence_of<T, F>(encoder: &mut Encoder, v: &[T], constraints: Constraints, mut f: F)
+                            -> Result<(), EncodeError>
+    where F: FnMut(&T, &mut Encoder, Constraints) -> Result<(), EncodeError>
+{
+    let el_constrs = Constraints {
+        value: None,
+        size: constraints.value,
+    };
+    encoder.encode_sized(v.len(), constraints.size, |enc, start, n| {
+        for x in &v[start..start + n] {
+            let ret = f(x, enc, el_constrs);
             if ret.is_err() {
                 return Err(ret.err().unwrap());
             }
-            content.push(ret.unwrap());
         }
+        Ok(())
+    })
+}
+
+impl<T: APerElement> APerElement for Vec<T> {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
 
-        Ok(content)
+    /// Read a `Vec[T]` from an aligned PER encoding.
+    fn from_aper(decoder: &mut Decoder, constraints: Constraints) -> Result<Self, DecodeError> {
+        decode_sequence_of(decoder, constraints, T::from_aper)
     }
 
     fn to_aper(&self, constraints: Constraints) -> Result<Encoding, EncodeError> {
-        let ret = encode_length(self.len());
+        Encoder::encode(|enc| self.write_aper(enc, constraints))
+    }
+
+    fn write_aper(&self, encoder: &mut Encoder, constraints: Constraints) -> Result<(), EncodeError> {
+        encode_sequence_of(encoder, self, constraints, T::write_aper)
+    }
+}
+
+/// A SET OF.
+///
+/// A `SetOf<T>` is encoded just like a `Vec<T>`, i.e. a SEQUENCE OF, with its elements in the order they're held. If
+/// the [Encoder](aper/struct.Encoder.html) produces CANONICAL-PER, the elements are instead written in ascending order
+/// of their own encodings, each compared as an octet string padded with trailing zero octets. So the encoding of a
+/// `SetOf<T>` only depends on which elements it holds, and is suitable for signing or hashing. Decoding keeps the
+/// elements in the order they're read, and doesn't require them to be sorted.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::SetOf;
+/// use asn1::aper::{self, APerElement, Encoder, UNCONSTRAINED};
+///
+/// let x = SetOf(vec![3u8, 1, 2]);
+/// println!("{:?}", x.to_aper(UNCONSTRAINED).unwrap().bytes()); // Prints [3, 3, 1, 2]
+///
+/// let mut enc = Encoder::new();
+/// enc.set_canonical(true);
+/// x.write_aper(&mut enc, UNCONSTRAINED).unwrap();
+/// let enc = enc.into_encoding();
+/// println!("{:?}", enc.bytes()); // Prints [3, 1, 2, 3]
+///
+/// let mut d = aper::Decoder::new(enc.bytes());
+/// let y = SetOf::<u8>::from_aper(&mut d, UNCONSTRAINED).unwrap();
+/// println!("{:?}", y.0); // Prints [1, 2, 3]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SetOf<T>(pub Vec<T>);
+
+impl<T> From<Vec<T>> for SetOf<T> {
+    fn from(v: Vec<T>) -> SetOf<T> {
+        SetOf(v)
+    }
+}
+
+/// Compare two encodings as octet strings, padding the shorter one with trailing zero octets.
+fn cmp_padded(a: &[u8], b: &[u8]) -> Ordering {
+    let n = a.len().min(b.len());
+    match a[..n].cmp(&b[..n]) {
+        Ordering::Equal if a[n..].iter().any(|&x| x != 0) => Ordering::Greater,
+        Ordering::Equal if b[n..].iter().any(|&x| x != 0) => Ordering::Less,
+        ord => ord,
+    }
+}
+
+/// Write the elements of `v` to `encoder` using `f`, sorted by their encodings if `encoder` is canonical.
+fn encode_set_of<T, F>(encoder: &mut Encoder, v: &[T], constraints: Constraints, mut f: F) -> Result<(), EncodeError>
+    where F: FnMut(&T, &mut Encoder, Constraints) -> Result<(), EncodeError>
+{
+    if !encoder.is_canonical() || v.len() < 2 {
+        return encode_sequence_of(encoder, v, constraints, f);
+    }
+
+    let el_constrs = Constraints {
+        value: None,
+        size: constraints.value,
+    };
+    let mut sorted = Vec::with_capacity(v.len());
+    for x in v {
+        let mut enc = encoder.nested();
+        let ret = f(x, &mut enc, el_constrs);
         if ret.is_err() {
             return Err(ret.err().unwrap());
         }
-        let mut enc = ret.unwrap();
-        for x in self {
-            let ret = enc.append(&x.to_aper(Constraints {
-                    value: None,
-                    size: constraints.value,
-                })
-                .unwrap());
-            if ret.is_err() {
-                return Err(ret.err().unwrap());
-            }
-        }
-        Ok(enc)
+        sorted.push((enc.into_encoding(), x));
+    }
+    sorted.sort_by(|a, b| cmp_padded(a.0.bytes(), b.0.bytes()));
+
+    // Each element is written again, since its encoding within the set may be aligned differently
+    let sorted: Vec<&T> = sorted.into_iter().map(|(_, x)| x).collect();
+    encode_sequence_of(encoder, &sorted, constraints, |x, enc, c| f(x, enc, c))
+}
+
+impl<T: APerElement> APerElement for SetOf<T> {
+    const CONSTRAINTS: Constraints = Constraints {
+        value: None,
+        size: None,
+    };
+
+    /// Read a `SetOf<T>` from an aligned PER encoding.
+    fn from_aper(decoder: &mut Decoder, constraints: Constraints) -> Result<Self, DecodeError> {
+        decode_sequence_of(decoder, constraints, T::from_aper).map(SetOf)
+    }
+
+    fn to_aper(&self, constraints: Constraints) -> Result<Encoding, EncodeError> {
+        Encoder::encode(|enc| self.write_aper(enc, constraints))
+    }
+
+    fn write_aper(&self, encoder: &mut Encoder, constraints: Constraints) -> Result<(), EncodeError> {
+        encode_set_of(encoder, &self.0, constraints, T::write_aper)
     }
 }
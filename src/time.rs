@@ -0,0 +1,925 @@
+use aper::{APerElement, Constraints, Decoder, DecodeError, Encoder, Encoding, EncodeError, UNCONSTRAINED};
+use char_string::VisibleString;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{self, SystemTime, UNIX_EPOCH};
+
+/// A DATE, which is a calendar date such as `2024-03-15` with a year from 0 to 9999.
+///
+/// A `Date` is encoded with the structured encoding of X.691, as the year, month and day. Years from 1749 to 2276 are
+/// encoded as an offset within one of a few ranges, and any other year as an unconstrained INTEGER.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::Date;
+/// use asn1::aper::{APerElement, UNCONSTRAINED};
+///
+/// let x: Date = "2024-03-15".parse().unwrap();
+/// println!("{}", x.year()); // Prints 2024
+/// println!("{:?}", x.to_aper(UNCONSTRAINED).unwrap().bytes()); // Prints [64, 3, 39, 0]
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Date {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+/// A TIME-OF-DAY, which is a local time such as `12:30:45`.
+///
+/// The hour is from 0 to 23, or 24 for `24:00:00`, the end of the day, and the second may be 60 for a leap second. A
+/// `TimeOfDay` is encoded with the structured encoding of X.691, as the hour, minute and second.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TimeOfDay {
+    hour: u8,
+    minute: u8,
+    second: u8,
+}
+
+/// A DATE-TIME, which is a local date and time such as `2024-03-15T12:30:45`. It's encoded as a
+/// [Date](struct.Date.html) followed by a [TimeOfDay](struct.TimeOfDay.html).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DateTime {
+    date: Date,
+    time: TimeOfDay,
+}
+
+/// A DURATION, which is an ISO 8601 duration such as `P1Y2M10DT2H30M` or `P3W`.
+///
+/// Each component is `None` if it's left out, which is distinct from being 0. At least one must be present, and
+/// `fraction` is the fractional part of the last one, as `(number of digits, value)`, so `PT1.25S` has `seconds` of
+/// `Some(1)` and `fraction` of `Some((2, 25))`. A `Duration` is encoded with the structured encoding of X.691, as a
+/// SEQUENCE of the components which are present.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Duration {
+    pub years: Option<u64>,
+    pub months: Option<u64>,
+    pub weeks: Option<u64>,
+    pub days: Option<u64>,
+    pub hours: Option<u64>,
+    pub minutes: Option<u64>,
+    pub seconds: Option<u64>,
+    pub fraction: Option<(u32, u64)>,
+}
+
+/// A UTCTime, which is a UTC time with a two-digit year standing for a year from 1950 to 2049.
+///
+/// A `UtcTime` is encoded as a VisibleString in the DER form `YYMMDDhhmmssZ`. When decoding or parsing, the seconds
+/// can be left out and the time can have an offset from UTC, such as `+0100`, which is removed.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::UtcTime;
+///
+/// let x: UtcTime = "2403151230+0100".parse().unwrap();
+/// println!("{}", x); // Prints 240315113000Z
+/// println!("{:?}", x.to_system_time()); // Prints SystemTime { tv_sec: 1710502200, tv_nsec: 0 }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct UtcTime {
+    date_time: DateTime,
+}
+
+/// A GeneralizedTime, which is a UTC time with a four-digit year and a fraction of a second.
+///
+/// A `GeneralizedTime` is encoded as a VisibleString in the DER form `YYYYMMDDhhmmss[.f]Z`, in which the fraction has
+/// no trailing zeros and is left out if it's 0. When decoding or parsing, the minutes and seconds can be left out and
+/// the time can have an offset from UTC, such as `-05` or `+0530`, which is removed. A local time, which has neither a
+/// `Z` nor an offset, can't be converted to UTC and isn't supported. Neither is a fraction of an hour or minute, or
+/// one more precise than a nanosecond.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GeneralizedTime {
+    date_time: DateTime,
+    nanosecond: u32,
+}
+
+/// An error from parsing one of the time types.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseTimeError;
+
+/// Check if `year` is a leap year in the proleptic Gregorian calendar.
+fn is_leap_year(year: u16) -> bool {
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+}
+
+/// Get the number of days in `month` of `year`.
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Get the number of days from 1970-01-01 to a date.
+fn days_from_civil(year: i64, month: u8, day: u8) -> i64 {
+    // Count from March, so that a leap day is at the end of the year
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * ((month as i64 + 9) % 12) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// Get the date which is `days` after 1970-01-01, as `(year, month, day)`.
+fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let doe = days - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = yoe + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+impl Date {
+    /// Construct a `Date`, or `None` if it isn't a valid date with a year from 0 to 9999.
+    pub fn new(year: u16, month: u8, day: u8) -> Option<Date> {
+        if year > 9999 || !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+            return None;
+        }
+        Some(Date { year, month, day })
+    }
+
+    /// Get the year.
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    /// Get the month, from 1 to 12.
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    /// Get the day of the month, from 1.
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+}
+
+impl TimeOfDay {
+    /// Construct a `TimeOfDay`, or `None` if it isn't a valid time of day.
+    pub fn new(hour: u8, minute: u8, second: u8) -> Option<TimeOfDay> {
+        if (hour > 23 || minute > 59 || second > 60) && (hour, minute, second) != (24, 0, 0) {
+            return None;
+        }
+        Some(TimeOfDay { hour, minute, second })
+    }
+
+    /// Get the hour, from 0 to 24.
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    /// Get the minute, from 0 to 59.
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    /// Get the second, from 0 to 60.
+    pub fn second(&self) -> u8 {
+        self.second
+    }
+}
+
+impl DateTime {
+    /// Construct a `DateTime` from a date and a time of day.
+    pub fn new(date: Date, time: TimeOfDay) -> DateTime {
+        DateTime { date, time }
+    }
+
+    /// Get the date.
+    pub fn date(&self) -> Date {
+        self.date
+    }
+
+    /// Get the time of day.
+    pub fn time(&self) -> TimeOfDay {
+        self.time
+    }
+
+    /// Get the number of seconds from 1970-01-01T00:00:00, as if this were a UTC time.
+    fn to_seconds(self) -> i64 {
+        let days = days_from_civil(self.date.year as i64, self.date.month, self.date.day);
+        days * 86400 + self.time.hour as i64 * 3600 + self.time.minute as i64 * 60 + self.time.second as i64
+    }
+
+    /// Get the `DateTime` which is `seconds` after 1970-01-01T00:00:00, or `None` if its year is beyond 9999.
+    fn from_seconds(seconds: i64) -> Option<DateTime> {
+        let (year, month, day) = civil_from_days(seconds.div_euclid(86400));
+        if !(0..=9999).contains(&year) {
+            return None;
+        }
+        let s = seconds.rem_euclid(86400);
+        Some(DateTime {
+            date: Date { year: year as u16, month, day },
+            time: TimeOfDay {
+                hour: (s / 3600) as u8,
+                minute: (s / 60 % 60) as u8,
+                second: (s % 60) as u8,
+            },
+        })
+    }
+
+    /// Convert a local `DateTime` which is `offset` seconds ahead of UTC to UTC, also moving `24:00:00` to the start
+    /// of the next day.
+    fn to_utc(self, offset: i64) -> Option<DateTime> {
+        if offset == 0 && self.time.hour < 24 {
+            return Some(self);
+        }
+        DateTime::from_seconds(self.to_seconds() - offset)
+    }
+}
+
+impl Duration {
+    /// Get the components from years to seconds.
+    fn components(&self) -> [Option<u64>; 7] {
+        [self.years, self.months, self.weeks, self.days, self.hours, self.minutes, self.seconds]
+    }
+
+    /// Set the component at `index` of [components()](#method.components).
+    fn set_component(&mut self, index: usize, v: u64) {
+        let component = match index {
+            0 => &mut self.years,
+            1 => &mut self.months,
+            2 => &mut self.weeks,
+            3 => &mut self.days,
+            4 => &mut self.hours,
+            5 => &mut self.minutes,
+            _ => &mut self.seconds,
+        };
+        *component = Some(v);
+    }
+
+    /// Check if at least one component is present and the fraction, if any, is valid.
+    fn is_valid(&self) -> bool {
+        self.components().iter().any(|c| c.is_some()) &&
+        self.fraction.is_none_or(|(digits, v)| (1..=19).contains(&digits) && v < 10u64.pow(digits))
+    }
+}
+
+impl UtcTime {
+    /// Construct a `UtcTime`, or `None` if the year isn't from 1950 to 2049 or the hour is 24.
+    pub fn new(date_time: DateTime) -> Option<UtcTime> {
+        if date_time.date.year < 1950 || date_time.date.year > 2049 || date_time.time.hour > 23 {
+            return None;
+        }
+        Some(UtcTime { date_time })
+    }
+
+    /// Get the date and time.
+    pub fn date_time(&self) -> DateTime {
+        self.date_time
+    }
+
+    /// Construct the `UtcTime` of `t`, rounded down to the second, or `None` if it's outside of the years 1950 to
+    /// 2049.
+    pub fn from_system_time(t: SystemTime) -> Option<UtcTime> {
+        let (seconds, _) = to_unix(t)?;
+        UtcTime::new(DateTime::from_seconds(seconds)?)
+    }
+
+    /// Get the `SystemTime` of the `UtcTime`.
+    pub fn to_system_time(&self) -> SystemTime {
+        from_unix(self.date_time.to_seconds(), 0)
+    }
+}
+
+impl GeneralizedTime {
+    /// Construct a `GeneralizedTime`, or `None` if the hour is 24 or `nanosecond` isn't below 1,000,000,000.
+    pub fn new(date_time: DateTime, nanosecond: u32) -> Option<GeneralizedTime> {
+        if date_time.time.hour > 23 || nanosecond >= 1_000_000_000 {
+            return None;
+        }
+        Some(GeneralizedTime { date_time, nanosecond })
+    }
+
+    /// Get the date and time.
+    pub fn date_time(&self) -> DateTime {
+        self.date_time
+    }
+
+    /// Get the fraction of the second in nanoseconds.
+    pub fn nanosecond(&self) -> u32 {
+        self.nanosecond
+    }
+
+    /// Construct the `GeneralizedTime` of `t`, or `None` if it's outside of the years 0 to 9999.
+    pub fn from_system_time(t: SystemTime) -> Option<GeneralizedTime> {
+        let (seconds, nanosecond) = to_unix(t)?;
+        GeneralizedTime::new(DateTime::from_seconds(seconds)?, nanosecond)
+    }
+
+    /// Get the `SystemTime` of the `GeneralizedTime`.
+    pub fn to_system_time(&self) -> SystemTime {
+        from_unix(self.date_time.to_seconds(), self.nanosecond)
+    }
+}
+
+/// Split `t` into whole seconds from the Unix epoch, which may be negative, and nanoseconds after that.
+fn to_unix(t: SystemTime) -> Option<(i64, u32)> {
+    match t.duration_since(UNIX_EPOCH) {
+        Ok(d) if d.as_secs() <= i64::MAX as u64 => Some((d.as_secs() as i64, d.subsec_nanos())),
+        Ok(_) => None,
+        Err(e) => {
+            let d = e.duration();
+            if d.as_secs() >= i64::MAX as u64 {
+                return None;
+            }
+            let seconds = -(d.as_secs() as i64);
+            match d.subsec_nanos() {
+                0 => Some((seconds, 0)),
+                n => Some((seconds - 1, 1_000_000_000 - n)),
+            }
+        }
+    }
+}
+
+/// Get the `SystemTime` which is `seconds` and `nanosecond` after the Unix epoch.
+fn from_unix(seconds: i64, nanosecond: u32) -> SystemTime {
+    let t = if seconds >= 0 {
+        UNIX_EPOCH + time::Duration::from_secs(seconds as u64)
+    } else {
+        UNIX_EPOCH - time::Duration::from_secs(seconds.unsigned_abs())
+    };
+    t + time::Duration::from_nanos(nanosecond as u64)
+}
+
+/// A cursor over the characters of a time string.
+struct Cursor<'a> {
+    s: &'a [u8],
+    i: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(s: &'a str) -> Cursor<'a> {
+        Cursor { s: s.as_bytes(), i: 0 }
+    }
+
+    /// Read a number of exactly `n` digits.
+    fn number(&mut self, n: usize) -> Option<u32> {
+        if self.i + n > self.s.len() {
+            return None;
+        }
+        let mut v = 0;
+        for &b in &self.s[self.i..self.i + n] {
+            if !b.is_ascii_digit() {
+                return None;
+            }
+            v = v * 10 + (b - b'0') as u32;
+        }
+        self.i += n;
+        Some(v)
+    }
+
+    /// Read a number of any length of up to 19 digits, and get it along with its length.
+    fn digits(&mut self) -> Option<(u64, u32)> {
+        let start = self.i;
+        while self.at_digit() {
+            self.i += 1;
+        }
+        let n = self.i - start;
+        if n == 0 || n > 19 {
+            return None;
+        }
+        let v = self.s[start..self.i].iter().fold(0u64, |v, &b| v * 10 + (b - b'0') as u64);
+        Some((v, n as u32))
+    }
+
+    /// Check if the next character is a digit.
+    fn at_digit(&self) -> bool {
+        self.i < self.s.len() && self.s[self.i].is_ascii_digit()
+    }
+
+    /// Skip over `c` if it's the next character, and return whether it was.
+    fn eat(&mut self, c: u8) -> bool {
+        if self.i < self.s.len() && self.s[self.i] == c {
+            self.i += 1;
+            return true;
+        }
+        false
+    }
+
+    /// Skip over `c`, or return `None` if it isn't the next character.
+    fn expect(&mut self, c: u8) -> Option<()> {
+        if self.eat(c) { Some(()) } else { None }
+    }
+
+    fn is_done(&self) -> bool {
+        self.i == self.s.len()
+    }
+
+    /// Read a time zone, which is `Z` or an offset such as `+0100`, and get its offset from UTC in seconds. The minutes
+    /// of an offset are optional if `short` is set.
+    fn zone(&mut self, short: bool) -> Option<i64> {
+        if self.eat(b'Z') {
+            return Some(0);
+        }
+        let sign = if self.eat(b'+') {
+            1
+        } else {
+            self.expect(b'-')?;
+            -1
+        };
+        let hours = self.number(2)?;
+        let minutes = if !short || self.at_digit() { self.number(2)? } else { 0 };
+        if hours > 23 || minutes > 59 {
+            return None;
+        }
+        Some(sign * (hours as i64 * 3600 + minutes as i64 * 60))
+    }
+
+    /// Read a date in the form `YYYY-MM-DD`.
+    fn date(&mut self) -> Option<Date> {
+        let year = self.number(4)?;
+        self.expect(b'-')?;
+        let month = self.number(2)?;
+        self.expect(b'-')?;
+        let day = self.number(2)?;
+        Date::new(year as u16, month as u8, day as u8)
+    }
+
+    /// Read a time of day in the form `hh:mm:ss`.
+    fn time(&mut self) -> Option<TimeOfDay> {
+        let hour = self.number(2)?;
+        self.expect(b':')?;
+        let minute = self.number(2)?;
+        self.expect(b':')?;
+        let second = self.number(2)?;
+        TimeOfDay::new(hour as u8, minute as u8, second as u8)
+    }
+}
+
+/// Parse all of `s` with `f`.
+fn parse<T, F>(s: &str, f: F) -> Result<T, ParseTimeError>
+    where F: FnOnce(&mut Cursor) -> Option<T>
+{
+    let mut c = Cursor::new(s);
+    match f(&mut c) {
+        Some(v) if c.is_done() => Ok(v),
+        _ => Err(ParseTimeError),
+    }
+}
+
+impl FromStr for Date {
+    type Err = ParseTimeError;
+
+    /// Parse a `Date` in the form `YYYY-MM-DD`.
+    fn from_str(s: &str) -> Result<Date, ParseTimeError> {
+        parse(s, |c| c.date())
+    }
+}
+
+impl FromStr for TimeOfDay {
+    type Err = ParseTimeError;
+
+    /// Parse a `TimeOfDay` in the form `hh:mm:ss`.
+    fn from_str(s: &str) -> Result<TimeOfDay, ParseTimeError> {
+        parse(s, |c| c.time())
+    }
+}
+
+impl FromStr for DateTime {
+    type Err = ParseTimeError;
+
+    /// Parse a `DateTime` in the form `YYYY-MM-DDThh:mm:ss`.
+    fn from_str(s: &str) -> Result<DateTime, ParseTimeError> {
+        parse(s, |c| {
+            let date = c.date()?;
+            c.expect(b'T')?;
+            Some(DateTime::new(date, c.time()?))
+        })
+    }
+}
+
+impl FromStr for Duration {
+    type Err = ParseTimeError;
+
+    /// Parse a `Duration` in the ISO 8601 form `PnYnMnWnDTnHnMnS`, in which each component is optional but they
+    /// must be in order, and the last one can have a fraction after a `.` or `,`.
+    fn from_str(s: &str) -> Result<Duration, ParseTimeError> {
+        parse(s, |c| {
+            c.expect(b'P')?;
+            let mut d = Duration::default();
+            let mut next = 0;
+            while !c.is_done() {
+                if next <= 4 && c.eat(b'T') {
+                    // The time components, which there must be at least one of
+                    next = 4;
+                    if !c.at_digit() {
+                        return None;
+                    }
+                }
+                let (v, _) = c.digits()?;
+                let fraction = if c.eat(b'.') || c.eat(b',') { Some(c.digits()?) } else { None };
+                let designator = *c.s.get(c.i)?;
+                c.i += 1;
+                let index = match (next >= 4, designator) {
+                    (false, b'Y') => 0,
+                    (false, b'M') => 1,
+                    (false, b'W') => 2,
+                    (false, b'D') => 3,
+                    (true, b'H') => 4,
+                    (true, b'M') => 5,
+                    (true, b'S') => 6,
+                    _ => return None,
+                };
+                if index < next {
+                    return None;
+                }
+                next = index + 1;
+                d.set_component(index, v);
+                if let Some((v, digits)) = fraction {
+                    if !c.is_done() {
+                        return None;
+                    }
+                    d.fraction = Some((digits, v));
+                }
+            }
+            if d.is_valid() { Some(d) } else { None }
+        })
+    }
+}
+
+impl FromStr for UtcTime {
+    type Err = ParseTimeError;
+
+    /// Parse a `UtcTime` in the form `YYMMDDhhmm[ss]` followed by `Z` or an offset from UTC such as `+0100`.
+    fn from_str(s: &str) -> Result<UtcTime, ParseTimeError> {
+        parse(s, |c| {
+            let year = c.number(2)?;
+            let year = if year < 50 { 2000 + year } else { 1900 + year };
+            let date = Date::new(year as u16, c.number(2)? as u8, c.number(2)? as u8)?;
+            let (hour, minute) = (c.number(2)?, c.number(2)?);
+            let second = if c.at_digit() { c.number(2)? } else { 0 };
+            let time = TimeOfDay::new(hour as u8, minute as u8, second as u8)?;
+            let offset = c.zone(false)?;
+            UtcTime::new(DateTime::new(date, time).to_utc(offset)?)
+        })
+    }
+}
+
+impl FromStr for GeneralizedTime {
+    type Err = ParseTimeError;
+
+    /// Parse a `GeneralizedTime` in the form `YYYYMMDDhh[mm[ss[.f]]]` followed by `Z` or an offset from UTC such as
+    /// `+01` or `+0100`. The fraction can also follow a `,`.
+    fn from_str(s: &str) -> Result<GeneralizedTime, ParseTimeError> {
+        parse(s, |c| {
+            let date = Date::new(c.number(4)? as u16, c.number(2)? as u8, c.number(2)? as u8)?;
+            let hour = c.number(2)?;
+            let minute = if c.at_digit() { Some(c.number(2)?) } else { None };
+            let second = if minute.is_some() && c.at_digit() { Some(c.number(2)?) } else { None };
+
+            let mut nanosecond = 0;
+            if second.is_some() && (c.eat(b'.') || c.eat(b',')) {
+                let start = c.i;
+                while c.at_digit() {
+                    c.i += 1;
+                }
+                let digits = &c.s[start..c.i];
+                if digits.is_empty() || digits.iter().skip(9).any(|&b| b != b'0') {
+                    return None;
+                }
+                for i in 0..9 {
+                    nanosecond = nanosecond * 10 + digits.get(i).map_or(0, |&b| (b - b'0') as u32);
+                }
+            }
+
+            let time = TimeOfDay::new(hour as u8, minute.unwrap_or(0) as u8, second.unwrap_or(0) as u8)?;
+            let offset = c.zone(true)?;
+            GeneralizedTime::new(DateTime::new(date, time).to_utc(offset)?, nanosecond)
+        })
+    }
+}
+
+impl fmt::Display for Date {
+    /// Format the `Date` as `YYYY-MM-DD`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl fmt::Display for TimeOfDay {
+    /// Format the `TimeOfDay` as `hh:mm:ss`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:02}:{:02}:{:02}", self.hour, self.minute, self.second)
+    }
+}
+
+impl fmt::Display for DateTime {
+    /// Format the `DateTime` as `YYYY-MM-DDThh:mm:ss`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}T{}", self.date, self.time)
+    }
+}
+
+impl fmt::Display for Duration {
+    /// Format the `Duration` in the ISO 8601 form, such as `P1Y2M10DT2H30M`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let components = self.components();
+        let last = components.iter().rposition(|c| c.is_some());
+        f.write_str("P")?;
+        for (i, c) in components.iter().enumerate() {
+            if i == 4 && components[4..].iter().any(|c| c.is_some()) {
+                f.write_str("T")?;
+            }
+            if let Some(v) = *c {
+                write!(f, "{}", v)?;
+                if let (Some((digits, v)), true) = (self.fraction, last == Some(i)) {
+                    write!(f, ".{:0width$}", v, width = digits as usize)?;
+                }
+                f.write_str(["Y", "M", "W", "D", "H", "M", "S"][i])?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for UtcTime {
+    /// Format the `UtcTime` in the DER form `YYMMDDhhmmssZ`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (date, time) = (self.date_time.date, self.date_time.time);
+        write!(f, "{:02}{:02}{:02}{:02}{:02}{:02}Z", date.year % 100, date.month, date.day, time.hour, time.minute,
+               time.second)
+    }
+}
+
+impl fmt::Display for GeneralizedTime {
+    /// Format the `GeneralizedTime` in the DER form `YYYYMMDDhhmmss[.f]Z`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (date, time) = (self.date_time.date, self.date_time.time);
+        write!(f, "{:04}{:02}{:02}{:02}{:02}{:02}", date.year, date.month, date.day, time.hour, time.minute,
+               time.second)?;
+        if self.nanosecond > 0 {
+            let fraction = format!("{:09}", self.nanosecond);
+            write!(f, ".{}", fraction.trim_end_matches('0'))?;
+        }
+        f.write_str("Z")
+    }
+}
+
+/// Fail with `DecodeError::MalformedTime` if the fields of a decoded time aren't valid.
+fn check<T>(v: Option<T>) -> Result<T, DecodeError> {
+    v.ok_or(DecodeError::MalformedTime)
+}
+
+/// Read a year, which is encoded as a CHOICE of ranges that are closer to the present or an unconstrained INTEGER.
+fn decode_year(decoder: &mut Decoder) -> Result<i64, DecodeError> {
+    let ret = decoder.decode_int(Some(0), Some(3));
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    let (min, max) = YEAR_RANGES[ret.unwrap() as usize];
+    decoder.decode_int(min, max)
+}
+
+/// The ranges of the alternatives of a year: immediate, near-future, near-past and remainder.
+const YEAR_RANGES: [(Option<i64>, Option<i64>); 4] = [
+    (Some(2005), Some(2020)),
+    (Some(2021), Some(2276)),
+    (Some(1749), Some(2004)),
+    (None, None),
+];
+
+fn encode_year(encoder: &mut Encoder, year: u16) -> Result<(), EncodeError> {
+    let year = year as i64;
+    let index = YEAR_RANGES.iter()
+        .position(|&(min, max)| min.is_none_or(|min| year >= min) && max.is_none_or(|max| year <= max))
+        .unwrap();
+    let ret = encoder.encode_int(index as i64, Some(0), Some(3));
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    let (min, max) = YEAR_RANGES[index];
+    encoder.encode_int(year, min, max)
+}
+
+/// Read the fields of a structured encoding, each an INTEGER within its bounds.
+fn decode_fields<const N: usize>(decoder: &mut Decoder, bounds: [(i64, i64); N]) -> Result<[i64; N], DecodeError> {
+    let mut fields = [0; N];
+    for (field, &(min, max)) in fields.iter_mut().zip(bounds.iter()) {
+        let ret = decoder.decode_int(Some(min), Some(max));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        *field = ret.unwrap();
+    }
+    Ok(fields)
+}
+
+/// Write the fields of a structured encoding, each an INTEGER within its bounds.
+fn encode_fields<const N: usize>(encoder: &mut Encoder, fields: [(i64, i64, i64); N]) -> Result<(), EncodeError> {
+    for &(v, min, max) in fields.iter() {
+        let ret = encoder.encode_int(v, Some(min), Some(max));
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+    }
+    Ok(())
+}
+
+/// Conversion to and from the PER encoding of a time type.
+trait TimeEncoding: Sized {
+    fn decode(decoder: &mut Decoder) -> Result<Self, DecodeError>;
+    fn encode(&self, encoder: &mut Encoder) -> Result<(), EncodeError>;
+}
+
+impl TimeEncoding for Date {
+    fn decode(decoder: &mut Decoder) -> Result<Date, DecodeError> {
+        let ret = decode_year(decoder);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let year = ret.unwrap();
+        let ret = decode_fields(decoder, [(1, 12), (1, 31)]);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let [month, day] = ret.unwrap();
+        if !(0..=9999).contains(&year) {
+            return Err(DecodeError::MalformedTime);
+        }
+        check(Date::new(year as u16, month as u8, day as u8))
+    }
+
+    fn encode(&self, encoder: &mut Encoder) -> Result<(), EncodeError> {
+        let ret = encode_year(encoder, self.year);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        encode_fields(encoder, [(self.month as i64, 1, 12), (self.day as i64, 1, 31)])
+    }
+}
+
+impl TimeEncoding for TimeOfDay {
+    fn decode(decoder: &mut Decoder) -> Result<TimeOfDay, DecodeError> {
+        let ret = decode_fields(decoder, [(0, 24), (0, 59), (0, 60)]);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let [hour, minute, second] = ret.unwrap();
+        check(TimeOfDay::new(hour as u8, minute as u8, second as u8))
+    }
+
+    fn encode(&self, encoder: &mut Encoder) -> Result<(), EncodeError> {
+        encode_fields(encoder, [(self.hour as i64, 0, 24), (self.minute as i64, 0, 59), (self.second as i64, 0, 60)])
+    }
+}
+
+impl TimeEncoding for DateTime {
+    fn decode(decoder: &mut Decoder) -> Result<DateTime, DecodeError> {
+        let ret = Date::decode(decoder);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let date = ret.unwrap();
+        let ret = TimeOfDay::decode(decoder);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        Ok(DateTime::new(date, ret.unwrap()))
+    }
+
+    fn encode(&self, encoder: &mut Encoder) -> Result<(), EncodeError> {
+        let ret = self.date.encode(encoder);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        self.time.encode(encoder)
+    }
+}
+
+impl TimeEncoding for Duration {
+    /// Read a `Duration`, which is a SEQUENCE with an OPTIONAL `INTEGER (0..MAX)` for each component and then an
+    /// OPTIONAL fraction of `INTEGER (1..MAX)` digits and an `INTEGER (0..MAX)` value.
+    fn decode(decoder: &mut Decoder) -> Result<Duration, DecodeError> {
+        let ret = decoder.read_bits(8);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        let preamble = ret.unwrap();
+
+        let mut d = Duration::default();
+        for i in 0..7 {
+            if preamble & (0x80 >> i) != 0 {
+                let ret = decoder.decode_int(Some(0), None);
+                if ret.is_err() {
+                    return Err(ret.err().unwrap());
+                }
+                d.set_component(i, ret.unwrap() as u64);
+            }
+        }
+        if preamble & 0x01 != 0 {
+            let ret = decoder.decode_int(Some(1), None);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            let digits = ret.unwrap();
+            let ret = decoder.decode_int(Some(0), None);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+            if digits > 19 {
+                return Err(DecodeError::MalformedTime);
+            }
+            d.fraction = Some((digits as u32, ret.unwrap() as u64));
+        }
+        if !d.is_valid() {
+            return Err(DecodeError::MalformedTime);
+        }
+        Ok(d)
+    }
+
+    fn encode(&self, encoder: &mut Encoder) -> Result<(), EncodeError> {
+        if !self.is_valid() {
+            return Err(EncodeError::InvalidValue);
+        }
+        let components = self.components();
+        for c in components.iter() {
+            encoder.write_bits(c.is_some() as u64, 1);
+        }
+        encoder.write_bits(self.fraction.is_some() as u64, 1);
+
+        let mut fields: Vec<(u64, i64)> = components.iter().filter_map(|&c| c.map(|v| (v, 0))).collect();
+        if let Some((digits, v)) = self.fraction {
+            fields.push((digits as u64, 1));
+            fields.push((v, 0));
+        }
+        for (v, min) in fields {
+            if v > i64::MAX as u64 {
+                return Err(EncodeError::InvalidValue);
+            }
+            let ret = encoder.encode_int(v as i64, Some(min), None);
+            if ret.is_err() {
+                return Err(ret.err().unwrap());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TimeEncoding for UtcTime {
+    fn decode(decoder: &mut Decoder) -> Result<UtcTime, DecodeError> {
+        // The decoder knows whether it's aligned, so this reads a VisibleString in either variant of PER
+        let ret = VisibleString::from_aper(decoder, UNCONSTRAINED);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        ret.unwrap().0.parse().map_err(|_| DecodeError::MalformedTime)
+    }
+
+    fn encode(&self, encoder: &mut Encoder) -> Result<(), EncodeError> {
+        VisibleString(self.to_string()).write_aper(encoder, UNCONSTRAINED)
+    }
+}
+
+impl TimeEncoding for GeneralizedTime {
+    fn decode(decoder: &mut Decoder) -> Result<GeneralizedTime, DecodeError> {
+        let ret = VisibleString::from_aper(decoder, UNCONSTRAINED);
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        ret.unwrap().0.parse().map_err(|_| DecodeError::MalformedTime)
+    }
+
+    fn encode(&self, encoder: &mut Encoder) -> Result<(), EncodeError> {
+        VisibleString(self.to_string()).write_aper(encoder, UNCONSTRAINED)
+    }
+}
+
+macro_rules! time_impl {
+    ($t:ident) => {
+        impl APerElement for $t {
+            const CONSTRAINTS: Constraints = UNCONSTRAINED;
+
+            fn from_aper(decoder: &mut Decoder, _: Constraints) -> Result<Self, DecodeError> {
+                $t::decode(decoder)
+            }
+
+            fn to_aper(&self, constraints: Constraints) -> Result<Encoding, EncodeError> {
+                Encoder::encode(|enc| self.write_aper(enc, constraints))
+            }
+
+            fn write_aper(&self, encoder: &mut Encoder, _: Constraints) -> Result<(), EncodeError> {
+                self.encode(encoder)
+            }
+        }
+    }
+}
+
+time_impl!(Date);
+time_impl!(TimeOfDay);
+time_impl!(DateTime);
+time_impl!(Duration);
+time_impl!(UtcTime);
+time_impl!(GeneralizedTime);
@@ -0,0 +1,46 @@
+use aper::{self, Encoder, Encoding, EncodeError};
+
+/// Encode an unaligned PER length determinant.
+///
+/// The length determinant itself is the same as in aligned PER, but it isn't octet-aligned. See
+/// [aper::encode_length](../aper/fn.encode_length.html).
+pub fn encode_length(len: usize) -> Result<Encoding, EncodeError> {
+    Encoder::encode_unaligned(|enc| enc.encode_length(len))
+}
+
+/// Encode a run of `len` items preceded by an unaligned PER length determinant, fragmenting it if necessary. See
+/// [aper::encode_fragmented](../aper/fn.encode_fragmented.html).
+pub fn encode_fragmented<F>(len: usize, f: F) -> Result<Encoding, EncodeError>
+    where F: FnMut(&mut Encoding, usize, usize) -> Result<(), EncodeError>
+{
+    Encoder::encode_unaligned(|enc| aper::fragmented(enc, len, f))
+}
+
+/// Encode a normally small non-negative whole number in unaligned PER. See
+/// [aper::encode_normally_small](../aper/fn.encode_normally_small.html).
+pub fn encode_normally_small(n: u64) -> Result<Encoding, EncodeError> {
+    Encoder::encode_unaligned(|enc| enc.encode_normally_small(n))
+}
+
+/// Encode `inner` as an unaligned PER open type. See [aper::encode_open_type](../aper/fn.encode_open_type.html).
+pub fn encode_open_type(inner: &Encoding) -> Result<Encoding, EncodeError> {
+    Encoder::encode_unaligned(|enc| enc.encode_open_type_bytes(inner.bytes()))
+}
+
+/// Encode an unaligned PER integer between `min` and `max`.
+///
+/// Unlike [aper::encode_int](../aper/fn.encode_int.html), a constrained integer is always encoded as a bit-field of
+/// the minimum width needed to express its range, regardless of how large that range is. Returns
+/// `EncodeError::InvalidValue` if `value` is out of bounds.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::uper::encode_int;
+///
+/// println!("{:?}", encode_int(1000, Some(0), Some(1000)).unwrap().bytes()); // Prints [250, 0]
+/// ```
+pub fn encode_int(value: i64, min: Option<i64>, max: Option<i64>) -> Result<Encoding, EncodeError> {
+    Encoder::encode_unaligned(|enc| enc.encode_int(value, min, max))
+}
@@ -0,0 +1,59 @@
+mod encoding;
+use aper::APerElement;
+pub use aper::{Constraint, Constraints, Decoder, DecodeError, Encoder, Encoding, EncodeError, UNCONSTRAINED};
+pub use self::encoding::{encode_fragmented, encode_int, encode_length, encode_normally_small, encode_open_type};
+
+/// Trait for Unaligned PER encoding/decoding.
+///
+/// `UPerElement` mirrors [aper::APerElement](../aper/trait.APerElement.html) and shares its constraint model, but
+/// never inserts padding to bring a field onto an octet boundary, and always encodes constrained integers using the
+/// minimum number of bits. Decoding is done with a `Decoder` constructed using
+/// [Decoder::new_unaligned](../aper/struct.Decoder.html#method.new_unaligned), and writing with an `Encoder`
+/// constructed using [Encoder::new_unaligned](../aper/struct.Encoder.html#method.new_unaligned).
+///
+/// Since a `Decoder` and an `Encoder` follow either variant of PER, every `APerElement` is also a `UPerElement`, so
+/// a type only needs to implement `APerElement`. Its `write_aper` should write to the `Encoder` it is given rather than
+/// append the result of `to_aper`, which is always aligned.
+///
+/// # Examples
+///
+/// ```
+/// extern crate asn1;
+/// use asn1::uper::{self, UPerElement, UNCONSTRAINED};
+///
+/// let data = b"\xfa\x00";
+/// let mut d = uper::Decoder::new_unaligned(data);
+/// let x = d.decode_int(Some(0), Some(1000)).unwrap();
+/// println!("x = {}", x); // Prints x = 1000
+/// let b = true.to_uper(UNCONSTRAINED).unwrap();
+/// println!("b = {:?}", b.bytes()); // Prints b = [128]
+/// ```
+pub trait UPerElement: Sized {
+    /// PER-visible Constraints
+    const CONSTRAINTS: Constraints;
+
+    /// Constructor for the `Result` type given an unaligned PER encoding.
+    fn from_uper(decoder: &mut Decoder, constraints: Constraints) -> Result<Self, DecodeError>;
+
+    /// For use with `Encoding::append`
+    fn to_uper(&self, constraints: Constraints) -> Result<Encoding, EncodeError>;
+
+    /// Write the unaligned PER encoding of `self` to `encoder`.
+    fn write_uper(&self, encoder: &mut Encoder, constraints: Constraints) -> Result<(), EncodeError>;
+}
+
+impl<T: APerElement> UPerElement for T {
+    const CONSTRAINTS: Constraints = <T as APerElement>::CONSTRAINTS;
+
+    fn from_uper(decoder: &mut Decoder, constraints: Constraints) -> Result<Self, DecodeError> {
+        T::from_aper(decoder, constraints)
+    }
+
+    fn to_uper(&self, constraints: Constraints) -> Result<Encoding, EncodeError> {
+        Encoder::encode_unaligned(|enc| self.write_aper(enc, constraints))
+    }
+
+    fn write_uper(&self, encoder: &mut Encoder, constraints: Constraints) -> Result<(), EncodeError> {
+        self.write_aper(encoder, constraints)
+    }
+}
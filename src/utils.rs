@@ -1,16 +1,90 @@
-pub fn shift_bytes_left(data: &mut Vec<u8>, shift: usize) {
-    if shift == 0 {
-        return;
+use aper::{Constraint, Decoder, DecodeError, Encoder, EncodeError};
+
+/// The number of bits needed to encode any value in `[min, max]` as an offset from `min`.
+pub fn range_bits(min: i64, max: i64) -> usize {
+    let r = max.wrapping_sub(min) as u64;
+    64 - r.leading_zeros() as usize
+}
+
+/// The minimum number of octets needed to encode `v` as a non-negative binary integer.
+pub fn uint_octets(v: u64) -> usize {
+    if v == 0 {
+        return 1;
+    }
+    (64 - v.leading_zeros() as usize).div_ceil(8)
+}
+
+/// The minimum number of octets needed to encode `v` as a 2's-complement binary integer.
+pub fn int_octets(v: i64) -> usize {
+    let magnitude = if v < 0 { !v as u64 } else { v as u64 };
+    (65 - magnitude.leading_zeros() as usize).div_ceil(8)
+}
+
+/// The bounds of a SIZE constraint as `(lb, ub, extensible)`, where a missing constraint or lower bound is 0 and a
+/// missing upper bound is `None`.
+pub fn size_bounds(size: Option<Constraint>) -> (usize, Option<usize>, bool) {
+    match size {
+        Some(c) => (c.min().unwrap_or(0).max(0) as usize, c.max().map(|h| h.max(0) as usize), c.is_extensible()),
+        None => (0, None, false),
+    }
+}
+
+/// Read the extension bit of an INTEGER between `min` and `max` if its value constraint is `extensible`, as in
+/// `INTEGER (0..65535, ...)`, and get the bounds to decode the value with. A value outside of the extension root is
+/// encoded as if it were unconstrained.
+pub fn decode_int_bounds(decoder: &mut Decoder, min: Option<i64>, max: Option<i64>, extensible: bool)
+                         -> Result<(Option<i64>, Option<i64>), DecodeError> {
+    if !extensible {
+        return Ok((min, max));
     }
-    let mask = !(0xFF >> shift);
-    let mut frag: u8;
-    if data.len() < 1 {
-        return;
+    let ret = decoder.read(1);
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
     }
-    data[0] <<= shift;
-    for i in 1..data.len() {
-        frag = data[i] & mask;
-        data[i] <<= shift;
-        data[i - 1] |= frag >> (8 - shift);
+    if ret.unwrap() > 0 {
+        Ok((None, None))
+    } else {
+        Ok((min, max))
     }
 }
+
+/// Write the extension bit of an INTEGER between `min` and `max` if its value constraint is `extensible`, where
+/// `in_root` tells whether the value is between them, and get the bounds to encode the value with.
+pub fn encode_int_bounds(encoder: &mut Encoder, min: Option<i64>, max: Option<i64>, extensible: bool, in_root: bool)
+                         -> (Option<i64>, Option<i64>) {
+    if !extensible {
+        return (min, max);
+    }
+    encoder.write_bits(!in_root as u64, 1);
+    if in_root {
+        (min, max)
+    } else {
+        (None, None)
+    }
+}
+
+/// Read the contents octets of a type that PER encodes like X.690, such as an OBJECT IDENTIFIER or REAL. They follow an
+/// unconstrained length determinant, and are octet-aligned in aligned PER.
+pub fn decode_contents(decoder: &mut Decoder) -> Result<Vec<u8>, DecodeError> {
+    let mut content: Vec<u8> = Vec::new();
+    let ret = decoder.decode_sized(None, |decoder, n| {
+        let ret = decoder.align();
+        if ret.is_err() {
+            return Err(ret.err().unwrap());
+        }
+        decoder.read_to_vec(&mut content, n * 8)
+    });
+    if ret.is_err() {
+        return Err(ret.err().unwrap());
+    }
+    Ok(content)
+}
+
+/// Write the contents octets of a type that PER encodes like X.690, preceded by an unconstrained length determinant.
+pub fn encode_contents(encoder: &mut Encoder, bytes: &[u8]) -> Result<(), EncodeError> {
+    encoder.encode_sized(bytes.len(), None, |enc, start, n| {
+        enc.align();
+        enc.write_bytes(&bytes[start..start + n]);
+        Ok(())
+    })
+}
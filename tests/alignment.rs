@@ -0,0 +1,111 @@
+extern crate asn1;
+use asn1::aper::{self, APerElement, Encoding, UNCONSTRAINED};
+use asn1::uper::{self, UPerElement};
+
+#[test]
+fn decode_align() {
+    let data = b"\xa0\x2b";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(1, d.read(1).unwrap());
+    d.align().unwrap();
+    assert_eq!(0x2b, d.read_u8().unwrap());
+
+    // Already on an octet boundary
+    let mut d = aper::Decoder::new(data);
+    d.align().unwrap();
+    assert_eq!(0xa0, d.read_u8().unwrap());
+
+    // The unaligned variant has no padding
+    let mut d = uper::Decoder::new_unaligned(data);
+    assert_eq!(1, d.read(1).unwrap());
+    d.align().unwrap();
+    assert_eq!(0x40, d.read_u8().unwrap());
+}
+
+#[test]
+fn decode_check_padding() {
+    let data = b"\xa0\x2b";
+    let mut d = aper::Decoder::new(data);
+    d.set_check_padding(true);
+    assert_eq!(1, d.read(1).unwrap());
+    assert_eq!(Err(aper::DecodeError::MalformedPadding), d.align());
+
+    let data = b"\x80\x2b";
+    let mut d = aper::Decoder::new(data);
+    d.set_check_padding(true);
+    assert_eq!(1, d.read(1).unwrap());
+    d.align().unwrap();
+    assert_eq!(0x2b, d.read_u8().unwrap());
+}
+
+#[test]
+fn encode_align() {
+    let mut enc = true.to_aper(UNCONSTRAINED).unwrap();
+    enc.align();
+    assert_eq!(vec![0x80], *enc.bytes());
+    assert_eq!(0, enc.r_padding());
+
+    // Aligning twice does nothing more
+    enc.align();
+    enc.append(&Encoding::with_aligned_bytes(vec![0x2b])).unwrap();
+    assert_eq!(vec![0x80, 0x2b], *enc.bytes());
+}
+
+#[test]
+fn append_at_offsets() {
+    // 3 bits, then an octet-aligned length determinant of 1 and one octet
+    let mut inner = aper::encode_int(5, Some(0), Some(7)).unwrap();
+    inner.append(&aper::encode_length(1).unwrap()).unwrap();
+    inner.append(&Encoding::with_aligned_bytes(vec![0xff])).unwrap();
+    assert_eq!(vec![0xa0, 0x01, 0xff], *inner.bytes());
+
+    // The padding before the length determinant shrinks as the inner encoding is shifted
+    let mut enc = true.to_aper(UNCONSTRAINED).unwrap();
+    enc.append(&inner).unwrap();
+    assert_eq!(vec![0xd0, 0x01, 0xff], *enc.bytes());
+
+    let mut enc = Encoding::with_bytes_and_padding(vec![0xf8], 3);
+    enc.append(&inner).unwrap();
+    assert_eq!(vec![0xfd, 0x01, 0xff], *enc.bytes());
+
+    // The 3 bits no longer fit before the boundary
+    let mut enc = Encoding::with_bytes_and_padding(vec![0xfc], 2);
+    enc.append(&inner).unwrap();
+    assert_eq!(vec![0xfe, 0x80, 0x01, 0xff], *enc.bytes());
+    assert_eq!(0, enc.r_padding());
+}
+
+#[test]
+fn nested_alignment() {
+    // An open type containing a two-octet integer, after 3 bits
+    let x = aper::encode_int(0x1234, Some(0), Some(0xffff)).unwrap();
+    let mut enc = aper::encode_int(5, Some(0), Some(7)).unwrap();
+    enc.append(&aper::encode_open_type(&x).unwrap()).unwrap();
+    assert_eq!(vec![0xa0, 0x02, 0x12, 0x34], *enc.bytes());
+
+    // Appending the whole thing after another bit recomputes only the first padding
+    let mut outer = true.to_aper(UNCONSTRAINED).unwrap();
+    outer.append(&enc).unwrap();
+    assert_eq!(vec![0xd0, 0x02, 0x12, 0x34], *outer.bytes());
+
+    let mut d = aper::Decoder::new(outer.bytes());
+    assert!(bool::from_aper(&mut d, UNCONSTRAINED).unwrap());
+    assert_eq!(5, d.decode_int(Some(0), Some(7)).unwrap());
+    assert_eq!(2, d.decode_length().unwrap());
+    assert_eq!(0x1234, d.decode_int(Some(0), Some(0xffff)).unwrap());
+}
+
+#[test]
+fn uper_unaligned() {
+    // The same fields in unaligned PER have no padding
+    let mut enc = true.to_uper(UNCONSTRAINED).unwrap();
+    enc.append(&uper::encode_length(1).unwrap()).unwrap();
+    enc.append(&0xffu8.to_uper(UNCONSTRAINED).unwrap()).unwrap();
+    assert_eq!(vec![0x80, 0xff, 0x80], *enc.bytes());
+    assert_eq!(7, enc.r_padding());
+
+    let mut d = uper::Decoder::new_unaligned(enc.bytes());
+    assert!(bool::from_uper(&mut d, UNCONSTRAINED).unwrap());
+    assert_eq!(1, d.decode_length().unwrap());
+    assert_eq!(0xff, u8::from_uper(&mut d, UNCONSTRAINED).unwrap());
+}
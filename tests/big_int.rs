@@ -0,0 +1,155 @@
+extern crate asn1;
+use asn1::BigInt;
+use asn1::aper::{self, APerElement, Constraint, Constraints, UNCONSTRAINED};
+use asn1::uper::{self, UPerElement};
+
+#[test]
+fn big_int_conversions() {
+    assert_eq!(&[0x00, 0x80], BigInt::from(128u8).to_signed_bytes_be());
+    assert_eq!(&[0x80], BigInt::from(-128i8).to_signed_bytes_be());
+    assert_eq!(&[0x00], BigInt::from_signed_bytes_be(&[]).to_signed_bytes_be());
+    assert_eq!(&[0xff], BigInt::from_signed_bytes_be(&[0xff, 0xff, 0xff]).to_signed_bytes_be());
+    assert_eq!(Some(&[0x80][..]), BigInt::from(128u64).to_unsigned_bytes_be());
+    assert_eq!(None, BigInt::from(-1i32).to_unsigned_bytes_be());
+
+    let x = BigInt::from(u64::MAX);
+    assert_eq!(None, x.to_i64());
+    assert_eq!(Some(u64::MAX), x.to_u64());
+    assert_eq!(Some(u64::MAX as i128), x.to_i128());
+
+    let x = BigInt::from(i128::MIN);
+    assert!(x.is_negative());
+    assert_eq!(Some(i128::MIN), x.to_i128());
+    assert_eq!(None, x.to_u128());
+    assert_eq!(None, BigInt::from_unsigned_bytes_be(&[1; 17]).to_u128());
+}
+
+#[test]
+fn big_int_unconstrained() {
+    // -2^72
+    let x = BigInt::from_signed_bytes_be(&[0xff, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    let target: Vec<u8> = vec![0x0a, 0xff, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    assert_eq!(target, *x.to_aper(UNCONSTRAINED).unwrap().bytes());
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(x, BigInt::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    // Too wide for `decode_int`
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(Err(aper::DecodeError::MalformedInt), d.decode_int(None, None));
+}
+
+#[test]
+fn big_int_semi_constrained() {
+    let c = Constraints {
+        value: Some(Constraint::new(Some(-1), None)),
+        size: None,
+    };
+    // 2^64 + 1 is 2^64 + 2 above the lower bound
+    let x = BigInt::from(u64::MAX as i128 + 2);
+    let target: Vec<u8> = vec![0x09, 0x01, 0, 0, 0, 0, 0, 0, 0, 0x02];
+    assert_eq!(target, *x.to_aper(c).unwrap().bytes());
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(x, BigInt::from_aper(&mut d, c).unwrap());
+
+    assert_eq!(Some(aper::EncodeError::InvalidValue), BigInt::from(-2).to_aper(c).err());
+}
+
+#[test]
+fn big_int_constrained() {
+    let c = Constraints {
+        value: Some(Constraint::new(Some(4000), Some(4255))),
+        size: None,
+    };
+    assert_eq!(vec![0x01], *BigInt::from(4001).to_aper(c).unwrap().bytes());
+    let mut d = aper::Decoder::new(b"\x01");
+    assert_eq!(BigInt::from(4001), BigInt::from_aper(&mut d, c).unwrap());
+
+    assert_eq!(Some(aper::EncodeError::InvalidValue), BigInt::from(4256).to_aper(c).err());
+    assert_eq!(Some(aper::EncodeError::InvalidValue), BigInt::from(u128::MAX).to_aper(c).err());
+}
+
+#[test]
+fn big_int_extensible() {
+    let c = Constraints {
+        value: Some(Constraint::new_extensible(Some(0), Some(7))),
+        size: None,
+    };
+    assert_eq!(vec![0x30], *BigInt::from(3).to_aper(c).unwrap().bytes());
+    let mut d = aper::Decoder::new(b"\x30");
+    assert_eq!(BigInt::from(3), BigInt::from_aper(&mut d, c).unwrap());
+
+    // -2^72 is outside of the root, so it's unconstrained
+    let x = BigInt::from_signed_bytes_be(&[0xff, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    let target: Vec<u8> = vec![0x80, 0x0a, 0xff, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    assert_eq!(target, *x.to_aper(c).unwrap().bytes());
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(x, BigInt::from_aper(&mut d, c).unwrap());
+
+    let target: Vec<u8> = vec![0x80, 0x09, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+    assert_eq!(target, *u64::MAX.to_aper(c).unwrap().bytes());
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(u64::MAX, u64::from_aper(&mut d, c).unwrap());
+}
+
+#[test]
+fn std_64() {
+    let target: Vec<u8> = vec![0x08, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+    assert_eq!(target, *u64::MAX.to_aper(UNCONSTRAINED).unwrap().bytes());
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(u64::MAX, u64::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    let target: Vec<u8> = vec![0x08, 0x80, 0, 0, 0, 0, 0, 0, 0];
+    assert_eq!(target, *i64::MIN.to_aper(UNCONSTRAINED).unwrap().bytes());
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(i64::MIN, i64::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    assert_eq!(vec![0x01, 0xd5], *(-43i64).to_aper(UNCONSTRAINED).unwrap().bytes());
+    assert_eq!(vec![0x01, 0x00], *0u64.to_aper(UNCONSTRAINED).unwrap().bytes());
+
+    // An unsigned value can't be negative
+    let target: Vec<u8> = vec![0x01, 0xff];
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(255, u64::from_aper(&mut d, UNCONSTRAINED).unwrap());
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(-1, i64::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn std_128() {
+    let mut target: Vec<u8> = vec![0x10];
+    target.extend_from_slice(&[0xff; 16]);
+    assert_eq!(target, *u128::MAX.to_aper(UNCONSTRAINED).unwrap().bytes());
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(u128::MAX, u128::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    // u128::MAX doesn't fit in an i128
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(-1, i128::from_aper(&mut d, UNCONSTRAINED).unwrap());
+    let target: Vec<u8> = vec![0x11, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                               0xff, 0xff, 0xff, 0xff];
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(Err(aper::DecodeError::MalformedInt), i128::from_aper(&mut d, UNCONSTRAINED));
+
+    let c = Constraints {
+        value: Some(Constraint::new(Some(-1), Some(2))),
+        size: None,
+    };
+    assert_eq!(vec![0xc0], *2i128.to_aper(c).unwrap().bytes());
+}
+
+#[test]
+fn uper_64() {
+    let enc = (-129i64).to_uper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x02, 0xff, 0x7f], *enc.bytes());
+    let mut d = uper::Decoder::new_unaligned(enc.bytes());
+    assert_eq!(-129, i64::from_uper(&mut d, UNCONSTRAINED).unwrap());
+
+    let c = Constraints {
+        value: Some(Constraint::new(Some(0), Some(1000))),
+        size: None,
+    };
+    let enc = 1000u64.to_uper(c).unwrap();
+    assert_eq!(vec![0xfa, 0x00], *enc.bytes());
+    let mut d = uper::Decoder::new_unaligned(enc.bytes());
+    assert_eq!(1000, u64::from_uper(&mut d, c).unwrap());
+}
@@ -1,6 +1,7 @@
 extern crate asn1;
-use asn1::BitString;
-use asn1::aper::{self, APerElement, Constraint, Constraints};
+use asn1::{BitString, ParseBitStringError};
+use asn1::aper::{self, APerElement, Constraint, Constraints, EncodeError, UNCONSTRAINED};
+use asn1::uper::{self, UPerElement};
 
 #[test]
 fn get_set() {
@@ -23,11 +24,11 @@ fn decode_padded() {
     let mut d = aper::Decoder::new(data);
     let b = BitString::from_aper(&mut d, Constraints {
         value: None,
-        size: Some(Constraint::new(None, Some(20))),
+        size: Some(Constraint::new(Some(20), Some(20))),
     }).unwrap();
     println!("{:?}", b);
     for i in 0..20 {
-        if i == 17 || i == 18 || i == 19 {
+        if i == 8 || i == 9 || i == 10 {
             assert_eq!(true, b.is_set(i));
         } else {
             assert_eq!(false, b.is_set(i));
@@ -42,11 +43,11 @@ fn decode_padded_small() {
     d.read(4).unwrap();
     let b = BitString::from_aper(&mut d, Constraints {
         value: None,
-        size: Some(Constraint::new(None, Some(4))),
+        size: Some(Constraint::new(Some(4), Some(4))),
     }).unwrap();
     println!("{:?}", b);
     for i in 0..4 {
-        if i == 1 || i == 2 || i == 3 {
+        if i == 0 || i == 1 || i == 2 {
             assert_eq!(true, b.is_set(i));
         } else {
             assert_eq!(false, b.is_set(i));
@@ -60,11 +61,11 @@ fn decode_unpadded() {
     let mut d = aper::Decoder::new(data);
     let b = BitString::from_aper(&mut d, Constraints {
         value: None,
-        size: Some(Constraint::new(None, Some(24))),
+        size: Some(Constraint::new(Some(24), Some(24))),
     }).unwrap();
     println!("{:?}", b);
     for i in 0..24 {
-        if i == 5 || i == 6 || i == 7 {
+        if i == 16 || i == 17 || i == 18 {
             assert_eq!(true, b.is_set(i));
         } else {
             assert_eq!(false, b.is_set(i));
@@ -74,10 +75,220 @@ fn decode_unpadded() {
 
 #[test]
 fn encode_padded_small() {
-    let bs = BitString::with_bytes_and_len(&vec![0x0e as u8], 4);
+    let bs = BitString::with_bytes_and_len(&[0xe0], 4);
     let target: Vec<u8> = vec![0xe0];
     assert_eq!(target, *bs.to_aper(Constraints{
         value: None,
-        size: Some(Constraint::new(None, Some(4))),
+        size: Some(Constraint::new(Some(4), Some(4))),
     }).unwrap().bytes());
 }
+
+#[test]
+fn cell_identity() {
+    // A 28-bit fixed size is octet-aligned, with no length
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(28), Some(28))),
+    };
+    let b = BitString::with_bytes_and_len(&[0x12, 0x34, 0x56, 0x70], 28);
+    let mut enc = true.to_aper(UNCONSTRAINED).unwrap();
+    enc.append(&b.to_aper(sz).unwrap()).unwrap();
+    assert_eq!(vec![0x80, 0x12, 0x34, 0x56, 0x70], *enc.bytes());
+    assert_eq!(4, enc.r_padding());
+
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert!(bool::from_aper(&mut d, UNCONSTRAINED).unwrap());
+    assert_eq!(b, BitString::from_aper(&mut d, sz).unwrap());
+
+    // Unaligned PER has no padding
+    let mut enc = true.to_uper(UNCONSTRAINED).unwrap();
+    enc.append(&b.to_uper(sz).unwrap()).unwrap();
+    assert_eq!(vec![0x89, 0x1a, 0x2b, 0x38], *enc.bytes());
+    let mut d = uper::Decoder::new_unaligned(enc.bytes());
+    assert!(bool::from_uper(&mut d, UNCONSTRAINED).unwrap());
+    assert_eq!(b, BitString::from_uper(&mut d, sz).unwrap());
+}
+
+#[test]
+fn transport_address() {
+    // TransportLayerAddress ::= BIT STRING (SIZE(1..160, ...))
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new_extensible(Some(1), Some(160))),
+    };
+
+    // 0 for a size in the root, an 8-bit length of 32 - 1, then the aligned bits of an IPv4 address
+    let ipv4 = BitString::with_bytes_and_len(&[192, 168, 0, 1], 32);
+    let enc = ipv4.to_aper(sz).unwrap();
+    assert_eq!(vec![0x0f, 0x80, 0xc0, 0xa8, 0x00, 0x01], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(ipv4, BitString::from_aper(&mut d, sz).unwrap());
+
+    let mut bytes: Vec<u8> = vec![192, 168, 0, 1];
+    bytes.extend((0..17).map(|i| i * 15));
+    for &n in &[1, 7, 128, 160, 161] {
+        let mut b = BitString::with_bytes_and_len(&bytes, n);
+        b.set(n - 1, true);
+
+        let enc = b.to_aper(sz).unwrap();
+        let mut d = aper::Decoder::new(enc.bytes());
+        assert_eq!(b, BitString::from_aper(&mut d, sz).unwrap());
+
+        let enc = b.to_uper(sz).unwrap();
+        let mut d = uper::Decoder::new_unaligned(enc.bytes());
+        assert_eq!(b, BitString::from_uper(&mut d, sz).unwrap());
+    }
+
+    // Outside of the root, a 1 and then an unconstrained length of 161
+    let b = BitString::with_bytes_and_len(&bytes, 161);
+    assert_eq!(vec![0x80, 0x80, 0xa1], b.to_aper(sz).unwrap().bytes()[..3].to_vec());
+}
+
+#[test]
+fn size_range() {
+    // A 5-bit length, then the bits on an octet boundary
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(None, Some(16))),
+    };
+    let b = BitString::with_bytes_and_len(&[0xe0], 4);
+    let enc = b.to_aper(sz).unwrap();
+    assert_eq!(vec![0x20, 0xe0], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(b, BitString::from_aper(&mut d, sz).unwrap());
+
+    // An empty bit string is only its length
+    let enc = BitString::with_len(0).to_aper(sz).unwrap();
+    assert_eq!(vec![0x00], *enc.bytes());
+    assert_eq!(3, enc.r_padding());
+
+    assert_eq!(Some(EncodeError::InvalidValue), BitString::with_len(17).to_aper(sz).err());
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(1), Some(160))),
+    };
+    assert_eq!(Some(EncodeError::InvalidValue), BitString::with_len(161).to_aper(sz).err());
+}
+
+#[test]
+fn unconstrained() {
+    // No size constraint is the same as an unbounded one
+    let b = BitString::with_bytes_and_len(&[0xe0], 4);
+    let enc = b.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x04, 0xe0], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(b, BitString::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn bit_order() {
+    // Bit 0 is the MSB of the first byte, and the first to be encoded
+    let mut b = BitString::with_len(10);
+    b.set(0, true);
+    b.set(9, true);
+    assert_eq!(&[0x80, 0x40], b.as_bytes());
+    assert_eq!(vec![0x0a, 0x80, 0x40], *b.to_uper(UNCONSTRAINED).unwrap().bytes());
+    assert_eq!(Some(true), b.get(9));
+    assert_eq!(None, b.get(10));
+    assert!(!b.is_set(10));
+
+    // Bits past the length are ignored
+    let b = BitString::with_bytes_and_len(&[0xff, 0xff], 12);
+    assert_eq!(&[0xff, 0xf0], b.as_bytes());
+    assert_eq!(BitString::with_bytes_and_len(&[0xff, 0xf0], 12), b);
+    assert_eq!(BitString::with_len(12), BitString::with_bytes_and_len(&[], 12));
+}
+
+#[test]
+fn conversions() {
+    // A 22-bit gNB ID
+    let b = BitString::from_uint(0x2a_bcde, 22);
+    assert_eq!(22, b.len());
+    assert_eq!(Some(0x2a_bcde), b.to_uint());
+    assert_eq!(&[0xaa, 0xf3, 0x78], b.as_bytes());
+    assert_eq!("AAF378", b.to_hex());
+    assert_eq!(None, BitString::with_len(65).to_uint());
+    assert_eq!(Some(0), BitString::new().to_uint());
+
+    let bools = vec![true, false, true, true];
+    let b = BitString::from(bools.clone());
+    assert_eq!(bools, b.to_bools());
+    assert_eq!(Some(0xb), b.to_uint());
+    assert_eq!(3, b.count_ones());
+
+    let b = BitString::from_bytes(&[0x01, 0x80]);
+    assert_eq!(16, b.len());
+    assert_eq!(vec![7, 8], b.iter().enumerate().filter(|&(_, v)| v).map(|(i, _)| i).collect::<Vec<_>>());
+}
+
+#[test]
+fn notation() {
+    let b: BitString = "'0101 1'B".parse().unwrap();
+    assert_eq!(BitString::from(vec![false, true, false, true, true]), b);
+    assert_eq!("'01011'B", b.to_string());
+    assert_eq!("BitString('01011'B)", format!("{:?}", b));
+
+    let h: BitString = "'A5F'H".parse().unwrap();
+    assert_eq!(BitString::from_uint(0xa5f, 12), h);
+    assert_eq!("A5F", h.to_hex());
+    assert_eq!(BitString::new(), "''B".parse().unwrap());
+
+    for s in &["0101", "'0102'B", "'0101'", "'a5'H", "'G'H", "'01'X", "'"] {
+        assert_eq!(Err(ParseBitStringError), s.parse::<BitString>(), "{}", s);
+    }
+}
+
+#[test]
+fn iter_slice_concat() {
+    let b = BitString::from_uint(0x3a5, 10);
+    assert_eq!(10, b.iter().len());
+    let rev: BitString = b.iter().rev().collect();
+    assert_eq!(BitString::from_uint(0x297, 10), rev);
+
+    assert_eq!(BitString::from_uint(0xe, 4), b.slice(..4));
+    assert_eq!(BitString::from_uint(0x25, 6), b.slice(4..));
+    assert_eq!(BitString::from_uint(0x1, 2), b.slice(8..=9));
+    assert_eq!(BitString::new(), b.slice(10..));
+
+    let mut c = b.slice(..3);
+    c.append(&b.slice(3..));
+    assert_eq!(b, c);
+    c.push(true);
+    c.extend(vec![false, true]);
+    assert_eq!(Some(0x3a5 << 3 | 0x5), c.to_uint());
+
+    let mut d = BitString::from_bytes(&[0xab]);
+    d.append(&BitString::from_bytes(&[0xcd]));
+    assert_eq!(BitString::from_bytes(&[0xab, 0xcd]), d);
+    d.set_num_bits(12);
+    assert_eq!(&[0xab, 0xc0], d.as_bytes());
+}
+
+#[test]
+#[should_panic]
+fn slice_out_of_bounds() {
+    BitString::with_len(4).slice(2..5);
+}
+
+#[test]
+fn bitwise() {
+    let a = BitString::from_uint(0x3a5, 10);
+    let b: BitString = "'1100110011'B".parse().unwrap();
+    assert_eq!(BitString::from_uint(0x321, 10), &a & &b);
+    assert_eq!(BitString::from_uint(0x3b7, 10), &a | &b);
+    assert_eq!(BitString::from_uint(0x096, 10), a.clone() ^ b.clone());
+    assert_eq!(BitString::from_uint(0x05a, 10), !&a);
+    assert_eq!(&[0x16, 0x80], (!a.clone()).as_bytes());
+
+    // A shorter operand is extended with 0 bits
+    let mut c = BitString::from_uint(0x3, 2);
+    c |= &a;
+    assert_eq!(a, c);
+    c &= BitString::from_uint(0x2, 2);
+    assert_eq!(BitString::from_uint(0x200, 10), c);
+
+    let mut set = ::std::collections::HashSet::new();
+    set.insert(a.clone());
+    assert!(set.contains(&BitString::from_uint(0x3a5, 10)));
+    assert!(!set.contains(&BitString::from_uint(0x3a5, 11)));
+}
@@ -0,0 +1,262 @@
+extern crate asn1;
+#[macro_use]
+extern crate asn1_derive;
+use asn1::{BmpString, GeneralString, GraphicString, Ia5String, NumericString, PrintableString, TeletexString,
+           UniversalString, Utf8String, VisibleString};
+use asn1::aper::{self, APerElement, Constraint, Constraints, DecodeError, EncodeError, UNCONSTRAINED};
+use asn1::uper::{self, UPerElement};
+
+#[derive(APerElement, Debug, PartialEq)]
+struct Record {
+    #[aper(from("ACGT"), size(4))]
+    seq: Ia5String,
+    #[aper(from('A'..'Z'), size(1..8))]
+    name: PrintableString,
+}
+
+#[test]
+fn ia5_unconstrained() {
+    let x = Ia5String::from("FOO");
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x03, 0x46, 0x4f, 0x4f], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, Ia5String::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    // 7 bits per character
+    let enc = x.to_uper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x03, 0x8d, 0x3e, 0x78], *enc.bytes());
+    let mut d = uper::Decoder::new_unaligned(enc.bytes());
+    assert_eq!(x, Ia5String::from_uper(&mut d, UNCONSTRAINED).unwrap());
+
+    assert_eq!("FOO", x.as_str());
+    assert_eq!("FOO", x.to_string());
+}
+
+#[test]
+fn numeric() {
+    // 4 bits per character, which are indexes since '9' doesn't fit
+    let x = NumericString::from("12 3");
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x04, 0x23, 0x04], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, NumericString::from_aper(&mut d, UNCONSTRAINED).unwrap());
+    assert_eq!(vec![0x04, 0x23, 0x04], *x.to_uper(UNCONSTRAINED).unwrap().bytes());
+
+    assert_eq!(Some(EncodeError::InvalidValue), NumericString::from("1a").to_aper(UNCONSTRAINED).err());
+}
+
+#[test]
+fn printable_size() {
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(1), Some(8))),
+    };
+    let x = PrintableString::from("Hi");
+    let enc = x.to_aper(sz).unwrap();
+    assert_eq!(vec![0x20, 0x48, 0x69], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, PrintableString::from_aper(&mut d, sz).unwrap());
+
+    let enc = x.to_uper(sz).unwrap();
+    assert_eq!(vec![0x32, 0x34, 0x80], *enc.bytes());
+    let mut d = uper::Decoder::new_unaligned(enc.bytes());
+    assert_eq!(x, PrintableString::from_uper(&mut d, sz).unwrap());
+
+    assert_eq!(Some(EncodeError::InvalidValue), PrintableString::from("too long!").to_aper(sz).err());
+    assert_eq!(Some(EncodeError::InvalidValue), PrintableString::from("a_b").to_aper(UNCONSTRAINED).err());
+
+    // '!' isn't printable
+    let mut d = aper::Decoder::new(b"\x01\x21");
+    assert_eq!(Err(DecodeError::InvalidCharacter(0x21)), PrintableString::from_aper(&mut d, UNCONSTRAINED));
+}
+
+#[test]
+fn permitted_alphabet() {
+    // 2 bits per character, as indexes into "ACGT"
+    let c = Constraints {
+        value: Some(Constraint::new_alphabet("TGCA")),
+        size: Some(Constraint::new(Some(4), Some(4))),
+    };
+    let x = Ia5String::from("GATC");
+    let mut enc = true.to_aper(UNCONSTRAINED).unwrap();
+    enc.append(&x.to_aper(c).unwrap()).unwrap();
+    assert_eq!(vec![0xc6, 0x80], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert!(bool::from_aper(&mut d, UNCONSTRAINED).unwrap());
+    assert_eq!(x, Ia5String::from_aper(&mut d, c).unwrap());
+
+    assert_eq!(Some(EncodeError::InvalidValue), Ia5String::from("GATX").to_aper(c).err());
+
+    // There's no character at index 3
+    let c = Constraints {
+        value: Some(Constraint::new_alphabet("ABC")),
+        size: Some(Constraint::new(Some(1), Some(1))),
+    };
+    let mut d = aper::Decoder::new(b"\xc0");
+    assert_eq!(Err(DecodeError::InvalidCharacter(3)), Ia5String::from_aper(&mut d, c));
+}
+
+#[test]
+fn permitted_range() {
+    // 32 characters, whose codes fit in the 8 bits of aligned PER but not the 5 bits of unaligned PER
+    let c = Constraints {
+        value: Some(Constraint::new(Some(0x20), Some(0x3f))),
+        size: Some(Constraint::new(Some(2), Some(2))),
+    };
+    let x = VisibleString::from("0?");
+    let enc = x.to_aper(c).unwrap();
+    assert_eq!(vec![0x30, 0x3f], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, VisibleString::from_aper(&mut d, c).unwrap());
+
+    let enc = x.to_uper(c).unwrap();
+    assert_eq!(vec![0x87, 0xc0], *enc.bytes());
+    let mut d = uper::Decoder::new_unaligned(enc.bytes());
+    assert_eq!(x, VisibleString::from_uper(&mut d, c).unwrap());
+}
+
+#[test]
+fn alignment() {
+    // Up to 16 bits of characters aren't octet-aligned, even if the size varies
+    let x = Ia5String::from("A");
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(1), Some(2))),
+    };
+    let mut enc = true.to_aper(UNCONSTRAINED).unwrap();
+    enc.append(&x.to_aper(sz).unwrap()).unwrap();
+    assert_eq!(vec![0x90, 0x40], *enc.bytes());
+
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(1), Some(3))),
+    };
+    let mut enc = true.to_aper(UNCONSTRAINED).unwrap();
+    enc.append(&x.to_aper(sz).unwrap()).unwrap();
+    assert_eq!(vec![0x80, 0x41], *enc.bytes());
+}
+
+#[test]
+fn fragmented() {
+    let x = Ia5String((0..16387).map(|i| (b'a' + (i % 26) as u8) as char).collect());
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(1 + 16384 + 1 + 3, enc.bytes().len());
+    assert_eq!(vec![0xc1, 0x61], enc.bytes()[..2].to_vec());
+    assert_eq!(0x03, enc.bytes()[16385]);
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, Ia5String::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    let enc = x.to_uper(UNCONSTRAINED).unwrap();
+    let mut d = uper::Decoder::new_unaligned(enc.bytes());
+    assert_eq!(x, Ia5String::from_uper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn derive() {
+    let x = Record {
+        seq: Ia5String::from("GATC"),
+        name: PrintableString::from("HI"),
+    };
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x8d, 0x20, 0x48, 0x49], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, Record::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    // Lower case isn't in the permitted alphabet of `name`
+    let y = Record {
+        seq: Ia5String::from("GATC"),
+        name: PrintableString::from("Hi"),
+    };
+    assert_eq!(Some(EncodeError::InvalidValue), y.to_aper(UNCONSTRAINED).err());
+}
+
+#[test]
+fn bmp() {
+    let x = BmpString::from("Hé");
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x02, 0x00, 0x48, 0x00, 0xe9], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, BmpString::from_aper(&mut d, UNCONSTRAINED).unwrap());
+    assert_eq!(vec![0x02, 0x00, 0x48, 0x00, 0xe9], *x.to_uper(UNCONSTRAINED).unwrap().bytes());
+
+    // A single bit per character, as an index
+    let c = Constraints {
+        value: Some(Constraint::new_alphabet("01")),
+        size: Some(Constraint::new(Some(2), Some(2))),
+    };
+    let x = BmpString::from("10");
+    let enc = x.to_aper(c).unwrap();
+    assert_eq!(vec![0x80], *enc.bytes());
+    assert_eq!(6, enc.r_padding());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, BmpString::from_aper(&mut d, c).unwrap());
+
+    // A lone surrogate isn't a char
+    let mut d = aper::Decoder::new(b"\x01\xd8\x00");
+    assert_eq!(Err(DecodeError::InvalidCharacter(0xd800)), BmpString::from_aper(&mut d, UNCONSTRAINED));
+}
+
+#[test]
+fn universal() {
+    let x = UniversalString::from("\u{1f600}");
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x01, 0x00, 0x01, 0xf6, 0x00], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, UniversalString::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    let enc = x.to_uper(UNCONSTRAINED).unwrap();
+    let mut d = uper::Decoder::new_unaligned(enc.bytes());
+    assert_eq!(x, UniversalString::from_uper(&mut d, UNCONSTRAINED).unwrap());
+
+    let mut d = aper::Decoder::new(b"\x01\x00\x11\x00\x00");
+    assert_eq!(Err(DecodeError::InvalidCharacter(0x110000)), UniversalString::from_aper(&mut d, UNCONSTRAINED));
+}
+
+#[test]
+fn utf8() {
+    // A length in octets, and constraints don't apply
+    let x = Utf8String::from("né");
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x03, 0x6e, 0xc3, 0xa9], *enc.bytes());
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(2), Some(2))),
+    };
+    assert_eq!(*enc.bytes(), *x.to_aper(sz).unwrap().bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, Utf8String::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    // Not octet-aligned in unaligned PER
+    let mut enc = true.to_uper(UNCONSTRAINED).unwrap();
+    enc.append(&x.to_uper(UNCONSTRAINED).unwrap()).unwrap();
+    assert_eq!(vec![0x81, 0xb7, 0x61, 0xd4, 0x80], *enc.bytes());
+    let mut d = uper::Decoder::new_unaligned(enc.bytes());
+    assert!(bool::from_uper(&mut d, UNCONSTRAINED).unwrap());
+    assert_eq!(x, Utf8String::from_uper(&mut d, UNCONSTRAINED).unwrap());
+
+    let mut d = aper::Decoder::new(b"\x02\xc3\x28");
+    assert_eq!(Err(DecodeError::MalformedString), Utf8String::from_aper(&mut d, UNCONSTRAINED));
+}
+
+#[test]
+fn iso_2022() {
+    // Escape sequences are kept as they are
+    let x = GeneralString::from("\x1b(Bkrbtgt");
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x09, 0x1b, 0x28, 0x42, 0x6b], enc.bytes()[..5].to_vec());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, GeneralString::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    let x = GraphicString::from("abc");
+    let mut d = aper::Decoder::new(b"\x03abc");
+    assert_eq!(x, GraphicString::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    let x = TeletexString::from("abc");
+    let enc = x.to_uper(UNCONSTRAINED).unwrap();
+    let mut d = uper::Decoder::new_unaligned(enc.bytes());
+    assert_eq!(x, TeletexString::from_uper(&mut d, UNCONSTRAINED).unwrap());
+
+    // An 8-bit character from a G1 set
+    let mut d = aper::Decoder::new(b"\x01\xe9");
+    assert_eq!(Err(DecodeError::MalformedString), TeletexString::from_aper(&mut d, UNCONSTRAINED));
+}
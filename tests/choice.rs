@@ -28,7 +28,7 @@ impl APerElement for Foo {
             0 => {
                 let bs = BitString::from_aper(decoder , Constraints {
                     value: None,
-                    size: Some(Constraint::new(None, Some(4))),
+                    size: Some(Constraint::new(Some(4), Some(4))),
                 });
                 if bs.is_err() {
                     Err(bs.err().unwrap())
@@ -56,7 +56,7 @@ impl APerElement for Foo {
                     Ok(Foo::Baz{ a: a.unwrap(), b: b.unwrap(), })
                 }
             }
-            _ => Err(aper::DecodeError::InvalidChoice)
+            c => Err(aper::DecodeError::InvalidChoice(c))
         }
     }
 
@@ -67,12 +67,15 @@ impl APerElement for Foo {
                 enc.append(&encode_int(0, Some(0), Some(2)).unwrap()).unwrap();
                 enc.append(&a.to_aper(Constraints {
                     value: None,
-                    size: Some(Constraint::new(None, Some(4))),
+                    size: Some(Constraint::new(Some(4), Some(4))),
                 }).unwrap()).unwrap();
             },
             Foo::Bar{ref a} => {
                 enc.append(&encode_int(1, Some(0), Some(2)).unwrap()).unwrap();
-                enc.append(&a.to_aper(UNCONSTRAINED).unwrap()).unwrap();
+                enc.append(&a.to_aper(Constraints {
+                    value: None,
+                    size: Some(Constraint::new(None, Some(3))),
+                }).unwrap()).unwrap();
             },
             Foo::Baz{ref a, ref b} => {
                 enc.append(&encode_int(2, Some(0), Some(2)).unwrap()).unwrap();
@@ -86,7 +89,7 @@ impl APerElement for Foo {
 
 #[test]
 fn encode_foo() {
-    let x: Foo = Foo::Foo{ a: BitString::with_bytes_and_len(&vec![0x0e], 4), };
+    let x: Foo = Foo::Foo{ a: BitString::with_bytes_and_len(&[0xe0], 4), };
     let target: Vec<u8> = vec![0x1c];
     assert_eq!(target, *x.to_aper(UNCONSTRAINED).unwrap().bytes());
 }
@@ -94,14 +97,14 @@ fn encode_foo() {
 #[test]
 fn encode_bar() {
     let x: Foo = Foo::Bar{ a: vec![0x46, 0x4f, 0x4f], };
-    let target: Vec<u8> = vec![32, 104, 201, 233, 224];
+    let target: Vec<u8> = vec![0x38, 0x46, 0x4f, 0x4f];
     assert_eq!(target, *x.to_aper(UNCONSTRAINED).unwrap().bytes());
 }
 
 #[test]
 fn encode_baz() {
     let x: Foo = Foo::Baz{ a: 42, b: 300 };
-    let target: Vec<u8> = vec![69, 64, 37, 128];
+    let target: Vec<u8> = vec![0x40, 0x2a, 0x01, 0x2c];
     assert_eq!(target, *x.to_aper(UNCONSTRAINED).unwrap().bytes());
 }
 
@@ -114,7 +117,7 @@ fn decode_foo() {
     match f {
         Foo::Foo{a: x} => {
             for i in 0..4 {
-                if i == 1 || i == 2 || i == 3 {
+                if i == 0 || i == 1 || i == 2 {
                     assert_eq!(true, x.is_set(i));
                 } else {
                     assert_eq!(false, x.is_set(i));
@@ -127,7 +130,7 @@ fn decode_foo() {
 
 #[test]
 fn decode_bar() {
-    let data = b"\x01\x03\x46\x4f\x4f";
+    let data = b"\x01\xc0\x46\x4f\x4f";
     let mut d = aper::Decoder::new(data);
     d.read(5).unwrap(); // strip left-padding
     let f = Foo::from_aper(&mut d, UNCONSTRAINED).unwrap();
@@ -0,0 +1,85 @@
+extern crate asn1;
+use asn1::BitString;
+use asn1::aper::{self, APerElement, Constraint, Constraints, Encoding, UNCONSTRAINED};
+
+#[test]
+fn read_bits() {
+    let data = b"\xde\xad\xbe\xef\x00\x00\x00\x01\x80";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(0, d.read_bits(0).unwrap());
+    assert_eq!(0x6, d.read_bits(3).unwrap());
+    assert_eq!(0x1ead, d.read_bits(13).unwrap());
+    assert_eq!(0xbe_ef00_0000_0180 >> 1, d.read_bits(55).unwrap());
+    assert_eq!(Err(aper::DecodeError::NotEnoughBits), d.read_bits(2));
+    assert_eq!(0, d.read_bits(1).unwrap());
+
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(0xdead_beef_0000_0001, d.read_bits(64).unwrap());
+    let mut d = aper::Decoder::new(data);
+    d.read(1).unwrap();
+    assert_eq!(0x5ead_beef_0000_0001, d.read_bits(63).unwrap());
+}
+
+#[test]
+fn read_to_vec() {
+    let data = b"\xff\xf3\x5a";
+    let mut content: Vec<u8> = Vec::new();
+    let mut d = aper::Decoder::new(data);
+    d.read_to_vec(&mut content, 12).unwrap();
+    assert_eq!(vec![0xff, 0x0f], content);
+
+    // Off an octet boundary, and the trailing bits aren't mixed into the last byte
+    let mut content: Vec<u8> = Vec::new();
+    let mut d = aper::Decoder::new(data);
+    d.read(4).unwrap();
+    d.read_to_vec(&mut content, 17).unwrap();
+    assert_eq!(vec![0xff, 0x35, 0x01], content);
+    assert_eq!(0x2, d.read(3).unwrap());
+
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(Err(aper::DecodeError::NotEnoughBits), d.read_to_vec(&mut content, 25));
+}
+
+#[test]
+fn large_open_type() {
+    let bytes: Vec<u8> = (0..1 << 20).map(|i| i as u8).collect();
+    let mut enc = true.to_aper(UNCONSTRAINED).unwrap();
+    enc.append(&aper::encode_open_type(&Encoding::with_bytes(bytes.clone())).unwrap()).unwrap();
+
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert!(bool::from_aper(&mut d, UNCONSTRAINED).unwrap());
+    assert_eq!(bytes, d.decode_open_type().unwrap());
+}
+
+#[test]
+fn large_bit_string() {
+    // A 1 bit, then a fragmented BIT STRING of 64K + 12 bits
+    let constraints = Constraints {
+        value: None,
+        size: Some(Constraint::new(None, None)),
+    };
+    let mut data: Vec<u8> = vec![0x80, 0xc4];
+    data.extend(vec![0xa5; 8192]);
+    data.extend(vec![0x0c, 0xff, 0xf0]);
+
+    let mut d = aper::Decoder::new(&data);
+    assert!(bool::from_aper(&mut d, UNCONSTRAINED).unwrap());
+    let b = BitString::from_aper(&mut d, constraints).unwrap();
+    let n = b.get_num_bits();
+    assert_eq!(65536 + 12, n);
+    assert_eq!(Some(0xa5), b.slice(..8).to_uint());
+    // The last bits of the first fragment, then the second
+    assert_eq!(Some(0x5fff), b.slice(n - 16..).to_uint());
+}
+
+#[test]
+fn large_octets() {
+    let bytes: Vec<u8> = (0..1 << 16).map(|i| (i * 7) as u8).collect();
+    let constraints = Constraints {
+        value: None,
+        size: Some(Constraint::new(None, None)),
+    };
+    let enc = bytes.to_aper(constraints).unwrap();
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(bytes, Vec::<u8>::from_aper(&mut d, constraints).unwrap());
+}
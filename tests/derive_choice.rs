@@ -0,0 +1,158 @@
+extern crate asn1;
+#[macro_use]
+extern crate asn1_derive;
+use asn1::BitString;
+use asn1::aper::{self, APerElement, UNCONSTRAINED};
+use asn1::uper::{self, UPerElement};
+
+#[derive(APerElement, Debug)]
+#[aper(extensible)]
+enum Msg {
+    Foo {
+        #[aper(size(4))]
+        a: BitString,
+    },
+    Bar {
+        #[aper(size(..3))]
+        a: Vec<u8>,
+    },
+    Baz { a: u8, b: u16 },
+}
+
+#[derive(APerElement, Debug, PartialEq)]
+enum Bar {
+    A,
+    B(bool),
+    C(Option<u8>),
+    #[aper(extension)]
+    D(u16),
+    #[aper(extension)]
+    E { a: bool, b: bool },
+}
+
+#[derive(APerElement, Debug, PartialEq)]
+struct Inner {
+    a: bool,
+    b: u16,
+}
+
+#[derive(APerElement, Debug, PartialEq)]
+enum Outer {
+    A(bool),
+    #[aper(extension)]
+    B(Inner),
+}
+
+#[derive(APerElement, Debug, PartialEq)]
+enum Single {
+    A(#[aper(value(0..7))] u8),
+}
+
+#[derive(APerElement, Debug, PartialEq)]
+enum Closed {
+    A,
+    B,
+    C,
+}
+
+#[test]
+fn encode_foo() {
+    let x: Msg = Msg::Foo { a: BitString::with_bytes_and_len(&[0xe0], 4) };
+    let target: Vec<u8> = vec![0x1c];
+    assert_eq!(target, *x.to_aper(UNCONSTRAINED).unwrap().bytes());
+}
+
+#[test]
+fn encode_bar() {
+    let x: Msg = Msg::Bar { a: vec![0x46, 0x4f, 0x4f] };
+    // 0, 01, then a 2-bit length of 3 and the aligned items
+    let target: Vec<u8> = vec![0x38, 0x46, 0x4f, 0x4f];
+    assert_eq!(target, *x.to_aper(UNCONSTRAINED).unwrap().bytes());
+}
+
+#[test]
+fn encode_baz() {
+    let x: Msg = Msg::Baz { a: 42, b: 300 };
+    let target: Vec<u8> = vec![0x40, 0x2a, 0x01, 0x2c];
+    assert_eq!(target, *x.to_aper(UNCONSTRAINED).unwrap().bytes());
+}
+
+#[test]
+fn decode_baz() {
+    let data = b"\x40\x2a\x01\x2c";
+    let mut d = aper::Decoder::new(data);
+    match Msg::from_aper(&mut d, UNCONSTRAINED).unwrap() {
+        Msg::Baz { a, b } => {
+            assert_eq!(42, a);
+            assert_eq!(300, b);
+        }
+        f => panic!("unexpected alternative {:?}", f),
+    }
+}
+
+#[test]
+fn root_alternatives() {
+    // extension marker 0, index 01, bool 1
+    let x = Bar::B(true);
+    let target: Vec<u8> = vec![0x30];
+    assert_eq!(target, *x.to_aper(UNCONSTRAINED).unwrap().bytes());
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(x, Bar::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    // extension marker 0, index 10, preamble 1, padding, u8
+    let x = Bar::C(Some(0xff));
+    let target: Vec<u8> = vec![0x50, 0xff];
+    assert_eq!(target, *x.to_aper(UNCONSTRAINED).unwrap().bytes());
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(x, Bar::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn extension_alternatives() {
+    // extension marker 1, normally small index 0, open type of length 2
+    let x = Bar::D(0x1234);
+    let target: Vec<u8> = vec![0x80, 0x02, 0x12, 0x34];
+    assert_eq!(target, *x.to_aper(UNCONSTRAINED).unwrap().bytes());
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(x, Bar::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    let x = Bar::E { a: true, b: false };
+    let target: Vec<u8> = vec![0x81, 0x01, 0x80];
+    assert_eq!(target, *x.to_aper(UNCONSTRAINED).unwrap().bytes());
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(x, Bar::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn unaligned_extension_alternative() {
+    // The open type holds the unaligned encoding of the alternative, i.e. 1 then 0x1234 without padding
+    let x = Outer::B(Inner { a: true, b: 0x1234 });
+    let target: Vec<u8> = vec![0x80, 0x03, 0x89, 0x1a, 0x00];
+    assert_eq!(target, *x.to_uper(UNCONSTRAINED).unwrap().bytes());
+    let mut d = uper::Decoder::new_unaligned(&target);
+    assert_eq!(x, Outer::from_uper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn unknown_extension() {
+    let data = b"\x82\x01\x00";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(aper::DecodeError::InvalidChoice(5), Bar::from_aper(&mut d, UNCONSTRAINED).err().unwrap());
+}
+
+#[test]
+fn unknown_root_alternative() {
+    let data = b"\xc0";
+    let mut d = aper::Decoder::new(data);
+    // An index beyond the root can't come from any version of the type
+    assert_eq!(aper::DecodeError::MalformedInt, Closed::from_aper(&mut d, UNCONSTRAINED).err().unwrap());
+}
+
+#[test]
+fn single_alternative() {
+    let x = Single::A(5);
+    let target: Vec<u8> = vec![0xa0];
+    assert_eq!(target, *x.to_aper(UNCONSTRAINED).unwrap().bytes());
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(x, Single::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
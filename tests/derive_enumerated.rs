@@ -0,0 +1,114 @@
+extern crate asn1;
+#[macro_use]
+extern crate asn1_derive;
+use asn1::{Enumerated, EnumeratedValue};
+use asn1::aper::{self, APerElement, UNCONSTRAINED};
+
+// Color ::= ENUMERATED { red (0), green (3), ..., blue (7), cyan }
+#[derive(APerElement, Debug, Clone, Copy, PartialEq)]
+#[aper(enumerated)]
+enum Color {
+    Red = 0,
+    Green = 3,
+    #[aper(extension)]
+    Blue = 7,
+    #[aper(extension)]
+    Cyan = 8,
+}
+
+// An older version of `Color`
+#[derive(APerElement, Debug, PartialEq)]
+#[aper(enumerated, extensible)]
+enum OldColor {
+    Red,
+    Green,
+}
+
+#[derive(APerElement, Debug, PartialEq)]
+#[aper(enumerated)]
+enum Fixed {
+    A,
+    B,
+    C,
+    D,
+    E,
+}
+
+// Level ::= ENUMERATED { high (2), low (-1), mid (0) }
+#[derive(APerElement, Debug, PartialEq)]
+#[aper(enumerated)]
+enum Level {
+    High = 2,
+    Low = -1,
+    Mid,
+}
+
+#[derive(APerElement, Debug, PartialEq)]
+struct Foo {
+    a: Fixed,
+    #[aper(default = Color::Green)]
+    b: Color,
+}
+
+#[test]
+fn enumerated_indexes() {
+    assert_eq!(2, Color::NUM_ROOT);
+    assert_eq!(1, Color::Green.index());
+    assert_eq!(3, Color::Cyan.index());
+    assert_eq!(Some(Color::Blue), Color::from_index(2));
+    assert_eq!(None, Color::from_index(4));
+}
+
+#[test]
+fn enumerated_encode() {
+    // extension bit 0, index 1
+    assert_eq!(vec![0x40], *Color::Green.to_aper(UNCONSTRAINED).unwrap().bytes());
+    // extension bit 1, normally small 0000001
+    assert_eq!(vec![0x81], *Color::Cyan.to_aper(UNCONSTRAINED).unwrap().bytes());
+    // index 100
+    assert_eq!(vec![0x80], *Fixed::E.to_aper(UNCONSTRAINED).unwrap().bytes());
+
+    // 01 followed by 1 0000001
+    let data = b"\x60\x40";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(Color::Green, Color::from_aper(&mut d, UNCONSTRAINED).unwrap());
+    assert_eq!(Color::Cyan, Color::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn enumerated_unknown_additions() {
+    let data = b"\x81";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(Err(aper::DecodeError::InvalidEnumerated(3)), OldColor::from_aper(&mut d, UNCONSTRAINED));
+
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(EnumeratedValue::Unknown(1),
+               EnumeratedValue::<OldColor>::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn enumerated_in_sequence() {
+    let x = Foo {
+        a: Fixed::B,
+        b: Color::Blue,
+    };
+    // preamble 1, a = 001, b = 1 0000000
+    let target: Vec<u8> = vec![0x98, 0x00];
+    assert_eq!(target, *x.to_aper(UNCONSTRAINED).unwrap().bytes());
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(x, Foo::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn enumerated_value_order() {
+    // The indexes follow the values, so low is 0, mid is 1 and high is 2
+    assert_eq!(0, Level::Low.index());
+    assert_eq!(1, Level::Mid.index());
+    assert_eq!(2, Level::High.index());
+    let target: Vec<u8> = vec![0x80];
+    assert_eq!(target, *Level::High.to_aper(UNCONSTRAINED).unwrap().bytes());
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(Level::High, Level::from_aper(&mut d, UNCONSTRAINED).unwrap());
+    let mut d = aper::Decoder::new(b"\x40");
+    assert_eq!(Level::Mid, Level::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
@@ -0,0 +1,252 @@
+extern crate asn1;
+#[macro_use]
+extern crate asn1_derive;
+use asn1::{BitString, SetOf};
+use asn1::aper::{self, APerElement, UNCONSTRAINED};
+use asn1::uper::{self, UPerElement};
+
+#[derive(APerElement, Debug)]
+struct Foo {
+    #[aper(size(4))]
+    pub a: BitString,
+    #[aper(size(..3))]
+    pub b: Vec<u8>,
+    // here the "value" constraint is a constraint on the size of each element
+    #[aper(value(4), size(..2))]
+    pub c: Vec<BitString>,
+}
+
+#[derive(APerElement, Debug, PartialEq)]
+struct Bar {
+    #[aper(value(0..7))]
+    pub a: u8,
+    #[aper(value(0..255))]
+    pub b: Option<u16>,
+    #[aper(value(0..3), default = 2)]
+    pub c: u8,
+    pub d: bool,
+}
+
+#[derive(APerElement, Debug, PartialEq)]
+struct Baz(#[aper(value(500..503))] i16, Option<bool>);
+
+#[derive(APerElement, Debug, PartialEq)]
+struct Empty;
+
+#[derive(APerElement, Debug, PartialEq)]
+#[aper(extensible)]
+struct Ext {
+    a: Option<bool>,
+}
+
+// A newer version of `Ext`
+#[derive(APerElement, Debug, PartialEq)]
+struct ExtV2 {
+    a: Option<bool>,
+    #[aper(extension, value(0..255))]
+    b: Option<u8>,
+    #[aper(extension)]
+    c: Option<ExtGroup>,
+}
+
+#[derive(APerElement, Debug, PartialEq)]
+struct ExtGroup {
+    d: bool,
+    e: Option<bool>,
+}
+
+#[derive(APerElement, Debug, PartialEq)]
+struct ExtPair {
+    a: bool,
+    b: u16,
+}
+
+#[derive(APerElement, Debug, PartialEq)]
+struct ExtV3 {
+    a: bool,
+    #[aper(extension)]
+    b: Option<ExtPair>,
+}
+
+#[derive(APerElement, Debug, PartialEq)]
+struct Lists {
+    #[aper(size(1..4, ...))]
+    a: Vec<bool>,
+    #[aper(size(2))]
+    b: Vec<bool>,
+}
+
+#[test]
+fn encode_foo() {
+    let x = Foo {
+        a: BitString::with_bytes_and_len(&[0xe0], 4),
+        b: vec![0x46, 0x4f, 0x4f],
+        c: vec![
+            BitString::with_bytes_and_len(&[0xe0], 4),
+            BitString::with_bytes_and_len(&[0xe0], 4),
+        ],
+    };
+    // The lengths of `b` and `c` are constrained whole numbers of 2 bits
+    let target: Vec<u8> = vec![0xec, 0x46, 0x4f, 0x4f, 0xbb, 0x80];
+    assert_eq!(target, *x.to_aper(UNCONSTRAINED).unwrap().bytes());
+}
+
+#[test]
+fn decode_foo() {
+    let data = b"\xec\x46\x4f\x4f\xbb\x80";
+    let mut d = aper::Decoder::new(data);
+    let f = Foo::from_aper(&mut d, UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x46, 0x4f, 0x4f], f.b);
+    assert_eq!(2, f.c.len());
+    for i in 0..4 {
+        assert_eq!(i < 3, f.a.is_set(i));
+        assert_eq!(i < 3, f.c[1].is_set(i));
+    }
+}
+
+#[test]
+fn optional_and_default_absent() {
+    let x = Bar {
+        a: 5,
+        b: None,
+        c: 2,
+        d: true,
+    };
+    // preamble 00, a = 101, d = 1
+    let target: Vec<u8> = vec![0x2c];
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(target, *enc.bytes());
+
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(x, Bar::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn optional_and_default_present() {
+    let x = Bar {
+        a: 5,
+        b: Some(0xa5),
+        c: 1,
+        d: false,
+    };
+    // preamble 11, a = 101, padding, b = 1010 0101, c = 01, d = 0
+    let target: Vec<u8> = vec![0xe8, 0xa5, 0x40];
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(target, *enc.bytes());
+
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(x, Bar::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn tuple_struct() {
+    let x = Baz(502, Some(true));
+    // preamble 1, 0 = 10, 1 = 1
+    let target: Vec<u8> = vec![0xd0];
+    assert_eq!(target, *x.to_aper(UNCONSTRAINED).unwrap().bytes());
+
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(x, Baz::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn unit_struct() {
+    assert_eq!(0, Empty.to_aper(UNCONSTRAINED).unwrap().bytes().len());
+    let mut d = aper::Decoder::new(b"");
+    assert_eq!(Empty, Empty::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn extensible_struct() {
+    let x = Ext { a: Some(true) };
+    // extension bit 0, preamble 1, a = 1
+    let target: Vec<u8> = vec![0x60];
+    assert_eq!(target, *x.to_aper(UNCONSTRAINED).unwrap().bytes());
+
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(x, Ext::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn extension_additions() {
+    let x = ExtV2 {
+        a: None,
+        b: Some(5),
+        c: Some(ExtGroup { d: true, e: None }),
+    };
+    // extension bit 1, preamble 0, bitmap length 0000001, bitmap 11, padding, b = 00000001 00000101,
+    // c = 00000001 01000000
+    let target: Vec<u8> = vec![0x80, 0xe0, 0x01, 0x05, 0x01, 0x40];
+    assert_eq!(target, *x.to_aper(UNCONSTRAINED).unwrap().bytes());
+
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(x, ExtV2::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    // The additions are skipped by the older version
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(Ext { a: None }, Ext::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    // Without any additions present, the encoding is the same as the older version's
+    let x = ExtV2 {
+        a: Some(false),
+        b: None,
+        c: None,
+    };
+    let target: Vec<u8> = vec![0x40];
+    assert_eq!(target, *x.to_aper(UNCONSTRAINED).unwrap().bytes());
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(x, ExtV2::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn unaligned_extension_additions() {
+    // extension bit 1, a = 1, bitmap length 0000000, bitmap 1, length 00000011, then the unaligned addition
+    // 1 00010010 00110100 and padding
+    let x = ExtV3 {
+        a: true,
+        b: Some(ExtPair { a: true, b: 0x1234 }),
+    };
+    let target: Vec<u8> = vec![0xc0, 0x40, 0xe2, 0x46, 0x80, 0x00];
+    assert_eq!(target, *x.to_uper(UNCONSTRAINED).unwrap().bytes());
+    let mut d = uper::Decoder::new_unaligned(&target);
+    assert_eq!(x, ExtV3::from_uper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn unaligned_derived_components() {
+    // length 00000010, then 1 00000000 00000001 and 0 11111111 11111111 without padding
+    let x = vec![ExtPair { a: true, b: 1 }, ExtPair { a: false, b: 0xffff }];
+    let target: Vec<u8> = vec![0x02, 0x80, 0x00, 0xbf, 0xff, 0xc0];
+    assert_eq!(target, *x.to_uper(UNCONSTRAINED).unwrap().bytes());
+    let mut d = uper::Decoder::new_unaligned(&target);
+    assert_eq!(x, Vec::<ExtPair>::from_uper(&mut d, UNCONSTRAINED).unwrap());
+
+    let x = SetOf(x);
+    assert_eq!(target, *x.to_uper(UNCONSTRAINED).unwrap().bytes());
+    let mut d = uper::Decoder::new_unaligned(&target);
+    assert_eq!(x, SetOf::<ExtPair>::from_uper(&mut d, UNCONSTRAINED).unwrap());
+
+    let x = Some(ExtPair { a: true, b: 0x1234 });
+    assert_eq!(vec![0x89, 0x1a, 0x00], *x.to_uper(UNCONSTRAINED).unwrap().bytes());
+    let mut d = uper::Decoder::new_unaligned(b"\x89\x1a\x00");
+    assert_eq!(x, Option::<ExtPair>::from_uper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn extensible_size() {
+    // 1 for a size outside of the root, an unconstrained length of 5, the items, then `b` without a length
+    let x = Lists {
+        a: vec![true; 5],
+        b: vec![false, true],
+    };
+    let target: Vec<u8> = vec![0x80, 0x05, 0xfa];
+    assert_eq!(target, *x.to_aper(UNCONSTRAINED).unwrap().bytes());
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(x, Lists::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    let x = Lists {
+        a: vec![true],
+        b: vec![true; 3],
+    };
+    assert_eq!(Some(aper::EncodeError::InvalidValue), x.to_aper(UNCONSTRAINED).err());
+}
@@ -0,0 +1,143 @@
+extern crate asn1;
+use asn1::OpenType;
+use asn1::aper::{self, APerElement, Constraint, Constraints, Encoder, Encoding, EncodeError, UNCONSTRAINED};
+use asn1::uper::UPerElement;
+
+// An element that only implements `to_aper`, so it's written with the default `write_aper`
+struct Legacy(u16);
+
+impl APerElement for Legacy {
+    const CONSTRAINTS: Constraints = UNCONSTRAINED;
+
+    fn from_aper(decoder: &mut aper::Decoder, constraints: Constraints) -> Result<Self, aper::DecodeError> {
+        bool::from_aper(decoder, UNCONSTRAINED)?;
+        Ok(Legacy(u16::from_aper(decoder, constraints)?))
+    }
+
+    fn to_aper(&self, constraints: Constraints) -> Result<Encoding, EncodeError> {
+        // a 1-bit marker, then the octet-aligned u16
+        let mut enc = true.to_aper(UNCONSTRAINED)?;
+        enc.append(&self.0.to_aper(constraints)?)?;
+        Ok(enc)
+    }
+}
+
+#[test]
+fn write_bits() {
+    let mut enc = Encoder::new();
+    assert!(enc.is_empty());
+    enc.write_bits(0b101, 3);
+    enc.write_bits(0x1ff, 9);
+    enc.write_bits(0, 0);
+    enc.write_bits(0xdead_beef_0000_0001, 64);
+    assert_eq!(76, enc.len());
+    assert_eq!(4, enc.r_padding());
+    let target: Vec<u8> = vec![0xbf, 0xfd, 0xea, 0xdb, 0xee, 0xf0, 0x00, 0x00, 0x00, 0x10];
+    assert_eq!(target, *enc.into_encoding().bytes());
+}
+
+#[test]
+fn write_bytes() {
+    let mut enc = Encoder::new();
+    enc.write_bytes(&[0xab]);
+    enc.write_bits(1, 1);
+    enc.write_bytes(&[0xff, 0x00]);
+    // Bits beyond the first 5 are ignored
+    enc.write_bits_from(&[0xff], 5);
+    assert_eq!(vec![0xab, 0xff, 0x80, 0x7c], *enc.bytes());
+    assert_eq!(2, enc.r_padding());
+
+    enc.write_aligned_bytes(&[0x2b]);
+    assert_eq!(vec![0xab, 0xff, 0x80, 0x7c, 0x2b], *enc.bytes());
+}
+
+#[test]
+fn unaligned() {
+    let mut enc = Encoder::new_unaligned();
+    assert!(!enc.is_aligned());
+    enc.write_bits(1, 1);
+    enc.align();
+    enc.encode_length(1).unwrap();
+    enc.write_aligned_bytes(&[0xff]);
+    assert_eq!(vec![0x80, 0xff, 0x80], *enc.bytes());
+    assert_eq!(7, enc.r_padding());
+}
+
+#[test]
+fn matches_to_aper() {
+    let v: Vec<i32> = vec![i32::MIN, 0, 43, i32::MAX];
+    let mut enc = Encoder::new();
+    enc.reserve(32);
+    true.write_aper(&mut enc, UNCONSTRAINED).unwrap();
+    v.write_aper(&mut enc, UNCONSTRAINED).unwrap();
+
+    let mut target = true.to_aper(UNCONSTRAINED).unwrap();
+    target.append(&v.to_aper(UNCONSTRAINED).unwrap()).unwrap();
+    assert_eq!(*target.bytes(), *enc.bytes());
+    assert_eq!(target.r_padding(), enc.r_padding());
+
+    let mut enc = Encoder::new_unaligned();
+    v.write_uper(&mut enc, UNCONSTRAINED).unwrap();
+    assert_eq!(*v.to_uper(UNCONSTRAINED).unwrap().bytes(), *enc.bytes());
+}
+
+#[test]
+fn fragmented() {
+    // 64K + 3 items: one fragment of 64K, then a length determinant of 3
+    let v: Vec<bool> = vec![true; 65539];
+    let constraints = Constraints {
+        value: None,
+        size: Some(Constraint::new(None, None)),
+    };
+    let mut enc = Encoder::new();
+    enc.write_bits(0, 1);
+    v.write_aper(&mut enc, constraints).unwrap();
+    let bytes = enc.into_encoding().bytes().clone();
+    assert_eq!(2 + 8192 + 2, bytes.len());
+    assert_eq!(vec![0x00, 0xc4, 0xff], bytes[..3].to_vec());
+    assert_eq!(vec![0xff, 0x03, 0xe0], bytes[bytes.len() - 3..].to_vec());
+
+    let mut d = aper::Decoder::new(&bytes);
+    assert!(!bool::from_aper(&mut d, UNCONSTRAINED).unwrap());
+    assert_eq!(v, Vec::<bool>::from_aper(&mut d, constraints).unwrap());
+}
+
+#[test]
+fn open_type() {
+    let mut enc = Encoder::new();
+    enc.write_bits(0b101, 3);
+    enc.encode_open_type(|enc| {
+        enc.write_bits(1, 1);
+        enc.encode_int(43, Some(0), Some(255))
+    }).unwrap();
+    assert_eq!(vec![0xa0, 0x02, 0x80, 0x2b], *enc.bytes());
+
+    // An empty open type holds a zero octet
+    let mut enc = Encoder::new();
+    OpenType(()).write_aper(&mut enc, UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x01, 0x00], *enc.bytes());
+}
+
+#[test]
+fn default_write_aper() {
+    // The alignment inside `Legacy`'s encoding is recomputed where it's written
+    let mut enc = Encoder::new();
+    enc.write_bits(0b11, 2);
+    Legacy(0x1234).write_aper(&mut enc, UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0xe0, 0x12, 0x34], *enc.bytes());
+
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(3, d.read(2).unwrap());
+    assert_eq!(0x1234, Legacy::from_aper(&mut d, UNCONSTRAINED).unwrap().0);
+}
+
+#[test]
+fn encode() {
+    let enc = Encoder::encode(|enc| enc.encode_int(-1, None, None)).unwrap();
+    assert_eq!(vec![0x01, 0xff], *enc.bytes());
+
+    assert_eq!(Some(EncodeError::FragmentationRequired),
+               Encoder::encode(|enc| enc.encode_length(16384)).err());
+    assert_eq!(Some(EncodeError::InvalidValue),
+               Encoder::encode_unaligned(|enc| enc.encode_int(8, Some(0), Some(7))).err());
+}
@@ -0,0 +1,110 @@
+extern crate asn1;
+use asn1::{Enumerated, EnumeratedValue};
+use asn1::aper::{self, APerElement, UNCONSTRAINED};
+use asn1::uper::{self, UPerElement};
+
+// Color ::= ENUMERATED { red, green, yellow, ..., blue }
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Color {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+}
+
+impl Enumerated for Color {
+    const NUM_ROOT: usize = 3;
+    const EXTENSIBLE: bool = true;
+
+    fn from_index(i: usize) -> Option<Self> {
+        match i {
+            0 => Some(Color::Red),
+            1 => Some(Color::Green),
+            2 => Some(Color::Yellow),
+            3 => Some(Color::Blue),
+            _ => None,
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+// Single ::= ENUMERATED { only }
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Single {
+    Only,
+}
+
+impl Enumerated for Single {
+    const NUM_ROOT: usize = 1;
+    const EXTENSIBLE: bool = false;
+
+    fn from_index(i: usize) -> Option<Self> {
+        match i {
+            0 => Some(Single::Only),
+            _ => None,
+        }
+    }
+
+    fn index(&self) -> usize {
+        0
+    }
+}
+
+#[test]
+fn encode_root() {
+    // extension bit 0, index 10
+    let enc = EnumeratedValue::Known(Color::Yellow).to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x40], *enc.bytes());
+    assert_eq!(5, enc.r_padding());
+
+    // The index of a single root item has no bits
+    let enc = EnumeratedValue::Known(Single::Only).to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(0, enc.bytes().len());
+}
+
+#[test]
+fn encode_addition() {
+    // extension bit 1, normally small 0000000
+    let enc = EnumeratedValue::Known(Color::Blue).to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x80], *enc.bytes());
+
+    let enc = EnumeratedValue::<Color>::Unknown(5).to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x85], *enc.bytes());
+
+    assert_eq!(Some(aper::EncodeError::InvalidValue),
+               EnumeratedValue::<Single>::Unknown(0).to_aper(UNCONSTRAINED).err());
+}
+
+#[test]
+fn decode() {
+    let data = b"\x40";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(EnumeratedValue::Known(Color::Yellow),
+               EnumeratedValue::<Color>::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    let data = b"\x80\x85";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(EnumeratedValue::Known(Color::Blue),
+               EnumeratedValue::<Color>::from_aper(&mut d, UNCONSTRAINED).unwrap());
+    let x = EnumeratedValue::<Color>::from_aper(&mut d, UNCONSTRAINED).unwrap();
+    assert_eq!(EnumeratedValue::Unknown(5), x);
+    assert_eq!(None, x.known());
+
+    // The root index 11 is beyond the root
+    let data = b"\x60";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(Err(aper::DecodeError::MalformedInt),
+               EnumeratedValue::<Color>::from_aper(&mut d, UNCONSTRAINED));
+}
+
+#[test]
+fn uper() {
+    let enc = EnumeratedValue::Known(Color::Green).to_uper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x20], *enc.bytes());
+    let mut d = uper::Decoder::new_unaligned(enc.bytes());
+    assert_eq!(EnumeratedValue::Known(Color::Green),
+               EnumeratedValue::<Color>::from_uper(&mut d, UNCONSTRAINED).unwrap());
+}
@@ -0,0 +1,101 @@
+extern crate asn1;
+use asn1::aper::{self, APerElement, Constraint, Constraints, ExtensionAdditions, UNCONSTRAINED,
+                 encode_normally_small_length};
+use asn1::uper::{self, UPerElement};
+
+#[test]
+fn normally_small_length() {
+    assert_eq!(vec![0x00], *encode_normally_small_length(1).unwrap().bytes());
+    assert_eq!(vec![0x7e], *encode_normally_small_length(64).unwrap().bytes());
+    // 1, padding, then a length determinant of 65
+    let enc = encode_normally_small_length(65).unwrap();
+    assert_eq!(vec![0x80, 0x41], *enc.bytes());
+
+    for n in &[1, 2, 63, 64, 65, 300] {
+        let enc = encode_normally_small_length(*n).unwrap();
+        let mut d = aper::Decoder::new(enc.bytes());
+        assert_eq!(*n, d.decode_normally_small_length().unwrap());
+    }
+}
+
+#[test]
+fn encode_additions() {
+    let mut ext = ExtensionAdditions::new();
+    assert!(!ext.is_any_present());
+    assert_eq!(0, ext.to_aper(UNCONSTRAINED).unwrap().bytes().len());
+
+    let enc = aper::Encoder::new();
+    ext.push(&enc, &None::<bool>, UNCONSTRAINED).unwrap();
+    ext.push(&enc, &Some(true), UNCONSTRAINED).unwrap();
+    ext.push_encoding(Some(().to_aper(UNCONSTRAINED).unwrap()));
+    assert_eq!(3, ext.len());
+    assert!(ext.is_any_present());
+    assert!(!ext.is_present(0));
+    assert_eq!(Some(&[0x80][..]), ext.get(1));
+    // An empty encoding is replaced by a zero octet
+    assert_eq!(Some(&[0x00][..]), ext.get(2));
+
+    // bitmap length 0000010, bitmap 011, padding, then each present addition as an open type:
+    // 00000001 10000000, 00000001 00000000
+    let target: Vec<u8> = vec![0x04, 0xc0, 0x01, 0x80, 0x01, 0x00];
+    assert_eq!(target, *ext.to_aper(UNCONSTRAINED).unwrap().bytes());
+}
+
+#[test]
+fn encode_additions_unaligned() {
+    let constraints = Constraints {
+        value: Some(Constraint::new(Some(0), Some(1000))),
+        size: None,
+    };
+    let aligned = aper::Encoder::new();
+    let mut ext = ExtensionAdditions::new();
+    ext.push(&aligned, &Some(5u16), constraints).unwrap();
+    assert_eq!(Some(&[0x00, 0x05][..]), ext.get(0));
+    assert_eq!(vec![0x01, 0x02, 0x00, 0x05], *ext.to_aper(UNCONSTRAINED).unwrap().bytes());
+
+    // The addition is written in the variant of PER of the encoder it's pushed with, here as a 10-bit field
+    let unaligned = uper::Encoder::new_unaligned();
+    let mut ext = ExtensionAdditions::new();
+    ext.push(&unaligned, &Some(5u16), constraints).unwrap();
+    assert_eq!(Some(&[0x01, 0x40][..]), ext.get(0));
+    let target: Vec<u8> = vec![0x01, 0x02, 0x01, 0x40];
+    assert_eq!(target, *ext.to_uper(UNCONSTRAINED).unwrap().bytes());
+
+    let mut d = uper::Decoder::new_unaligned(&target);
+    let ext = ExtensionAdditions::from_uper(&mut d, UNCONSTRAINED).unwrap();
+    assert_eq!(Some(5u16), ext.decode(0, constraints).unwrap());
+}
+
+#[test]
+fn decode_additions() {
+    let data = b"\x04\xc0\x01\x80\x01\x00";
+    let mut d = aper::Decoder::new(data);
+    let ext = ExtensionAdditions::from_aper(&mut d, UNCONSTRAINED).unwrap();
+    assert_eq!(3, ext.len());
+    assert_eq!(None, ext.decode::<bool>(0, UNCONSTRAINED).unwrap());
+    assert_eq!(Some(true), ext.decode::<bool>(1, UNCONSTRAINED).unwrap());
+    assert_eq!(Some(()), ext.decode::<()>(2, UNCONSTRAINED).unwrap());
+    assert_eq!(None, ext.decode::<bool>(3, UNCONSTRAINED).unwrap());
+
+    // A decoder that only knows about the first addition skips the other two
+    assert_eq!(2, ext.num_unknown(1));
+    assert_eq!(0, ext.num_unknown(3));
+}
+
+#[test]
+fn decode_additions_check_padding() {
+    // A single addition holding a bit followed by padding that isn't all 0
+    let data = b"\x01\x02\xc0\x2b";
+    let mut d = aper::Decoder::new(data);
+    let ext = ExtensionAdditions::from_aper(&mut d, UNCONSTRAINED).unwrap();
+    let mut inner = ext.decoder(0).unwrap();
+    assert_eq!(1, inner.read(1).unwrap());
+    assert_eq!(Ok(()), inner.align());
+
+    let mut d = aper::Decoder::new(data);
+    d.set_check_padding(true);
+    let ext = ExtensionAdditions::from_aper(&mut d, UNCONSTRAINED).unwrap();
+    let mut inner = ext.decoder(0).unwrap();
+    assert_eq!(1, inner.read(1).unwrap());
+    assert_eq!(Err(aper::DecodeError::MalformedPadding), inner.align());
+}
@@ -1,5 +1,6 @@
 extern crate asn1;
-use asn1::aper::{self, APerElement, UNCONSTRAINED};
+use asn1::aper::{self, APerElement, Constraint, Constraints, UNCONSTRAINED};
+use asn1::uper::{self, UPerElement};
 use std::i32;
 
 #[test]
@@ -66,9 +67,10 @@ fn std_i16() {
 
 #[test]
 fn std_i32() {
-    let data_min = b"\x04\x00\x00\x00\x00"; // i32::MIN
-    let data_med = b"\x04\x80\x00\x00\x2b"; // 43
-    let data_max = b"\x04\xff\xff\xff\xff"; // i32::MAX
+    // The number of octets is a 2-bit field, followed by the octet-aligned minimal offset from i32::MIN
+    let data_min = b"\x00\x00"; // i32::MIN
+    let data_med = b"\xc0\x80\x00\x00\x2b"; // 43
+    let data_max = b"\xc0\xff\xff\xff\xff"; // i32::MAX
     let mut d = aper::Decoder::new(data_min);
     assert_eq!(std::i32::MIN, i32::from_aper(&mut d, UNCONSTRAINED).unwrap());
     d = aper::Decoder::new(data_med);
@@ -105,9 +107,9 @@ fn std_u16() {
 
 #[test]
 fn std_u32() {
-    let data_min = b"\x04\x00\x00\x00\x00"; // u32::MIN
-    let data_med = b"\x04\x00\x00\x00\x2b"; // 43
-    let data_max = b"\x04\xff\xff\xff\xff"; // u32::MAX
+    let data_min = b"\x00\x00"; // u32::MIN
+    let data_med = b"\x00\x2b"; // 43
+    let data_max = b"\xc0\xff\xff\xff\xff"; // u32::MAX
     let mut d = aper::Decoder::new(data_min);
     assert_eq!(std::u32::MIN, u32::from_aper(&mut d, UNCONSTRAINED).unwrap());
     d = aper::Decoder::new(data_med);
@@ -115,3 +117,93 @@ fn std_u32() {
     d = aper::Decoder::new(data_max);
     assert_eq!(std::u32::MAX, u32::from_aper(&mut d, UNCONSTRAINED).unwrap());
 }
+
+#[test]
+fn constrained_widths() {
+    // A single value takes no bits at all
+    assert_eq!(0, aper::encode_int(7, Some(7), Some(7)).unwrap().bytes().len());
+    let mut d = aper::Decoder::new(b"");
+    assert_eq!(7, d.decode_int(Some(7), Some(7)).unwrap());
+
+    // 255 values take an 8-bit field, 256 values one octet and 257 values two octets
+    assert_eq!(vec![0xfe], *aper::encode_int(254, Some(0), Some(254)).unwrap().bytes());
+    assert_eq!(vec![0xff], *aper::encode_int(255, Some(0), Some(255)).unwrap().bytes());
+    assert_eq!(vec![0x01, 0x00], *aper::encode_int(256, Some(0), Some(256)).unwrap().bytes());
+    assert_eq!(vec![0xff, 0xff], *aper::encode_int(65535, Some(0), Some(65535)).unwrap().bytes());
+
+    // Beyond 64K values, the minimal octets are preceded by their number, here in [1, 3]
+    let enc = aper::encode_int(65536, Some(0), Some(65536)).unwrap();
+    assert_eq!(vec![0x80, 0x01, 0x00, 0x00], *enc.bytes());
+    assert_eq!(0, enc.r_padding());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(65536, d.decode_int(Some(0), Some(65536)).unwrap());
+
+    // The full range of an i64
+    let enc = aper::encode_int(i64::MAX, Some(i64::MIN), Some(i64::MAX)).unwrap();
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(i64::MAX, d.decode_int(Some(i64::MIN), Some(i64::MAX)).unwrap());
+}
+
+#[test]
+fn constrained_out_of_range() {
+    assert_eq!(Some(aper::EncodeError::InvalidValue), aper::encode_int(3, Some(0), Some(2)).err());
+    assert_eq!(Some(aper::EncodeError::InvalidValue), aper::encode_int(-1, Some(0), None).err());
+
+    let data = b"\xc0";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(Err(aper::DecodeError::MalformedInt), d.decode_int(Some(0), Some(2)));
+}
+
+#[test]
+fn unconstrained_encode() {
+    assert_eq!(vec![0x01, 0x00], *aper::encode_int(0, None, None).unwrap().bytes());
+    assert_eq!(vec![0x01, 0xff], *aper::encode_int(-1, None, None).unwrap().bytes());
+    assert_eq!(vec![0x02, 0x00, 0x80], *aper::encode_int(128, None, None).unwrap().bytes());
+    assert_eq!(vec![0x01, 0x80], *aper::encode_int(-128, None, None).unwrap().bytes());
+    assert_eq!(vec![0x02, 0x10, 0x01], *aper::encode_int(4096, Some(-1), None).unwrap().bytes());
+    assert_eq!(vec![0x01, 0x00], *aper::encode_int(-1, Some(-1), None).unwrap().bytes());
+}
+
+#[test]
+fn constrained_beyond_type() {
+    // INTEGER (0..1000) decoded as a u8, which 1000 doesn't fit in
+    let constraints = Constraints {
+        value: Some(Constraint::new(Some(0), Some(1000))),
+        size: None,
+    };
+    let data = b"\x03\xe8";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(Err(aper::DecodeError::MalformedInt), u8::from_aper(&mut d, constraints));
+    let mut d = aper::Decoder::new(b"\x00\xc8");
+    assert_eq!(200, u8::from_aper(&mut d, constraints).unwrap());
+    let mut d = uper::Decoder::new_unaligned(b"\xfa\x00");
+    assert_eq!(Err(aper::DecodeError::MalformedInt), i8::from_uper(&mut d, constraints));
+}
+
+#[test]
+fn extensible_value() {
+    // INTEGER (0..7, ...), where a value outside of the root is unconstrained
+    let constraints = Constraints {
+        value: Some(Constraint::new_extensible(Some(0), Some(7))),
+        size: None,
+    };
+    assert_eq!(vec![0x50], *5u8.to_aper(constraints).unwrap().bytes());
+    assert_eq!(vec![0x50], *5u8.to_uper(constraints).unwrap().bytes());
+    let mut d = aper::Decoder::new(b"\x50");
+    assert_eq!(5, u8::from_aper(&mut d, constraints).unwrap());
+
+    let target: Vec<u8> = vec![0x80, 0x02, 0x00, 0xc8];
+    assert_eq!(target, *200u8.to_aper(constraints).unwrap().bytes());
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(200, u8::from_aper(&mut d, constraints).unwrap());
+
+    let target: Vec<u8> = vec![0x81, 0x00, 0x64, 0x00];
+    assert_eq!(target, *200u8.to_uper(constraints).unwrap().bytes());
+    let mut d = uper::Decoder::new_unaligned(&target);
+    assert_eq!(200, u8::from_uper(&mut d, constraints).unwrap());
+
+    let target: Vec<u8> = vec![0x80, 0x01, 0xff];
+    assert_eq!(target, *(-1i32).to_aper(constraints).unwrap().bytes());
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(-1, i32::from_aper(&mut d, constraints).unwrap());
+}
@@ -0,0 +1,116 @@
+extern crate asn1;
+use asn1::BitString;
+use asn1::aper::{self, APerElement, Constraint, Constraints, EncodeError, encode_length};
+
+#[test]
+fn decode_short() {
+    let data = b"\x7f";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(127, d.decode_length().unwrap());
+}
+
+#[test]
+fn decode_long() {
+    let data = b"\xbf\xff";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(16383, d.decode_length().unwrap());
+}
+
+#[test]
+fn decode_fragment() {
+    let data = b"\xc3";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!((49152, true), d.decode_length_fragment().unwrap());
+}
+
+#[test]
+fn decode_unexpected_fragment() {
+    let data = b"\xc1";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(Err(aper::DecodeError::UnexpectedFragment), d.decode_length());
+}
+
+#[test]
+fn decode_malformed_fragment() {
+    let data = b"\xc5";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(Err(aper::DecodeError::MalformedLength), d.decode_length_fragment());
+}
+
+#[test]
+fn encode_long() {
+    let target: Vec<u8> = vec![0x81, 0x2c];
+    assert_eq!(target, *encode_length(300).unwrap().bytes());
+    let target: Vec<u8> = vec![0xbf, 0xff];
+    assert_eq!(target, *encode_length(16383).unwrap().bytes());
+}
+
+#[test]
+fn encode_requires_fragmentation() {
+    assert_eq!(EncodeError::FragmentationRequired, encode_length(16384).err().unwrap());
+}
+
+#[test]
+fn encode_fragmented_u8() {
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(None, None)),
+    };
+    let v: Vec<u8> = (0..70000).map(|i| i as u8).collect();
+    let enc = v.to_aper(sz).unwrap();
+    let bytes = enc.bytes();
+
+    // 64K fragment, then a long-form length for the remaining 4464 items
+    assert_eq!(1 + 65536 + 2 + 4464, bytes.len());
+    assert_eq!(0xc4, bytes[0]);
+    assert_eq!(0x00, bytes[1]);
+    assert_eq!(0x91, bytes[65537]);
+    assert_eq!(0x70, bytes[65538]);
+
+    let mut d = aper::Decoder::new(bytes);
+    assert_eq!(v, Vec::<u8>::from_aper(&mut d, sz).unwrap());
+}
+
+#[test]
+fn encode_fragmented_exact_multiple() {
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(None, None)),
+    };
+    let v: Vec<u8> = vec![0xa5; 32768];
+    let enc = v.to_aper(sz).unwrap();
+    let bytes = enc.bytes();
+
+    // A fragment of exactly 32K items is followed by an empty final length determinant
+    assert_eq!(1 + 32768 + 1, bytes.len());
+    assert_eq!(0xc2, bytes[0]);
+    assert_eq!(0x00, bytes[32769]);
+
+    let mut d = aper::Decoder::new(bytes);
+    assert_eq!(v, Vec::<u8>::from_aper(&mut d, sz).unwrap());
+}
+
+#[test]
+fn bit_string_fragmented() {
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(None, None)),
+    };
+    let n = 16384 + 12;
+    let mut b = BitString::with_len(n);
+    b.set(0, true);
+    b.set(5, true);
+    b.set(n - 1, true);
+    let enc = b.to_aper(sz).unwrap();
+    assert_eq!(0xc1, enc.bytes()[0]);
+    assert_eq!(0x84, enc.bytes()[1]);
+    assert_eq!(0x0c, enc.bytes()[2049]);
+    assert_eq!(4, enc.r_padding());
+
+    let mut d = aper::Decoder::new(enc.bytes());
+    let decoded = BitString::from_aper(&mut d, sz).unwrap();
+    assert_eq!(n, decoded.get_num_bits());
+    for i in 0..n {
+        assert_eq!(b.is_set(i), decoded.is_set(i));
+    }
+}
@@ -0,0 +1,137 @@
+extern crate asn1;
+#[macro_use]
+extern crate asn1_derive;
+use asn1::{BitString, NamedBit, NamedBits};
+use asn1::aper::{self, APerElement, Constraint, Constraints, UNCONSTRAINED};
+use asn1::uper::{self, UPerElement};
+
+// Algorithm ::= BIT STRING { ciphering (0), integrity (1), nulling (5) }
+#[derive(NamedBit, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Algorithm {
+    Ciphering = 0,
+    Integrity,
+    Nulling = 5,
+}
+
+#[derive(APerElement, Debug, PartialEq)]
+struct Capabilities {
+    #[aper(size(8))]
+    algorithms: NamedBits<Algorithm>,
+    #[aper(size(1..4, ...))]
+    extra: NamedBits<Algorithm>,
+}
+
+#[test]
+fn derive() {
+    assert_eq!(1, Algorithm::Integrity.bit());
+    assert_eq!(5, Algorithm::Nulling.bit());
+    assert_eq!(Some(Algorithm::Nulling), Algorithm::from_bit(5));
+    assert_eq!(None, Algorithm::from_bit(2));
+}
+
+#[test]
+fn flags() {
+    let mut x: NamedBits<Algorithm> = NamedBits::new();
+    assert!(x.is_empty());
+    x.insert(Algorithm::Nulling);
+    x.insert(Algorithm::Ciphering);
+    assert!(x.contains(Algorithm::Ciphering));
+    assert!(!x.contains(Algorithm::Integrity));
+    assert_eq!(vec![Algorithm::Ciphering, Algorithm::Nulling], x.iter().collect::<Vec<_>>());
+    assert_eq!("'100001'B", x.to_string());
+
+    // Clearing the last bit set trims the zeros before it
+    x.remove(Algorithm::Nulling);
+    assert_eq!("'1'B", x.to_string());
+    x.remove(Algorithm::Integrity);
+    assert_eq!(1, x.as_bit_string().len());
+    x.set(Algorithm::Ciphering, false);
+    assert!(x.is_empty());
+    assert_eq!(NamedBits::default(), x);
+
+    let y: NamedBits<Algorithm> = vec![Algorithm::Integrity].into_iter().collect();
+    assert_eq!(y, NamedBits::from("'01000000'B".parse::<BitString>().unwrap()));
+    assert_eq!(BitString::from_uint(0x1, 2), BitString::from(y));
+}
+
+#[test]
+fn trailing_zeros() {
+    let mut b: BitString = "'0110 0000 000'B".parse().unwrap();
+    b.trim_trailing_zeros();
+    assert_eq!("'011'B", b.to_string());
+    let mut b = BitString::with_len(20);
+    b.trim_trailing_zeros();
+    assert!(b.is_empty());
+}
+
+#[test]
+fn fixed_size() {
+    // Padded back out to the fixed size of 8
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(8), Some(8))),
+    };
+    let x: NamedBits<Algorithm> = vec![Algorithm::Integrity].into_iter().collect();
+    assert_eq!(8, x.to_bit_string(sz).len());
+    let enc = x.to_aper(sz).unwrap();
+    assert_eq!(vec![0x40], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, NamedBits::from_aper(&mut d, sz).unwrap());
+
+    let enc = x.to_uper(sz).unwrap();
+    assert_eq!(vec![0x40], *enc.bytes());
+    let mut d = uper::Decoder::new_unaligned(enc.bytes());
+    assert_eq!(x, NamedBits::from_uper(&mut d, sz).unwrap());
+}
+
+#[test]
+fn size_range() {
+    // Only the leading 1 bit is encoded, after a length of 1 - 1
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(1), Some(8))),
+    };
+    let x: NamedBits<Algorithm> = vec![Algorithm::Ciphering].into_iter().collect();
+    assert_eq!(vec![0x00, 0x80], *x.to_aper(sz).unwrap().bytes());
+    assert_eq!(vec![0x10], *x.to_uper(sz).unwrap().bytes());
+
+    // An empty value still has the lower bound of 1 bit
+    let x: NamedBits<Algorithm> = NamedBits::new();
+    let enc = x.to_aper(sz).unwrap();
+    assert_eq!(vec![0x00, 0x00], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, NamedBits::from_aper(&mut d, sz).unwrap());
+}
+
+#[test]
+fn unconstrained() {
+    let x: NamedBits<Algorithm> = vec![Algorithm::Ciphering, Algorithm::Nulling].into_iter().collect();
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x06, 0x84], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, NamedBits::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn unknown_bits() {
+    // Bit 3 isn't named, but is kept
+    let data = b"\x06\x14";
+    let mut d = aper::Decoder::new(data);
+    let x = NamedBits::<Algorithm>::from_aper(&mut d, UNCONSTRAINED).unwrap();
+    assert_eq!(vec![Algorithm::Nulling], x.iter().collect::<Vec<_>>());
+    assert_eq!("'000101'B", x.to_string());
+    assert_eq!(data.to_vec(), *x.to_aper(UNCONSTRAINED).unwrap().bytes());
+}
+
+#[test]
+fn sequence() {
+    let x = Capabilities {
+        algorithms: vec![Algorithm::Ciphering, Algorithm::Integrity].into_iter().collect(),
+        extra: vec![Algorithm::Nulling].into_iter().collect(),
+    };
+    // 1100 0000, then a 1 for the extension and an unconstrained length of 6
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0xc0, 0x80, 0x06, 0x04], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, Capabilities::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
@@ -0,0 +1,99 @@
+extern crate asn1;
+use asn1::{ObjectIdentifier, ParseOidError, RelativeOid};
+use asn1::aper::{self, APerElement, DecodeError, UNCONSTRAINED};
+use asn1::uper::{self, UPerElement};
+
+#[test]
+fn arcs() {
+    let oid = ObjectIdentifier::new(&[1, 2, 840, 113549]).unwrap();
+    assert_eq!(&[1, 2, 840, 113549], oid.arcs());
+    assert_eq!(vec![0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d], oid.to_contents());
+    assert_eq!(Some(oid), ObjectIdentifier::from_contents(&[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d]));
+
+    // The first two arcs share a subidentifier, which can be large under 2
+    let oid = ObjectIdentifier::new(&[2, 999, 3]).unwrap();
+    assert_eq!(vec![0x88, 0x37, 0x03], oid.to_contents());
+    assert_eq!(Some(oid), ObjectIdentifier::from_contents(&[0x88, 0x37, 0x03]));
+
+    assert_eq!(None, ObjectIdentifier::new(&[1]));
+    assert_eq!(None, ObjectIdentifier::new(&[3, 1]));
+    assert_eq!(None, ObjectIdentifier::new(&[1, 40]));
+    assert!(ObjectIdentifier::new(&[2, 40]).is_some());
+}
+
+#[test]
+fn dotted() {
+    let oid: ObjectIdentifier = "0.0.8.2250".parse().unwrap();
+    assert_eq!(&[0, 0, 8, 2250], oid.arcs());
+    assert_eq!("0.0.8.2250", oid.to_string());
+    assert_eq!("ObjectIdentifier(0.0.8.2250)", format!("{:?}", oid));
+
+    let rel: RelativeOid = "8.2250".parse().unwrap();
+    assert_eq!(RelativeOid::new(&[8, 2250]), rel);
+    assert_eq!("8.2250", rel.to_string());
+    assert_eq!(oid, "0.0".parse::<ObjectIdentifier>().unwrap().join(&rel));
+
+    for s in &["", "1", "3.1", "1.40", "1..2", "1.2.", "+1.2", " 1.2", "1.2.18446744073709551616"] {
+        assert_eq!(Err(ParseOidError), s.parse::<ObjectIdentifier>(), "{}", s);
+    }
+    assert_eq!(Err(ParseOidError), "8.x".parse::<RelativeOid>());
+}
+
+#[test]
+fn large_arcs() {
+    let oid = ObjectIdentifier::new(&[1, 2, u64::MAX]).unwrap();
+    let contents = oid.to_contents();
+    assert_eq!(vec![0x2a, 0x81, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f], contents);
+    assert_eq!(Some(oid), ObjectIdentifier::from_contents(&contents));
+
+    let oid = ObjectIdentifier::new(&[2, u64::MAX]).unwrap();
+    assert_eq!(Some(oid.clone()), ObjectIdentifier::from_contents(&oid.to_contents()));
+
+    // One past u64::MAX
+    let data = b"\x0a\x82\x80\x80\x80\x80\x80\x80\x80\x80\x00";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(Err(DecodeError::MalformedObjectIdentifier), RelativeOid::from_aper(&mut d, UNCONSTRAINED));
+    let data = b"\x0b\x2a\x82\x80\x80\x80\x80\x80\x80\x80\x80\x00";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(Err(DecodeError::MalformedObjectIdentifier), ObjectIdentifier::from_aper(&mut d, UNCONSTRAINED));
+
+    // Far beyond it
+    let mut data = vec![0x40, 0x2a];
+    data.extend(vec![0xff; 63]);
+    let mut d = aper::Decoder::new(&data);
+    assert_eq!(Err(DecodeError::MalformedObjectIdentifier), ObjectIdentifier::from_aper(&mut d, UNCONSTRAINED));
+}
+
+#[test]
+fn malformed() {
+    // Not minimal, truncated and empty
+    assert_eq!(None, ObjectIdentifier::from_contents(&[0x2a, 0x80, 0x01]));
+    assert_eq!(None, ObjectIdentifier::from_contents(&[0x2a, 0x86]));
+    assert_eq!(None, ObjectIdentifier::from_contents(&[]));
+    assert_eq!(None, RelativeOid::from_contents(&[0x86]));
+    assert_eq!(Some(RelativeOid::new(&[])), RelativeOid::from_contents(&[]));
+}
+
+#[test]
+fn per() {
+    let oid: ObjectIdentifier = "1.2.840.113549".parse().unwrap();
+    let enc = oid.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x06, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(oid, ObjectIdentifier::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    // No alignment in unaligned PER
+    let oid: ObjectIdentifier = "1.2".parse().unwrap();
+    let mut enc = true.to_uper(UNCONSTRAINED).unwrap();
+    enc.append(&oid.to_uper(UNCONSTRAINED).unwrap()).unwrap();
+    assert_eq!(vec![0x80, 0x95, 0x00], *enc.bytes());
+    let mut d = uper::Decoder::new_unaligned(enc.bytes());
+    assert!(bool::from_uper(&mut d, UNCONSTRAINED).unwrap());
+    assert_eq!(oid, ObjectIdentifier::from_uper(&mut d, UNCONSTRAINED).unwrap());
+
+    let rel = RelativeOid::new(&[8, 2250]);
+    let enc = rel.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x03, 0x08, 0x91, 0x4a], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(rel, RelativeOid::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
@@ -0,0 +1,170 @@
+extern crate asn1;
+use asn1::OctetString;
+use asn1::aper::{self, APerElement, Constraint, Constraints, EncodeError, UNCONSTRAINED};
+use asn1::uper::{self, UPerElement};
+
+#[test]
+fn fixed_small() {
+    // Up to two octets aren't octet-aligned
+    let x = OctetString(vec![0xab, 0xcd]);
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(2), Some(2))),
+    };
+    let mut enc = true.to_aper(UNCONSTRAINED).unwrap();
+    enc.append(&x.to_aper(sz).unwrap()).unwrap();
+    assert_eq!(vec![0xd5, 0xe6, 0x80], *enc.bytes());
+
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert!(bool::from_aper(&mut d, UNCONSTRAINED).unwrap());
+    assert_eq!(x, OctetString::from_aper(&mut d, sz).unwrap());
+}
+
+#[test]
+fn fixed_aligned() {
+    let x = OctetString(vec![0x01, 0x23, 0x45]);
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(3), Some(3))),
+    };
+    let mut enc = true.to_aper(UNCONSTRAINED).unwrap();
+    enc.append(&x.to_aper(sz).unwrap()).unwrap();
+    assert_eq!(vec![0x80, 0x01, 0x23, 0x45], *enc.bytes());
+
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert!(bool::from_aper(&mut d, UNCONSTRAINED).unwrap());
+    assert_eq!(x, OctetString::from_aper(&mut d, sz).unwrap());
+
+    // Nothing at all for a size of 0
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(0), Some(0))),
+    };
+    assert_eq!(0, OctetString(vec![]).to_aper(sz).unwrap().bytes().len());
+}
+
+#[test]
+fn constrained_length() {
+    // 3 bits of length, then the aligned contents
+    let x = OctetString(b"FOO".to_vec());
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(1), Some(8))),
+    };
+    let enc = x.to_aper(sz).unwrap();
+    assert_eq!(vec![0x40, 0x46, 0x4f, 0x4f], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, OctetString::from_aper(&mut d, sz).unwrap());
+
+    // A missing lower bound is 0, and an empty string isn't padded
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(None, Some(3))),
+    };
+    let enc = OctetString(vec![]).to_aper(sz).unwrap();
+    assert_eq!(vec![0x00], *enc.bytes());
+    assert_eq!(6, enc.r_padding());
+
+    // Two octets of length for a range over 256
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(0), Some(1000))),
+    };
+    let enc = x.to_aper(sz).unwrap();
+    assert_eq!(vec![0x00, 0x03, 0x46, 0x4f, 0x4f], *enc.bytes());
+
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(4), Some(8))),
+    };
+    assert_eq!(Some(EncodeError::InvalidValue), x.to_aper(sz).err());
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(2), Some(2))),
+    };
+    assert_eq!(Some(EncodeError::InvalidValue), x.to_aper(sz).err());
+}
+
+#[test]
+fn unconstrained() {
+    let x = OctetString(b"FOO".to_vec());
+    let mut enc = true.to_aper(UNCONSTRAINED).unwrap();
+    enc.append(&x.to_aper(UNCONSTRAINED).unwrap()).unwrap();
+    assert_eq!(vec![0x80, 0x03, 0x46, 0x4f, 0x4f], *enc.bytes());
+
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert!(bool::from_aper(&mut d, UNCONSTRAINED).unwrap());
+    assert_eq!(x, OctetString::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    // A lower bound alone still has a general length determinant, which is checked when decoding
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(1), None)),
+    };
+    let enc = x.to_aper(sz).unwrap();
+    assert_eq!(vec![0x03, 0x46, 0x4f, 0x4f], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(4), None)),
+    };
+    assert_eq!(Err(aper::DecodeError::MalformedLength), OctetString::from_aper(&mut d, sz));
+}
+
+#[test]
+fn fragmented() {
+    // 64K + 3 octets: one fragment of 64K, then a length determinant of 3
+    let x = OctetString((0..65539).map(|i| i as u8).collect());
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(1 + 65536 + 1 + 3, enc.bytes().len());
+    assert_eq!(0xc4, enc.bytes()[0]);
+    assert_eq!(vec![0x03, 0x00, 0x01, 0x02], enc.bytes()[65537..].to_vec());
+
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, OctetString::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    // A fixed size of 64K or more is fragmented too
+    let x = OctetString(vec![0xa5; 65536]);
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(65536), Some(65536))),
+    };
+    let enc = x.to_aper(sz).unwrap();
+    assert_eq!(vec![0xc4, 0xa5], enc.bytes()[..2].to_vec());
+    assert_eq!(0x00, enc.bytes()[65537]);
+}
+
+#[test]
+fn unaligned() {
+    let x = OctetString(b"FOO".to_vec());
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(1), Some(8))),
+    };
+    let mut enc = true.to_uper(UNCONSTRAINED).unwrap();
+    enc.append(&x.to_uper(sz).unwrap()).unwrap();
+    // 1, 010, then the contents with no padding
+    assert_eq!(vec![0xa4, 0x64, 0xf4, 0xf0], *enc.bytes());
+
+    let mut d = uper::Decoder::new_unaligned(enc.bytes());
+    assert!(bool::from_uper(&mut d, UNCONSTRAINED).unwrap());
+    assert_eq!(x, OctetString::from_uper(&mut d, sz).unwrap());
+}
+
+#[test]
+fn extensible() {
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new_extensible(Some(2), Some(2))),
+    };
+    // In the root, the fixed size rules apply after the 0 bit
+    let x = OctetString(vec![0xab, 0xcd]);
+    assert_eq!(vec![0x55, 0xe6, 0x80], *x.to_aper(sz).unwrap().bytes());
+
+    // Outside of it, an unconstrained length and aligned contents
+    let x = OctetString(b"FOO".to_vec());
+    let enc = x.to_aper(sz).unwrap();
+    assert_eq!(vec![0x80, 0x03, 0x46, 0x4f, 0x4f], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, OctetString::from_aper(&mut d, sz).unwrap());
+}
@@ -0,0 +1,97 @@
+extern crate asn1;
+use asn1::{OpenType, RawOpenType};
+use asn1::aper::{self, APerElement, Constraint, Constraints, UNCONSTRAINED};
+use asn1::uper::{self, UPerElement};
+
+#[test]
+fn encode_open_type() {
+    let c = Constraints {
+        value: Some(Constraint::new(Some(0), Some(255))),
+        size: None,
+    };
+    let x = OpenType(true);
+    assert_eq!(vec![0x01, 0x80], *x.to_aper(UNCONSTRAINED).unwrap().bytes());
+
+    // Constraints apply to the value
+    let x = OpenType(0xa5u8);
+    assert_eq!(vec![0x01, 0xa5], *x.to_aper(c).unwrap().bytes());
+
+    // An empty encoding is a zero octet
+    let x = OpenType(());
+    assert_eq!(vec![0x01, 0x00], *x.to_aper(UNCONSTRAINED).unwrap().bytes());
+}
+
+#[test]
+fn decode_open_type() {
+    let c = Constraints {
+        value: Some(Constraint::new(Some(0), Some(255))),
+        size: None,
+    };
+    let data = b"\x01\xa5\x01\x80";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(OpenType(0xa5u8), OpenType::<u8>::from_aper(&mut d, c).unwrap());
+    assert_eq!(OpenType(true), OpenType::<bool>::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    // The value doesn't have to use all of the contents octets
+    let data = b"\x02\x80\xff\x01\x00";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(OpenType(true), OpenType::<bool>::from_aper(&mut d, UNCONSTRAINED).unwrap());
+    assert_eq!(OpenType(()), OpenType::<()>::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    let data = b"\x02\x80";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(Err(aper::DecodeError::NotEnoughBits), OpenType::<bool>::from_aper(&mut d, UNCONSTRAINED));
+}
+
+#[test]
+fn uper_open_type() {
+    let c = Constraints {
+        value: Some(Constraint::new(Some(0), Some(255))),
+        size: None,
+    };
+    let x = OpenType(0x05u8);
+    let enc = x.to_uper(c).unwrap();
+    assert_eq!(vec![0x01, 0x05], *enc.bytes());
+
+    let mut d = uper::Decoder::new_unaligned(enc.bytes());
+    assert_eq!(x, OpenType::<u8>::from_uper(&mut d, c).unwrap());
+}
+
+#[test]
+fn raw_open_type() {
+    let c = Constraints {
+        value: Some(Constraint::new(Some(0), Some(255))),
+        size: None,
+    };
+    let data = b"\x01\xa5\x01\x00";
+    let mut d = aper::Decoder::new(data);
+    let x = RawOpenType::from_aper(&mut d, UNCONSTRAINED).unwrap();
+    assert_eq!(RawOpenType(vec![0xa5]), x);
+    assert_eq!(0xa5, x.decode::<u8>(c).unwrap());
+    assert_eq!(vec![0x01, 0xa5], *x.to_aper(UNCONSTRAINED).unwrap().bytes());
+
+    let x = RawOpenType::from_aper(&mut d, UNCONSTRAINED).unwrap();
+    assert_eq!(RawOpenType::encode(&(), UNCONSTRAINED).unwrap(), x);
+
+    let x = RawOpenType::encode(&true, UNCONSTRAINED).unwrap();
+    assert_eq!(RawOpenType(vec![0x80]), x);
+    assert_eq!(*OpenType(true).to_aper(UNCONSTRAINED).unwrap().bytes(),
+               *x.to_aper(UNCONSTRAINED).unwrap().bytes());
+}
+
+#[test]
+fn unaligned_contents() {
+    // An INTEGER (0..4294967295) is 32 bits in unaligned PER, but has a length and octets in aligned PER
+    let x = OpenType(5u32);
+    let mut enc = aper::Encoder::new_unaligned();
+    x.write_aper(&mut enc, UNCONSTRAINED).unwrap();
+    let target: Vec<u8> = vec![0x04, 0x00, 0x00, 0x00, 0x05];
+    assert_eq!(target, *enc.into_encoding().bytes());
+    let mut d = uper::Decoder::new_unaligned(&target);
+    assert_eq!(x, OpenType::<u32>::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    let x = RawOpenType::encode_unaligned(&5u32, UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x00, 0x00, 0x00, 0x05], x.0);
+    assert_eq!(5, x.decode_unaligned::<u32>(UNCONSTRAINED).unwrap());
+    assert_eq!(vec![0x00, 0x05], RawOpenType::encode(&5u32, UNCONSTRAINED).unwrap().0);
+}
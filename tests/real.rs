@@ -0,0 +1,208 @@
+extern crate asn1;
+#[macro_use]
+extern crate asn1_derive;
+use asn1::Real;
+use asn1::aper::{self, APerElement, Constraint, Constraints, DecodeError, EncodeError, RealComponents, UNCONSTRAINED};
+use asn1::uper::{self, UPerElement};
+
+// Measurement ::= SEQUENCE {
+//     value REAL (WITH COMPONENTS { mantissa (-16777215..16777215), base (2), exponent (-125..128) }),
+//     reading REAL (WITH COMPONENTS { base (10) })
+// }
+#[derive(APerElement, Debug, PartialEq)]
+struct Measurement {
+    #[aper(with_components(mantissa(-16777215..16777215), base(2), exponent(-125..128)))]
+    value: f64,
+    #[aper(with_components(base(10)))]
+    reading: f64,
+}
+
+fn components(mantissa: (Option<i64>, Option<i64>), base: Option<u32>, exponent: (Option<i64>, Option<i64>))
+               -> Constraints {
+    Constraints {
+        value: Some(Constraint::new_real(RealComponents {
+            mantissa,
+            base,
+            exponent,
+        })),
+        size: None,
+    }
+}
+
+fn contents(v: f64) -> Vec<u8> {
+    Real::from(v).to_contents()
+}
+
+#[test]
+fn binary() {
+    assert_eq!(Vec::<u8>::new(), contents(0.0));
+    assert_eq!(vec![0x80, 0x00, 0x01], contents(1.0));
+    assert_eq!(vec![0x80, 0xff, 0x01], contents(0.5));
+    assert_eq!(vec![0xc0, 0x00, 0x03], contents(-3.0));
+    assert_eq!(vec![0x81, 0x03, 0xcb, 0x1f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff], contents(f64::MAX));
+    assert_eq!(vec![0x81, 0xfb, 0xce, 0x01], contents(5e-324));
+
+    // The mantissa is odd, and the exponent has the fewest octets
+    assert_eq!(Real::Binary { mantissa: 3602879701896397, exponent: -55 }, Real::from(0.1));
+    assert_eq!(Real::Binary { mantissa: 1, exponent: 2 }, Real::binary(4, 0));
+    assert_eq!(vec![0x80, 0x02, 0x01], Real::Binary { mantissa: 4, exponent: 0 }.to_contents());
+    assert_eq!(vec![0x83, 0x04, 0x01, 0x00, 0x00, 0x00, 0x01], Real::binary(1, 1 << 24).to_contents());
+
+    for &v in &[0.0, 1.0, -1.5, 0.1, 1e300, -2.5e-310, f64::MAX, f64::MIN_POSITIVE, 5e-324] {
+        let real = Real::from_contents(&contents(v)).unwrap();
+        assert_eq!(Real::from(v), real);
+        assert_eq!(v.to_bits(), real.to_f64().to_bits());
+    }
+}
+
+#[test]
+fn decimal() {
+    // NR3 form, with no trailing zeros in the mantissa and a sign on the exponent only if it's zero
+    assert_eq!(b"\x031.E-1".to_vec(), Real::decimal(1, -1).to_contents());
+    assert_eq!(b"\x03123.E+0".to_vec(), Real::decimal(123, 0).to_contents());
+    assert_eq!(b"\x03-12.E2".to_vec(), Real::decimal(-1200, 0).to_contents());
+    assert_eq!(Real::Decimal { mantissa: -12, exponent: 2 }, Real::decimal(-1200, 0));
+    assert_eq!(Real::binary(0, 0), Real::decimal(0, 5));
+
+    // Any of NR1, NR2 and NR3 are accepted
+    assert_eq!(Some(Real::decimal(-12, 1)), Real::from_contents(b"\x01  -0120"));
+    assert_eq!(Some(Real::decimal(15, -1)), Real::from_contents(b"\x02 1,50"));
+    assert_eq!(Some(Real::decimal(15, 1)), Real::from_contents(b"\x031.5E+2"));
+    assert_eq!(Some(Real::decimal(1, -1)), Real::from_contents(b"\x03000.00100e2"));
+    assert_eq!(Some(Real::binary(0, 0)), Real::from_contents(b"\x03-0.E+0"));
+
+    assert_eq!(0.1, Real::decimal(1, -1).to_f64());
+    assert_eq!(-1200.0, Real::decimal(-12, 2).to_f64());
+    assert_eq!(f64::INFINITY, Real::decimal(1, 400).to_f64());
+}
+
+#[test]
+fn special() {
+    assert_eq!(vec![0x40], contents(f64::INFINITY));
+    assert_eq!(vec![0x41], contents(f64::NEG_INFINITY));
+    assert_eq!(vec![0x42], contents(f64::NAN));
+    assert_eq!(vec![0x43], contents(-0.0));
+
+    let x = Real::from_contents(&[0x43]).unwrap().to_f64();
+    assert!(x == 0.0 && x.is_sign_negative());
+    assert!(Real::from_contents(&[0x42]).unwrap().to_f64().is_nan());
+    assert_eq!(f64::NEG_INFINITY, Real::from_contents(&[0x41]).unwrap().to_f64());
+
+    assert_eq!("PLUS-INFINITY", Real::PlusInfinity.to_string());
+    assert_eq!("{ mantissa 15, base 10, exponent -1 }", Real::decimal(150, -2).to_string());
+    assert_eq!("{ mantissa -3, base 2, exponent 0 }", Real::from(-3.0).to_string());
+}
+
+#[test]
+fn ber() {
+    // Base 8 and 16, a scale factor, an even mantissa and a long exponent
+    assert_eq!(Some(Real::binary(1, 3)), Real::from_contents(&[0x90, 0x01, 0x01]));
+    assert_eq!(Some(Real::binary(3, 5)), Real::from_contents(&[0xa4, 0x01, 0x03]));
+    assert_eq!(Some(Real::binary(1, 2)), Real::from_contents(&[0x80, 0x00, 0x04]));
+    assert_eq!(Some(Real::binary(1, 5)), Real::from_contents(&[0x83, 0x01, 0x05, 0x01]));
+    assert_eq!(Some(Real::binary(1, 64)), Real::from_contents(&[0x80, 0x00, 0x01, 0, 0, 0, 0, 0, 0, 0, 0]));
+}
+
+#[test]
+fn malformed() {
+    let long_exponent = b"\x83\x09\x01\x00\x00\x00\x00\x00\x00\x00\x00\x01";
+    for bytes in &[&b"\xb0\x00\x01"[..], b"\x80\x00", b"\x83\x00\x01", long_exponent, b"\x40\x00", b"\x44",
+                   b"\x00\x31", b"\x04\x31", b"\x03", b"\x03.", b"\x031.E", b"\x031.E+", b"\x031.2.", b"\x031 ",
+                   b"\x0399999999999999999999.", b"\x031.E99999999999999999999"] {
+        assert_eq!(None, Real::from_contents(bytes), "{:?}", bytes);
+    }
+
+    // A mantissa beyond an i64
+    assert_eq!(None, Real::from_contents(&[0x80, 0x00, 0x80, 0, 0, 0, 0, 0, 0, 1]));
+
+    let data = b"\x02\x03\x2e";
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(Err(DecodeError::MalformedReal), f64::from_aper(&mut d, UNCONSTRAINED));
+}
+
+#[test]
+fn per() {
+    let enc = 1.0f64.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x03, 0x80, 0x00, 0x01], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(1.0, f64::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    let enc = 0.0f64.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x00], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(Real::binary(0, 0), Real::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    let x = Real::decimal(1, -1);
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(b"\x06\x031.E-1".to_vec(), *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, Real::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    // No alignment in unaligned PER
+    let mut enc = true.to_uper(UNCONSTRAINED).unwrap();
+    enc.append(&1.0f64.to_uper(UNCONSTRAINED).unwrap()).unwrap();
+    assert_eq!(vec![0x81, 0xc0, 0x00, 0x00, 0x80], *enc.bytes());
+    let mut d = uper::Decoder::new_unaligned(enc.bytes());
+    assert!(bool::from_uper(&mut d, UNCONSTRAINED).unwrap());
+    assert_eq!(1.0, f64::from_uper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn with_components() {
+    let float = components((Some(-16777215), Some(16777215)), Some(2), (Some(-125), Some(128)));
+    assert!(1.5f64.to_aper(float).is_ok());
+    assert!((-16777215.0f64).to_aper(float).is_ok());
+    assert_eq!(Some(EncodeError::InvalidValue), 0.1f64.to_aper(float).err());
+    assert_eq!(Some(EncodeError::InvalidValue), Real::decimal(5, 0).to_aper(float).err());
+
+    // 2^130 is 4 × 2^128, but 2^200 and 2^-130 can't be written within the limits
+    assert!(2f64.powi(130).to_aper(float).is_ok());
+    assert_eq!(Some(EncodeError::InvalidValue), 2f64.powi(200).to_aper(float).err());
+    assert_eq!(Some(EncodeError::InvalidValue), 2f64.powi(-130).to_aper(float).err());
+
+    // Zero and the special values have no base
+    assert!(0.0f64.to_aper(float).is_ok());
+    assert!(f64::INFINITY.to_aper(float).is_ok());
+    assert!(f64::NAN.to_aper(float).is_ok());
+
+    let positive = components((Some(1), Some(999)), Some(10), (Some(-2), Some(2)));
+    assert!(Real::decimal(5, 3).to_aper(positive).is_ok());
+    assert_eq!(Some(EncodeError::InvalidValue), Real::decimal(5, -3).to_aper(positive).err());
+    assert_eq!(Some(EncodeError::InvalidValue), Real::decimal(-5, 0).to_aper(positive).err());
+    assert_eq!(Some(EncodeError::InvalidValue), Real::decimal(0, 0).to_aper(positive).err());
+    assert_eq!(Some(EncodeError::InvalidValue), Real::binary(1, 0).to_aper(positive).err());
+}
+
+#[test]
+fn base_10() {
+    // An f64 with the base constrained to 10 has the fewest digits that convert back to it
+    let decimal = components((None, None), Some(10), (None, None));
+    let enc = 0.1f64.to_aper(decimal).unwrap();
+    assert_eq!(b"\x06\x031.E-1".to_vec(), *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(0.1, f64::from_aper(&mut d, decimal).unwrap());
+
+    for &v in &[1.5, -1e-300, 123456789.0, f64::MAX, 5e-324] {
+        let enc = v.to_aper(decimal).unwrap();
+        let mut d = aper::Decoder::new(enc.bytes());
+        assert_eq!(v, f64::from_aper(&mut d, decimal).unwrap());
+    }
+}
+
+#[test]
+fn sequence() {
+    let x = Measurement {
+        value: 1.5,
+        reading: 0.25,
+    };
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(b"\x03\x80\xff\x03\x07\x0325.E-2".to_vec(), *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, Measurement::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    let x = Measurement {
+        value: 0.1,
+        reading: 0.1,
+    };
+    assert_eq!(Some(EncodeError::InvalidValue), x.to_aper(UNCONSTRAINED).err());
+}
@@ -0,0 +1,123 @@
+#![feature(associated_consts)]
+extern crate asn1;
+use asn1::SequencePreamble;
+use asn1::aper::{self, APerElement, Constraint, Constraints, UNCONSTRAINED};
+
+#[derive(Debug, PartialEq)]
+struct Foo {
+    bar: Option<u8>,
+    baz: Option<bool>,
+    qux: u8,
+}
+
+const BAR_CONSTRAINTS: Constraints = Constraints {
+    value: Some(Constraint::new(Some(0), Some(255))),
+    size: None,
+};
+
+impl APerElement for Foo {
+    const CONSTRAINTS: Constraints = UNCONSTRAINED;
+
+    fn from_aper(decoder: &mut aper::Decoder, _: Constraints) -> Result<Self, aper::DecodeError> {
+        let preamble = SequencePreamble::decode(decoder, 3)?;
+        let bar = if preamble.is_present(0) {
+            Option::<u8>::from_aper(decoder, BAR_CONSTRAINTS)?
+        } else {
+            None
+        };
+        let baz = if preamble.is_present(1) {
+            Option::<bool>::from_aper(decoder, UNCONSTRAINED)?
+        } else {
+            None
+        };
+        let qux = if preamble.is_present(2) {
+            u8::from_aper(decoder, BAR_CONSTRAINTS)?
+        } else {
+            7
+        };
+        Ok(Foo {
+            bar: bar,
+            baz: baz,
+            qux: qux,
+        })
+    }
+
+    fn to_aper(&self, _: Constraints) -> Result<aper::Encoding, aper::EncodeError> {
+        let mut preamble = SequencePreamble::new();
+        preamble.push_optional(&self.bar);
+        preamble.push_optional(&self.baz);
+        preamble.push_default(&self.qux, &7);
+
+        let mut enc = preamble.to_aper(UNCONSTRAINED)?;
+        enc.append(&self.bar.to_aper(BAR_CONSTRAINTS)?)?;
+        enc.append(&self.baz.to_aper(UNCONSTRAINED)?)?;
+        if preamble.is_present(2) {
+            enc.append(&self.qux.to_aper(BAR_CONSTRAINTS)?)?;
+        }
+        Ok(enc)
+    }
+}
+
+#[test]
+fn encode_preamble() {
+    let mut p = SequencePreamble::with_len(10);
+    p.set_present(0, true);
+    p.set_present(9, true);
+    let enc = p.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(*enc.bytes(), vec![0x80, 0x40]);
+    assert_eq!(enc.r_padding(), 6);
+
+    assert_eq!(0, SequencePreamble::new().to_aper(UNCONSTRAINED).unwrap().bytes().len());
+}
+
+#[test]
+fn decode_preamble() {
+    let data = b"\xa0";
+    let mut d = aper::Decoder::new(data);
+    let p = SequencePreamble::decode(&mut d, 3).unwrap();
+    assert_eq!(p.len(), 3);
+    assert_eq!(p.bitmap(), &[true, false, true]);
+    assert!(!p.is_present(3));
+
+    let mut d = aper::Decoder::new(data);
+    let size = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(3), Some(3))),
+    };
+    assert_eq!(p, SequencePreamble::from_aper(&mut d, size).unwrap());
+
+    let mut d = aper::Decoder::new(data);
+    assert_eq!(Err(aper::DecodeError::MissingSizeConstraint),
+               SequencePreamble::from_aper(&mut d, UNCONSTRAINED));
+}
+
+#[test]
+fn encode_option() {
+    assert_eq!(0, None::<u8>.to_aper(BAR_CONSTRAINTS).unwrap().bytes().len());
+    assert_eq!(vec![0x80], *Some(true).to_aper(UNCONSTRAINED).unwrap().bytes());
+}
+
+#[test]
+fn optional_and_default() {
+    let x = Foo {
+        bar: None,
+        baz: Some(true),
+        qux: 7,
+    };
+    // preamble 010, baz = 1
+    let target: Vec<u8> = vec![0x50];
+    assert_eq!(target, *x.to_aper(UNCONSTRAINED).unwrap().bytes());
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(x, Foo::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    let x = Foo {
+        bar: Some(0xff),
+        baz: None,
+        qux: 0x0f,
+    };
+    // preamble 101, padding, bar = 1111 1111, qux = 0000 1111
+    let target: Vec<u8> = vec![0xa0, 0xff, 0x0f];
+    assert_eq!(target, *x.to_aper(UNCONSTRAINED).unwrap().bytes());
+    let mut d = aper::Decoder::new(&target);
+    assert_eq!(x, Foo::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
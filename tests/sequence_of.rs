@@ -1,6 +1,7 @@
 extern crate asn1;
 use asn1::BitString;
-use asn1::aper::{self, APerElement, Constraint, Constraints, UNCONSTRAINED};
+use asn1::aper::{self, APerElement, Constraint, Constraints, EncodeError, UNCONSTRAINED};
+use asn1::uper::{self, UPerElement};
 use std::i32;
 
 #[test]
@@ -16,7 +17,7 @@ fn decode_sequence_of_u8() {
     let mut d = aper::Decoder::new(data);
     let v = Vec::<u8>::from_aper(&mut d, Constraints {
         value: None,
-        size: Some(Constraint::new(None, Some(3))),
+        size: Some(Constraint::new(None, None)),
     }).unwrap();
     assert_eq!(v.len(), data.len() - 1);
     for i in 0..v.len() {
@@ -38,7 +39,7 @@ fn decode_sequence_of_u16() {
     let mut d = aper::Decoder::new(data);
     let v = Vec::<u16>::from_aper(&mut d, Constraints {
         value: None,
-        size: Some(Constraint::new(None, Some(3))),
+        size: Some(Constraint::new(None, None)),
     }).unwrap();
     assert_eq!(v.len(), target.len());
     for i in 0..v.len() {
@@ -49,16 +50,14 @@ fn decode_sequence_of_u16() {
 #[test]
 fn encode_sequence_of_i32() {
     let v: Vec<i32> = vec![i32::MIN, i32::MIN + 1, i32::MIN + 2];
-    let target: Vec<u8> = vec![0x3,
-                               0x04, 0x00, 0x00, 0x00, 0x00,
-                               0x04, 0x00, 0x00, 0x00, 0x01,
-                               0x04, 0x00, 0x00, 0x00, 0x02];
+    // Each item is a 2-bit number of octets followed by one octet-aligned octet
+    let target: Vec<u8> = vec![0x3, 0x00, 0x00, 0x00, 0x01, 0x00, 0x02];
     assert_eq!(target, *v.to_aper(UNCONSTRAINED).unwrap().bytes());
 }
 
 #[test]
 fn decode_sequence_of_i32() {
-    let data = b"\x03\x04\x00\x00\x00\x00\x04\x00\x00\x00\x01\x04\x00\x00\x00\x02";
+    let data = b"\x03\x00\x00\x00\x01\x00\x02";
     let mut target = Vec::new();
     use std::i32;
     for i in 0..3 {
@@ -67,7 +66,7 @@ fn decode_sequence_of_i32() {
     let mut d = aper::Decoder::new(data);
     let v = Vec::<i32>::from_aper(&mut d, Constraints {
         value: None,
-        size: Some(Constraint::new(None, Some(3))),
+        size: Some(Constraint::new(None, None)),
     }).unwrap();
     assert_eq!(v.len(), target.len());
     for i in 0..v.len() {
@@ -81,15 +80,15 @@ fn decode_sequence_of_short_bit_string() {
     let mut d = aper::Decoder::new(data);
     let v = Vec::<BitString>::from_aper(&mut d, Constraints {
         // here the "value" constraint is a constraint on the size of each element
-        value: Some(Constraint::new(None, Some(4))), 
+        value: Some(Constraint::new(Some(4), Some(4))), 
         // "size" behaves normally 
-        size: Some(Constraint::new(None, Some(2))),
+        size: Some(Constraint::new(None, None)),
     }).unwrap();
     assert_eq!(v.len(), 2);
 
     for i in 0..v.len() {
         for j in 0..4 {
-            if j == 1 || j == 2 || j == 3 {
+            if j == 0 || j == 1 || j == 2 {
                 assert_eq!(true, v[i].is_set(j));
             } else {
                 assert_eq!(false, v[i].is_set(j));
@@ -104,15 +103,15 @@ fn decode_sequence_of_long_bit_string() {
     let mut d = aper::Decoder::new(data);
     let v = Vec::<BitString>::from_aper(&mut d, Constraints {
         // here the "value" constraint is a constraint on the size of each element
-        value: Some(Constraint::new(None, Some(24))), 
+        value: Some(Constraint::new(Some(24), Some(24))), 
         // "size" behaves normally 
-        size: Some(Constraint::new(None, Some(2))),
+        size: Some(Constraint::new(None, None)),
     }).unwrap();
     assert_eq!(v.len(), 2);
 
     for i in 0..v.len() {
-        for j in 0..20 {
-            if j == 5 || j == 6 || j == 7 {
+        for j in 0..24 {
+            if j == 16 || j == 17 || j == 18 {
                 assert_eq!(true, v[i].is_set(j));
             } else {
                 assert_eq!(false, v[i].is_set(j));
@@ -120,3 +119,94 @@ fn decode_sequence_of_long_bit_string() {
         }
     }
 }
+
+#[test]
+fn fixed_size() {
+    // No length at all
+    let v = vec![true, false, true];
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(3), Some(3))),
+    };
+    let enc = v.to_aper(sz).unwrap();
+    assert_eq!(vec![0xa0], *enc.bytes());
+    assert_eq!(5, enc.r_padding());
+
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(v, Vec::<bool>::from_aper(&mut d, sz).unwrap());
+}
+
+#[test]
+fn constrained_size() {
+    // A 2-bit length of 2 - 1, then the items
+    let v = vec![true, true];
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(1), Some(4))),
+    };
+    assert_eq!(vec![0x70], *v.to_aper(sz).unwrap().bytes());
+    let mut d = aper::Decoder::new(b"\x70");
+    assert_eq!(v, Vec::<bool>::from_aper(&mut d, sz).unwrap());
+
+    // An octet-aligned length of 2 octets for a range over 256
+    let v = vec![true, false, true];
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(0), Some(300))),
+    };
+    let mut enc = true.to_aper(UNCONSTRAINED).unwrap();
+    enc.append(&v.to_aper(sz).unwrap()).unwrap();
+    assert_eq!(vec![0x80, 0x00, 0x03, 0xa0], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert!(bool::from_aper(&mut d, UNCONSTRAINED).unwrap());
+    assert_eq!(v, Vec::<bool>::from_aper(&mut d, sz).unwrap());
+}
+
+#[test]
+fn extensible_size() {
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new_extensible(Some(1), Some(4))),
+    };
+
+    // In the root, a 0 bit and then as if the constraint wasn't extensible
+    let v = vec![true, true];
+    assert_eq!(vec![0x38], *v.to_aper(sz).unwrap().bytes());
+    let mut d = aper::Decoder::new(b"\x38");
+    assert_eq!(v, Vec::<bool>::from_aper(&mut d, sz).unwrap());
+
+    // Outside of it, a 1 bit and then an unconstrained length
+    let v = vec![true; 5];
+    let enc = v.to_aper(sz).unwrap();
+    assert_eq!(vec![0x80, 0x05, 0xf8], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(v, Vec::<bool>::from_aper(&mut d, sz).unwrap());
+
+    let enc = v.to_uper(sz).unwrap();
+    assert_eq!(vec![0x82, 0xfc], *enc.bytes());
+    let mut d = uper::Decoder::new_unaligned(enc.bytes());
+    assert_eq!(v, Vec::<bool>::from_uper(&mut d, sz).unwrap());
+}
+
+#[test]
+fn size_violations() {
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(1), Some(4))),
+    };
+    assert_eq!(Some(EncodeError::InvalidValue), vec![true; 5].to_aper(sz).err());
+    assert_eq!(Some(EncodeError::InvalidValue), Vec::<bool>::new().to_aper(sz).err());
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(3), Some(3))),
+    };
+    assert_eq!(Some(EncodeError::InvalidValue), vec![true; 2].to_uper(sz).err());
+
+    // A general length is still checked against the lower bound
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(2), None)),
+    };
+    let mut d = aper::Decoder::new(b"\x01\x80");
+    assert_eq!(Err(aper::DecodeError::MalformedLength), Vec::<bool>::from_aper(&mut d, sz));
+}
@@ -0,0 +1,170 @@
+extern crate asn1;
+#[macro_use]
+extern crate asn1_derive;
+use asn1::{OctetString, SetOf};
+use asn1::aper::{self, APerElement, Constraint, Constraints, Encoder, UNCONSTRAINED};
+use asn1::uper::{self, UPerElement};
+
+// Foo ::= SET {
+//     a [APPLICATION 0] INTEGER (0..255),
+//     b [2] BOOLEAN,
+//     c [0] INTEGER (0..15) OPTIONAL,
+//     d [PRIVATE 1] BOOLEAN DEFAULT TRUE
+// }
+#[derive(APerElement, Debug, PartialEq)]
+#[aper(set)]
+struct Foo {
+    #[aper(tag(application 0), value(0..255))]
+    a: u8,
+    #[aper(tag(2))]
+    b: bool,
+    #[aper(tag(0), value(0..15))]
+    c: Option<u8>,
+    #[aper(tag(private 1), default = true)]
+    d: bool,
+}
+
+// Bar ::= SET { a INTEGER (0..255), b BOOLEAN }, with automatic tagging
+#[derive(APerElement, Debug, PartialEq)]
+#[aper(set)]
+struct Bar(#[aper(value(0..255))] u8, bool);
+
+// Baz ::= SET {
+//     a [1] BOOLEAN,
+//     b [0] SET OF INTEGER (0..255),
+//     ...,
+//     c [2] SET OF INTEGER (0..255) OPTIONAL
+// }
+#[derive(APerElement, Debug, PartialEq)]
+#[aper(set)]
+struct Baz {
+    #[aper(tag(1))]
+    a: bool,
+    #[aper(tag(0))]
+    b: SetOf<u8>,
+    #[aper(tag(2), extension)]
+    c: Option<SetOf<u8>>,
+}
+
+fn canonical<T: APerElement>(x: &T) -> Vec<u8> {
+    let mut enc = Encoder::new();
+    enc.set_canonical(true);
+    x.write_aper(&mut enc, UNCONSTRAINED).unwrap();
+    enc.into_encoding().bytes().clone()
+}
+
+#[test]
+fn set_of() {
+    let x = SetOf(vec![3u8, 1, 2]);
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x03, 0x03, 0x01, 0x02], *enc.bytes());
+    assert_eq!(vec![0x03, 0x01, 0x02, 0x03], canonical(&x));
+
+    // Decoding keeps the order of the encoding
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, SetOf::<u8>::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    let constraints = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(1), Some(4))),
+    };
+    let x = SetOf::from(vec![200u8, 7]);
+    let enc = x.to_aper(constraints).unwrap();
+    assert_eq!(vec![0x40, 0xc8, 0x07], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, SetOf::<u8>::from_aper(&mut d, constraints).unwrap());
+
+    assert_eq!(vec![0x00], canonical(&SetOf::<u8>(vec![])));
+    assert_eq!(vec![0x02, 0x05, 0x05], canonical(&SetOf(vec![5u8, 5])));
+}
+
+#[test]
+fn canonical_order() {
+    // The encodings are compared as octet strings, so a shorter integer sorts first
+    let x = SetOf(vec![256i64, -1, 1]);
+    assert_eq!(vec![0x03, 0x01, 0x01, 0x01, 0xff, 0x02, 0x01, 0x00], canonical(&x));
+
+    // The length determinant comes first, so a shorter string sorts first
+    let x = SetOf(vec![OctetString(b"aa".to_vec()), OctetString(b"b".to_vec())]);
+    assert_eq!(b"\x02\x01b\x02aa".to_vec(), canonical(&x));
+
+    // Nested sets are sorted too, before the outer one
+    let x = SetOf(vec![SetOf(vec![9u8, 4]), SetOf(vec![2u8])]);
+    assert_eq!(vec![0x02, 0x01, 0x02, 0x02, 0x04, 0x09], canonical(&x));
+
+    // Each element is sorted by its encoding on its own, but is still aligned within the set
+    let mut enc = Encoder::new();
+    enc.set_canonical(true);
+    true.write_aper(&mut enc, UNCONSTRAINED).unwrap();
+    SetOf(vec![OctetString(b"b".to_vec()), OctetString(b"a".to_vec())]).write_aper(&mut enc, UNCONSTRAINED).unwrap();
+    assert_eq!(b"\x80\x02\x01a\x01b".to_vec(), *enc.into_encoding().bytes());
+}
+
+#[test]
+fn unaligned() {
+    let x = SetOf(vec![true, false, true]);
+    let enc = x.to_uper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x03, 0xa0], *enc.bytes());
+
+    let mut enc = Encoder::new_unaligned();
+    enc.set_canonical(true);
+    assert!(enc.is_canonical() && !enc.nested().is_aligned() && enc.nested().is_canonical());
+    x.write_uper(&mut enc, UNCONSTRAINED).unwrap();
+    let enc = enc.into_encoding();
+    assert_eq!(vec![0x03, 0x60], *enc.bytes());
+    let mut d = uper::Decoder::new_unaligned(enc.bytes());
+    assert_eq!(SetOf(vec![false, true, true]), SetOf::<bool>::from_uper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn set() {
+    // Encoded in the order a, c, b, d, with the preamble bits for c then d
+    let x = Foo {
+        a: 0xab,
+        b: true,
+        c: Some(5),
+        d: false,
+    };
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0xc0, 0xab, 0x58], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, Foo::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    let x = Foo {
+        a: 1,
+        b: false,
+        c: None,
+        d: true,
+    };
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x00, 0x01, 0x00], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, Foo::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    // Without tags, the components stay in declaration order
+    let x = Bar(7, true);
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x07, 0x80], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, Bar::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn extension() {
+    let x = Baz {
+        a: true,
+        b: SetOf(vec![2, 1]),
+        c: Some(SetOf(vec![4, 3])),
+    };
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x80, 0x02, 0x02, 0x01, 0x80, 0x80, 0x03, 0x02, 0x04, 0x03], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, Baz::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    // The sets within extension additions are sorted as well
+    let bytes = canonical(&x);
+    assert_eq!(vec![0x80, 0x02, 0x01, 0x02, 0x80, 0x80, 0x03, 0x02, 0x03, 0x04], bytes);
+    let mut d = aper::Decoder::new(&bytes);
+    let y = Baz::from_aper(&mut d, UNCONSTRAINED).unwrap();
+    assert_eq!(canonical(&x), canonical(&y));
+}
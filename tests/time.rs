@@ -0,0 +1,264 @@
+extern crate asn1;
+#[macro_use]
+extern crate asn1_derive;
+use asn1::{Date, DateTime, Duration, GeneralizedTime, ParseTimeError, TimeOfDay, UtcTime};
+use asn1::aper::{self, APerElement, DecodeError, EncodeError, UNCONSTRAINED};
+use asn1::uper::{self, UPerElement};
+use std::time::{self, UNIX_EPOCH};
+
+// Record ::= SEQUENCE {
+//     start GeneralizedTime,
+//     length DURATION OPTIONAL
+// }
+#[derive(APerElement, Debug, PartialEq)]
+struct Record {
+    start: GeneralizedTime,
+    length: Option<Duration>,
+}
+
+#[test]
+fn dates() {
+    assert!(Date::new(2024, 2, 29).is_some());
+    assert!(Date::new(2000, 2, 29).is_some());
+    assert_eq!(None, Date::new(2023, 2, 29));
+    assert_eq!(None, Date::new(1900, 2, 29));
+    assert_eq!(None, Date::new(2024, 4, 31));
+    assert_eq!(None, Date::new(2024, 13, 1));
+    assert_eq!(None, Date::new(2024, 1, 0));
+    assert_eq!(None, Date::new(10000, 1, 1));
+
+    let x: Date = "2024-03-15".parse().unwrap();
+    assert_eq!((2024, 3, 15), (x.year(), x.month(), x.day()));
+    assert_eq!("2024-03-15", x.to_string());
+    assert_eq!("0042-01-01", Date::new(42, 1, 1).unwrap().to_string());
+    for s in &["2024-3-15", "2024-03-15T", "24-03-15", "2024-02-30", "2024/03/15", ""] {
+        assert_eq!(Err(ParseTimeError), s.parse::<Date>(), "{}", s);
+    }
+}
+
+#[test]
+fn times() {
+    let x: TimeOfDay = "12:30:45".parse().unwrap();
+    assert_eq!((12, 30, 45), (x.hour(), x.minute(), x.second()));
+    assert_eq!("12:30:45", x.to_string());
+    assert!("24:00:00".parse::<TimeOfDay>().is_ok());
+    assert!("23:59:60".parse::<TimeOfDay>().is_ok());
+    for s in &["24:00:01", "12:60:00", "12:30", "12:30:45Z", "1:30:45"] {
+        assert_eq!(Err(ParseTimeError), s.parse::<TimeOfDay>(), "{}", s);
+    }
+
+    let x: DateTime = "2024-03-15T12:30:45".parse().unwrap();
+    assert_eq!(Date::new(2024, 3, 15).unwrap(), x.date());
+    assert_eq!(TimeOfDay::new(12, 30, 45).unwrap(), x.time());
+    assert_eq!("2024-03-15T12:30:45", x.to_string());
+    assert_eq!(Err(ParseTimeError), "2024-03-15 12:30:45".parse::<DateTime>());
+}
+
+#[test]
+fn durations() {
+    let x: Duration = "P1Y2M10DT2H30M".parse().unwrap();
+    assert_eq!(Duration {
+                   years: Some(1),
+                   months: Some(2),
+                   days: Some(10),
+                   hours: Some(2),
+                   minutes: Some(30),
+                   ..Duration::default()
+               },
+               x);
+    assert_eq!("P1Y2M10DT2H30M", x.to_string());
+    assert_eq!("P3W", "P3W".parse::<Duration>().unwrap().to_string());
+    assert_eq!("P0D", "P0D".parse::<Duration>().unwrap().to_string());
+
+    let x: Duration = "PT0,5S".parse().unwrap();
+    assert_eq!((Some(0), Some((1, 5))), (x.seconds, x.fraction));
+    assert_eq!("PT0.5S", x.to_string());
+    let x = Duration {
+        hours: Some(1),
+        fraction: Some((3, 5)),
+        ..Duration::default()
+    };
+    assert_eq!("PT1.005H", x.to_string());
+
+    for s in &["", "P", "PT", "P1YT", "P1M1Y", "P1.5YT1H", "P1H", "PT1D", "1Y", "P1", "P1Y1Y", "P.5Y", "PT1.S"] {
+        assert_eq!(Err(ParseTimeError), s.parse::<Duration>(), "{}", s);
+    }
+}
+
+#[test]
+fn utc_time() {
+    // The offset is removed
+    let x: UtcTime = "2403151230+0100".parse().unwrap();
+    assert_eq!("240315113000Z", x.to_string());
+    assert_eq!(UNIX_EPOCH + time::Duration::from_secs(1710502200), x.to_system_time());
+    assert_eq!(Some(x), UtcTime::from_system_time(x.to_system_time()));
+
+    // Two-digit years are from 1950 to 2049
+    assert_eq!(1999, "991231235959Z".parse::<UtcTime>().unwrap().date_time().date().year());
+    assert_eq!(1950, "500101000000Z".parse::<UtcTime>().unwrap().date_time().date().year());
+    assert_eq!(2049, "491231235959Z".parse::<UtcTime>().unwrap().date_time().date().year());
+    for s in &["4912312359-0100", "240315123045", "240230000000Z", "2403151230+01", "240315123045.5Z"] {
+        assert_eq!(Err(ParseTimeError), s.parse::<UtcTime>(), "{}", s);
+    }
+
+    assert_eq!("700101000000Z", UtcTime::from_system_time(UNIX_EPOCH).unwrap().to_string());
+    let t = UNIX_EPOCH - time::Duration::from_millis(500);
+    assert_eq!("691231235959Z", UtcTime::from_system_time(t).unwrap().to_string());
+    let t = UNIX_EPOCH + time::Duration::from_secs(2524608000); // 2050-01-01
+    assert_eq!(None, UtcTime::from_system_time(t));
+}
+
+#[test]
+fn generalized_time() {
+    let x: GeneralizedTime = "20240315123045.123Z".parse().unwrap();
+    assert_eq!(123000000, x.nanosecond());
+    assert_eq!("20240315123045.123Z", x.to_string());
+    assert_eq!("20240315120000Z", "2024031512Z".parse::<GeneralizedTime>().unwrap().to_string());
+    assert_eq!("20240315180045.5Z", "20240315123045,5-0530".parse::<GeneralizedTime>().unwrap().to_string());
+    assert_eq!("20250101003000Z", "20241231233000-01".parse::<GeneralizedTime>().unwrap().to_string());
+    assert_eq!("20240316000000Z", "20240315240000Z".parse::<GeneralizedTime>().unwrap().to_string());
+    assert_eq!("20240315123045.1Z", "20240315123045.1000000000Z".parse::<GeneralizedTime>().unwrap().to_string());
+    for s in &["202403151230.5Z", "20240315123045.1234567891Z", "20240315123045", "20240315123045.Z", "2024031512+1"] {
+        assert_eq!(Err(ParseTimeError), s.parse::<GeneralizedTime>(), "{}", s);
+    }
+
+    let date_time: DateTime = "2024-03-15T24:00:00".parse().unwrap();
+    assert_eq!(None, GeneralizedTime::new(date_time, 0));
+    let date_time: DateTime = "2024-03-15T12:00:00".parse().unwrap();
+    assert_eq!(None, GeneralizedTime::new(date_time, 1_000_000_000));
+
+    let t = UNIX_EPOCH + time::Duration::new(1710502200, 500);
+    let x = GeneralizedTime::from_system_time(t).unwrap();
+    assert_eq!("20240315113000.0000005Z", x.to_string());
+    assert_eq!(t, x.to_system_time());
+    let t = UNIX_EPOCH - time::Duration::from_millis(250);
+    let x = GeneralizedTime::from_system_time(t).unwrap();
+    assert_eq!("19691231235959.75Z", x.to_string());
+    assert_eq!(t, x.to_system_time());
+}
+
+#[test]
+fn useful_types_per() {
+    let x: UtcTime = "240315113000Z".parse().unwrap();
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    let mut expected = vec![13];
+    expected.extend(b"240315113000Z");
+    assert_eq!(expected, *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, UtcTime::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    // Characters are 7 bits in unaligned PER
+    let x: GeneralizedTime = "20240315123045.5Z".parse().unwrap();
+    let enc = x.to_uper(UNCONSTRAINED).unwrap();
+    assert_eq!(1 + (17 * 7usize).div_ceil(8), enc.bytes().len());
+    let mut d = uper::Decoder::new_unaligned(enc.bytes());
+    assert_eq!(x, GeneralizedTime::from_uper(&mut d, UNCONSTRAINED).unwrap());
+
+    // Other forms are accepted when decoding
+    let mut data = vec![15];
+    data.extend(b"2403151230+0100");
+    let mut d = aper::Decoder::new(&data);
+    assert_eq!("240315113000Z", UtcTime::from_aper(&mut d, UNCONSTRAINED).unwrap().to_string());
+
+    let mut data = vec![14];
+    data.extend(b"20240315123045");
+    let mut d = aper::Decoder::new(&data);
+    assert_eq!(Err(DecodeError::MalformedTime), GeneralizedTime::from_aper(&mut d, UNCONSTRAINED));
+}
+
+#[test]
+fn date_per() {
+    // A near-future year, which is an octet-aligned offset from 2021 in aligned PER
+    let x: Date = "2024-03-15".parse().unwrap();
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x40, 0x03, 0x27, 0x00], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, Date::from_aper(&mut d, UNCONSTRAINED).unwrap());
+    let enc = x.to_uper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x40, 0xc9, 0xc0], *enc.bytes());
+    let mut d = uper::Decoder::new_unaligned(enc.bytes());
+    assert_eq!(x, Date::from_uper(&mut d, UNCONSTRAINED).unwrap());
+
+    // An immediate year, which is 4 bits
+    let x: Date = "2010-01-01".parse().unwrap();
+    assert_eq!(vec![0x14, 0x00], *x.to_aper(UNCONSTRAINED).unwrap().bytes());
+
+    // Any other year is an unconstrained INTEGER
+    let x: Date = "1600-01-01".parse().unwrap();
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0xc0, 0x02, 0x06, 0x40, 0x00, 0x00], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, Date::from_aper(&mut d, UNCONSTRAINED).unwrap());
+    assert_eq!(vec![0xc0, 0x81, 0x90, 0x00, 0x00], *x.to_uper(UNCONSTRAINED).unwrap().bytes());
+
+    for s in &["1749-01-01", "2004-12-31", "2005-01-01", "2020-12-31", "2276-12-31", "2277-01-01", "9999-12-31"] {
+        let x: Date = s.parse().unwrap();
+        let enc = x.to_aper(UNCONSTRAINED).unwrap();
+        let mut d = aper::Decoder::new(enc.bytes());
+        assert_eq!(x, Date::from_aper(&mut d, UNCONSTRAINED).unwrap());
+    }
+
+    // Month 13 is beyond the bounds of its INTEGER, and 2010-02-31 isn't a date
+    let mut d = aper::Decoder::new(b"\x17\x00");
+    assert_eq!(Err(DecodeError::MalformedInt), Date::from_aper(&mut d, UNCONSTRAINED));
+    let mut d = aper::Decoder::new(b"\x14\x78");
+    assert_eq!(Err(DecodeError::MalformedTime), Date::from_aper(&mut d, UNCONSTRAINED));
+}
+
+#[test]
+fn time_per() {
+    let x: TimeOfDay = "12:30:45".parse().unwrap();
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x63, 0xd6, 0x80], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, TimeOfDay::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    let x: DateTime = "2010-01-01T12:30:45".parse().unwrap();
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x14, 0x00, 0xc7, 0xad], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, DateTime::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    // 24:30:00
+    let mut d = aper::Decoder::new(b"\xc3\xc0\x00");
+    assert_eq!(Err(DecodeError::MalformedTime), TimeOfDay::from_aper(&mut d, UNCONSTRAINED));
+}
+
+#[test]
+fn duration_per() {
+    // A preamble of the components present, then each as an INTEGER (0..MAX)
+    let x: Duration = "P1Y2M10DT2H30M".parse().unwrap();
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0xdc, 1, 1, 1, 2, 1, 10, 1, 2, 1, 30], *enc.bytes());
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, Duration::from_aper(&mut d, UNCONSTRAINED).unwrap());
+
+    // The number of digits of the fraction is an INTEGER (1..MAX)
+    let x: Duration = "PT0.5S".parse().unwrap();
+    let enc = x.to_uper(UNCONSTRAINED).unwrap();
+    assert_eq!(vec![0x03, 1, 0, 1, 0, 1, 5], *enc.bytes());
+    let mut d = uper::Decoder::new_unaligned(enc.bytes());
+    assert_eq!(x, Duration::from_uper(&mut d, UNCONSTRAINED).unwrap());
+
+    assert_eq!(Some(EncodeError::InvalidValue), Duration::default().to_aper(UNCONSTRAINED).err());
+    let x = Duration {
+        seconds: Some(1),
+        fraction: Some((2, 100)),
+        ..Duration::default()
+    };
+    assert_eq!(Some(EncodeError::InvalidValue), x.to_aper(UNCONSTRAINED).err());
+
+    let mut d = aper::Decoder::new(b"\x00");
+    assert_eq!(Err(DecodeError::MalformedTime), Duration::from_aper(&mut d, UNCONSTRAINED));
+}
+
+#[test]
+fn sequence() {
+    let x = Record {
+        start: "20240315123045Z".parse().unwrap(),
+        length: Some("PT90M".parse().unwrap()),
+    };
+    let enc = x.to_aper(UNCONSTRAINED).unwrap();
+    let mut d = aper::Decoder::new(enc.bytes());
+    assert_eq!(x, Record::from_aper(&mut d, UNCONSTRAINED).unwrap());
+}
@@ -0,0 +1,104 @@
+extern crate asn1;
+use asn1::{BitString, ExtensionMarker};
+use asn1::uper::{self, Constraint, Constraints, UPerElement, UNCONSTRAINED};
+
+#[test]
+fn constrained_minimal_bits() {
+    let target: Vec<u8> = vec![0xfa, 0x00];
+    let enc = uper::encode_int(1000, Some(0), Some(1000)).unwrap();
+    assert_eq!(target, *enc.bytes());
+    assert_eq!(6, enc.r_padding());
+
+    let mut d = uper::Decoder::new_unaligned(&target);
+    assert_eq!(1000, d.decode_int(Some(0), Some(1000)).unwrap());
+}
+
+#[test]
+fn constrained_single_value() {
+    let enc = uper::encode_int(7, Some(7), Some(7)).unwrap();
+    assert_eq!(0, enc.bytes().len());
+
+    let data = b"";
+    let mut d = uper::Decoder::new_unaligned(data);
+    assert_eq!(7, d.decode_int(Some(7), Some(7)).unwrap());
+}
+
+#[test]
+fn constrained_large_range() {
+    // 2^40 values need exactly 40 bits, with no length determinant
+    let target: Vec<u8> = vec![0x00, 0x00, 0x00, 0x01, 0x00];
+    let max = (1i64 << 40) - 1;
+    assert_eq!(target, *uper::encode_int(256, Some(0), Some(max)).unwrap().bytes());
+
+    let mut d = uper::Decoder::new_unaligned(&target);
+    assert_eq!(256, d.decode_int(Some(0), Some(max)).unwrap());
+}
+
+#[test]
+fn unconstrained() {
+    let target: Vec<u8> = vec![0x01, 0xd5];
+    assert_eq!(target, *uper::encode_int(-43, None, None).unwrap().bytes());
+    let mut d = uper::Decoder::new_unaligned(&target);
+    assert_eq!(-43, d.decode_int(None, None).unwrap());
+
+    let target: Vec<u8> = vec![0x02, 0x00, 0x80];
+    assert_eq!(target, *uper::encode_int(128, None, None).unwrap().bytes());
+    let mut d = uper::Decoder::new_unaligned(&target);
+    assert_eq!(128, d.decode_int(None, None).unwrap());
+}
+
+#[test]
+fn semiconstrained() {
+    let target: Vec<u8> = vec![0x02, 0x10, 0x01];
+    assert_eq!(target, *uper::encode_int(4096, Some(-1), None).unwrap().bytes());
+    let mut d = uper::Decoder::new_unaligned(&target);
+    assert_eq!(4096, d.decode_int(Some(-1), None).unwrap());
+}
+
+#[test]
+fn unaligned_fields() {
+    // 1 bit extension marker, 1 bit bool, 16 bit u16, all packed without alignment
+    let mut enc = (false as ExtensionMarker).to_uper(UNCONSTRAINED).unwrap();
+    enc.append(&true.to_uper(UNCONSTRAINED).unwrap()).unwrap();
+    enc.append(&0xf93bu16.to_uper(UNCONSTRAINED).unwrap()).unwrap();
+    let target: Vec<u8> = vec![0x7e, 0x4e, 0xc0];
+    assert_eq!(target, *enc.bytes());
+    assert_eq!(6, enc.r_padding());
+
+    let mut d = uper::Decoder::new_unaligned(&target);
+    assert!(!ExtensionMarker::from_uper(&mut d, UNCONSTRAINED).unwrap());
+    assert!(bool::from_uper(&mut d, UNCONSTRAINED).unwrap());
+    assert_eq!(0xf93b, u16::from_uper(&mut d, UNCONSTRAINED).unwrap());
+}
+
+#[test]
+fn sequence_of_i8() {
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(None, Some(3))),
+    };
+    let v: Vec<i8> = vec![-1, 0, 1];
+    // A 2-bit length, then the items with no padding
+    let target: Vec<u8> = vec![0xdf, 0xe0, 0x20, 0x40];
+    assert_eq!(target, *v.to_uper(sz).unwrap().bytes());
+
+    let mut d = uper::Decoder::new_unaligned(&target);
+    assert_eq!(v, Vec::<i8>::from_uper(&mut d, sz).unwrap());
+}
+
+#[test]
+fn bit_string() {
+    let sz = Constraints {
+        value: None,
+        size: Some(Constraint::new(Some(4), Some(4))),
+    };
+    let b = BitString::with_bytes_and_len(&[0xe0], 4);
+    let target: Vec<u8> = vec![0xe0];
+    assert_eq!(target, *b.to_uper(sz).unwrap().bytes());
+
+    let mut d = uper::Decoder::new_unaligned(&target);
+    let decoded = BitString::from_uper(&mut d, sz).unwrap();
+    for i in 0..4 {
+        assert_eq!(b.is_set(i), decoded.is_set(i));
+    }
+}